@@ -1,14 +1,92 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use time::Date;
 
 #[derive(Parser)]
 #[command(version, about="a bullet journal for your terminal", long_about = None)]
 pub struct Cli {
     /// the path to a sqlite database to use instead of the default
-    #[arg(short, long, conflicts_with = "ephemeral")]
+    #[arg(short, long, conflicts_with = "ephemeral", global = true)]
     pub database_path: Option<PathBuf>,
+    /// the path to a directory of markdown files, one per day, to use instead of a database
+    #[arg(short, long, conflicts_with_all = ["ephemeral", "database_path"], global = true)]
+    pub markdown_dir: Option<PathBuf>,
     /// use an in-memory model rather than a database backed model
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, global = true)]
     pub ephemeral: bool,
+    /// the markdown file of page templates that fill empty days, instead of
+    /// templates.md in the config directory
+    #[arg(long)]
+    pub templates: Option<PathBuf>,
+    /// also copy yanked entries to the system clipboard, using an OSC 52
+    /// escape sequence that the terminal passes on
+    #[arg(long, default_value_t = false)]
+    pub osc52: bool,
+    /// how many days deleted entries are kept in the trash before they are
+    /// deleted for good
+    #[arg(long, default_value_t = 30)]
+    pub trash_days: u32,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// write the entries in a date range to a file or stdout
+    Export {
+        /// the format to export entries as
+        #[arg(short, long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        /// the first date to export, formatted as YYYY-MM-DD; defaults to today
+        #[arg(long, value_parser = parse_date)]
+        from: Option<Date>,
+        /// the last date to export, formatted as YYYY-MM-DD; defaults to the first date
+        #[arg(long, value_parser = parse_date)]
+        to: Option<Date>,
+        /// the file to write to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// add the entries in a previously exported file to the journal
+    Import {
+        /// the format of the file being imported
+        #[arg(short, long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        /// the file to import
+        file: PathBuf,
+    },
+    /// play a vhs tape's key presses against an empty in-memory journal, then
+    /// print the final screen and the resulting journal
+    Replay {
+        /// the tape to replay
+        file: PathBuf,
+        /// the date to start on, formatted as YYYY-MM-DD; defaults to today
+        #[arg(long, value_parser = parse_date)]
+        date: Option<Date>,
+        /// the width of the headless terminal
+        #[arg(long, default_value_t = 80)]
+        width: u16,
+        /// the height of the headless terminal
+        #[arg(long, default_value_t = 24)]
+        height: u16,
+    },
+    /// merge the tasks in a todo.txt file with the journal in both directions
+    Sync {
+        /// the todo.txt file to sync with
+        file: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Markdown,
+    /// iCalendar, with events as VEVENTs and tasks as VTODOs
+    Ics,
+    /// todo.txt, which only includes tasks
+    Todotxt,
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    crate::interchange::parse_date(s).map_err(|e| e.to_string())
 }
@@ -0,0 +1,50 @@
+//! Copies text to the system clipboard with an OSC 52 escape sequence, which
+//! the terminal passes on to the clipboard. This works over ssh and needs no
+//! clipboard service, but terminals that do not support OSC 52 ignore it.
+
+use std::io::{self, Write};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text).as_bytes())?;
+    stdout.flush()
+}
+
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, x)| n | u32::from(*x) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("- ○ rest".as_bytes()), "LSDil4sgcmVzdA==");
+        assert_eq!(osc52("foo"), "\x1b]52;c;Zm9v\x07");
+    }
+}
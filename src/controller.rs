@@ -2,16 +2,17 @@ use std::io;
 use std::time::Duration;
 
 use crossterm::event::{self, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::backend::Backend;
 
 use crate::view::View;
 
-pub struct Controller {
-    view: View,
+pub struct Controller<B: Backend> {
+    view: View<B>,
     should_exit: bool,
 }
 
-impl Controller {
-    pub fn new(view: View) -> Self {
+impl<B: Backend> Controller<B> {
+    pub fn new(view: View<B>) -> Self {
         Self {
             view,
             should_exit: false,
@@ -26,20 +27,49 @@ impl Controller {
         Ok(())
     }
 
+    /// Renders and handles each event in turn, as if they had been typed, then
+    /// renders the final state. Stops early if an event quits jotty.
+    pub fn replay(&mut self, events: impl IntoIterator<Item = event::Event>) -> io::Result<()> {
+        for event in events {
+            if self.should_exit {
+                break;
+            }
+            self.view.render()?;
+            self.handle(event);
+        }
+        self.view.render()
+    }
+
+    pub fn view(&self) -> &View<B> {
+        &self.view
+    }
+
     fn update(&mut self) -> io::Result<()> {
-        self.single_update()?; // blocking so that updates do not go out spuriously
+        self.handle(event::read()?); // blocking so that updates do not go out spuriously
         while event::poll(Duration::ZERO)? {
-            self.single_update()?;
+            self.handle(event::read()?);
         }
         Ok(())
     }
 
-    fn single_update(&mut self) -> io::Result<()> {
-        match event::read()? {
+    fn handle(&mut self, event: event::Event) {
+        match event {
             event::Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 match key_event.code {
-                    KeyCode::Up => self.view.move_up(),
-                    KeyCode::Down => self.view.move_down(),
+                    KeyCode::Up => {
+                        if key_event.modifiers.contains(KeyModifiers::ALT) {
+                            self.view.shift_up();
+                        } else {
+                            self.view.move_up();
+                        }
+                    }
+                    KeyCode::Down => {
+                        if key_event.modifiers.contains(KeyModifiers::ALT) {
+                            self.view.shift_down();
+                        } else {
+                            self.view.move_down();
+                        }
+                    }
                     KeyCode::Left => {
                         if key_event.modifiers.contains(KeyModifiers::SHIFT) {
                             self.view.move_to_prev();
@@ -58,8 +88,10 @@ impl Controller {
                             self.view.move_right();
                         }
                     }
+                    KeyCode::Tab => self.view.indent(),
+                    KeyCode::BackTab => self.view.outdent(),
                     KeyCode::Backspace => self.view.delete_char(),
-                    KeyCode::Enter => self.view.toggle_editing_mode(),
+                    KeyCode::Enter => self.view.select(),
                     KeyCode::Esc => self.view.exit_mode(),
                     KeyCode::Char(c) => {
                         if self.view.is_editing() {
@@ -68,7 +100,34 @@ impl Controller {
                             match c {
                                 'q' => self.should_exit = true,
                                 'h' => self.view.toggle_help(),
+                                '#' => self.view.toggle_tags(),
+                                'a' => self.view.toggle_agenda(),
+                                'o' => self.view.toggle_due(),
+                                'r' => self.view.toggle_recurring(),
+                                'u' => self.view.toggle_trash(),
+                                'S' => self.view.toggle_stats(),
+                                'Y' => self.view.toggle_heatmap(),
+                                'x' => self.view.toggle_habits(),
+                                'M' => self.view.cycle_mood(),
+                                'E' => self.view.cycle_energy(),
+                                'R' => self.view.reflect(),
+                                'T' => self.view.toggle_moods(),
+                                'j' => self.view.toggle_journal(),
+                                'O' => self.view.open_in_editor(),
+                                'w' => self.view.edit_in_editor(),
+                                '/' => self.view.toggle_search(),
+                                '1'..='9' => self.view.toggle_habit(c as usize - '1' as usize),
+                                'p' => self.view.apply_page_templates(),
+                                'z' => self.view.toggle_collapsed(),
+                                'i' => self.view.toggle_details(),
+                                '>' => self.view.send_to_tomorrow(),
+                                'm' => self.view.toggle_moving(),
+                                's' => self.view.toggle_mark(),
+                                'y' => self.view.yank(),
+                                'v' => self.view.paste(true),
+                                'V' => self.view.paste(false),
                                 ' ' => self.view.cycle(),
+                                '!' => self.view.cycle_importance(),
                                 'c' => self.view.move_to_today(),
                                 'n' => {
                                     self.view.insert_new_item();
@@ -85,6 +144,5 @@ impl Controller {
             }
             _ => {}
         }
-        Ok(())
     }
 }
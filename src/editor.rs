@@ -0,0 +1,99 @@
+//! Hands the terminal over to the user's editor, `$VISUAL` or `$EDITOR`, to
+//! change text in a temporary file, then takes the terminal back.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, process};
+
+use crossterm::execute;
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+
+/// Edits a file and returns once the edit is done.
+pub type Editor = fn(&Path) -> io::Result<()>;
+
+/// Runs the user's editor on `path`, with the terminal out of raw mode and
+/// off the alternate screen for as long as it runs.
+pub fn open(path: &Path) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    let status = command(path).status();
+    // the terminal is taken back even if the editor could not be started
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+
+    let status = status?;
+    if !status.success() {
+        return Err(io::Error::other(format!("the editor exited with {status}")));
+    }
+    Ok(())
+}
+
+/// The command in `$VISUAL`, or else `$EDITOR`, or else `vi`. It may carry
+/// its own arguments, like `code --wait`.
+fn command(path: &Path) -> Command {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|x| env::var(x).ok())
+        .find(|x| !x.trim().is_empty())
+        .unwrap_or("vi".to_string());
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().expect("the editor is not blank"));
+    command.args(words).arg(path);
+    command
+}
+
+/// Writes `text` to a new temporary file, has `editor` change it, and reads
+/// it back. The file's name ends in `suffix`, like `.md`, so that editors
+/// pick the right syntax.
+pub fn edit(editor: Editor, text: &str, suffix: &str) -> io::Result<String> {
+    let path = create(text, suffix)?;
+    let edited = editor(&path).and_then(|()| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    edited
+}
+
+/// Creates a temporary file that only the user can read, never reusing one
+/// that is already there.
+fn create(text: &str, suffix: &str) -> io::Result<PathBuf> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut attempt = 0;
+    loop {
+        let path = env::temp_dir().join(format!("jotty-{}-{attempt}{suffix}", process::id()));
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(text.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_a_temporary_file() {
+        fn append(path: &Path) -> io::Result<()> {
+            let text = fs::read_to_string(path)?;
+            assert!(path.to_string_lossy().ends_with(".md"));
+            fs::write(path, format!("{text} and after\n"))
+        }
+        fn fail(_: &Path) -> io::Result<()> {
+            Err(io::Error::other("no editor"))
+        }
+
+        assert_eq!(edit(append, "before", ".md").unwrap(), "before and after\n");
+        assert!(edit(fail, "before", ".md").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+pub mod ical;
+pub mod markdown;
+pub mod todotxt;
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use time::{Date, Month};
+
+pub fn parse_date(s: &str) -> Result<Date> {
+    let mut parts = s.trim().splitn(3, '-');
+    let mut next = || {
+        parts.next().ok_or(anyhow!(
+            "expected a date formatted as YYYY-MM-DD, got `{s}`"
+        ))
+    };
+
+    let year = next()?.parse()?;
+    let month = Month::try_from(next()?.parse::<u8>()?)?;
+    let day = next()?.parse()?;
+    Ok(Date::from_calendar_date(year, month, day)?)
+}
+
+pub fn dates(from: Date, to: Date) -> impl Iterator<Item = Date> {
+    std::iter::successors(Some(from), |d| d.next_day()).take_while(move |d| *d <= to)
+}
+
+/// Writes a finished temporary file next to `path`, then renames it over
+/// `path`, so that other programs never see a half written file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = path.parent().expect("files are always in a directory");
+    fs::create_dir_all(dir)?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
@@ -0,0 +1,572 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use anyhow::{Result, anyhow, bail};
+use time::{Date, Month, OffsetDateTime, Time};
+
+use super::dates;
+use crate::model::{CompletionLevel, Event, Importance, Model, Task};
+
+const PRODID: &str = "-//w13n//jotty//EN";
+
+#[derive(Debug, Clone)]
+pub enum Component {
+    Event(Date, Event),
+    Task(Date, Task),
+}
+
+/// A parsed component, with its UID and the UID in its `RELATED-TO`.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub uid: Option<String>,
+    pub related_to: Option<String>,
+    pub component: Component,
+}
+
+pub fn export(model: &dyn Model, from: Date, to: Date, out: &mut impl Write) -> Result<()> {
+    let now = OffsetDateTime::now_utc();
+    let stamp = format!(
+        "{}T{:02}{:02}{:02}Z",
+        format_date(now.date()),
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+
+    write_line(out, "BEGIN:VCALENDAR")?;
+    write_line(out, "VERSION:2.0")?;
+    write_line(out, &format!("PRODID:{PRODID}"))?;
+    for date in dates(from, to) {
+        for event in model.events_iter(date) {
+            write_line(out, "BEGIN:VEVENT")?;
+            write_line(out, &format!("UID:{}", event_uid(date, &event)))?;
+            write_line(out, &format!("DTSTAMP:{stamp}"))?;
+            if let Some(start) = event.start {
+                let date = format_date(date);
+                write_line(out, &format!("DTSTART:{date}T{}", format_time(start)))?;
+                if let Some(end) = event.end {
+                    write_line(out, &format!("DTEND:{date}T{}", format_time(end)))?;
+                }
+            } else {
+                write_line(out, &format!("DTSTART;VALUE=DATE:{}", format_date(date)))?;
+                if let Some(next) = date.next_day() {
+                    write_line(out, &format!("DTEND;VALUE=DATE:{}", format_date(next)))?;
+                }
+            }
+            write_line(out, &format!("SUMMARY:{}", escape(&event.title)))?;
+            if let Importance::High = event.importance {
+                write_line(out, "PRIORITY:1")?;
+            }
+            write_line(out, "END:VEVENT")?;
+        }
+        let mut parents: Vec<String> = Vec::new();
+        for task in model.tasks_iter(date) {
+            let uid = task_uid(date, &task);
+            parents.truncate(task.depth.into());
+            let status = match task.completion_level {
+                CompletionLevel::None => "NEEDS-ACTION",
+                CompletionLevel::Partial => "IN-PROCESS",
+                CompletionLevel::Full => "COMPLETED",
+            };
+            write_line(out, "BEGIN:VTODO")?;
+            write_line(out, &format!("UID:{uid}"))?;
+            write_line(out, &format!("DTSTAMP:{stamp}"))?;
+            write_line(out, &format!("DTSTART;VALUE=DATE:{}", format_date(date)))?;
+            if let Some(parent) = parents.last() {
+                write_line(out, &format!("RELATED-TO:{parent}"))?;
+            }
+            if let Some(due) = task.due {
+                write_line(out, &format!("DUE;VALUE=DATE:{}", format_date(due)))?;
+            }
+            write_line(out, &format!("SUMMARY:{}", escape(&task.title)))?;
+            write_line(out, &format!("STATUS:{status}"))?;
+            if let Importance::High = task.importance {
+                write_line(out, "PRIORITY:1")?;
+            }
+            write_line(out, "END:VTODO")?;
+            parents.push(uid);
+        }
+        model.err()?;
+    }
+    write_line(out, "END:VCALENDAR")?;
+    Ok(())
+}
+
+/// Adds every event and task in `input` to the model, keeping their UIDs.
+/// Components whose UID matches an entry already in the journal, on any
+/// date, or one seen earlier in the file, are skipped, so importing the same
+/// file twice does not duplicate entries. A task related to a task on the
+/// same date becomes its subtask.
+pub fn import(model: &mut dyn Model, input: &str) -> Result<usize> {
+    let mut imported = 0;
+    let mut seen = HashSet::new();
+    for date in model.dates() {
+        let events = model.events_iter(date).map(|x| x.uid);
+        seen.extend(events.chain(model.tasks_iter(date).map(|x| x.uid)));
+    }
+    seen.remove("");
+    model.err()?;
+    let mut depths = HashMap::new();
+
+    for Parsed {
+        uid,
+        related_to,
+        component,
+    } in parse(input)?
+    {
+        if let Some(uid) = &uid
+            && !seen.insert(uid.clone())
+        {
+            continue;
+        }
+
+        match component {
+            Component::Event(date, event) => {
+                let idx = model.events_len(date);
+                model.new_event(date, idx)?;
+                model.replace_event(date, idx, event)?;
+            }
+            Component::Task(date, mut task) => {
+                let idx = model.tasks_len(date);
+                let deepest = match idx {
+                    0 => 0,
+                    _ => model.get_task(date, idx - 1)?.depth.saturating_add(1),
+                };
+                task.depth = related_to
+                    .and_then(|x| depths.get(&(date, x)))
+                    .map_or(0, |x: &u8| x.saturating_add(1).min(deepest));
+                if let Some(uid) = uid {
+                    depths.insert((date, uid), task.depth);
+                }
+                model.new_task(date, idx)?;
+                model.replace_task(date, idx, task)?;
+            }
+        }
+        model.err()?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
+/// Parses the VEVENT and VTODO components of a calendar, along with their
+/// UIDs and the UIDs they are related to. Times are truncated to their dates,
+/// and components without a start or due date are skipped.
+pub fn parse(input: &str) -> Result<Vec<Parsed>> {
+    let mut components = Vec::new();
+    let mut current: Option<Properties> = None;
+
+    for line in unfold(input) {
+        let (name, value) = split_property(&line)?;
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT" | "VTODO") => {
+                current = Some(Properties {
+                    kind: value.to_string(),
+                    ..Default::default()
+                });
+            }
+            ("END", "VEVENT" | "VTODO") => {
+                if let Some(props) = current.take()
+                    && let Some(component) = props.to_component()
+                {
+                    components.push(Parsed {
+                        uid: props.uid,
+                        related_to: props.related_to,
+                        component,
+                    });
+                }
+            }
+            _ => {
+                if let Some(props) = &mut current {
+                    props.set(&name, value)?;
+                }
+            }
+        }
+    }
+    Ok(components)
+}
+
+#[derive(Default)]
+struct Properties {
+    kind: String,
+    uid: Option<String>,
+    related_to: Option<String>,
+    summary: String,
+    date: Option<Date>,
+    start: Option<Time>,
+    end: Option<Time>,
+    due: Option<Date>,
+    priority: Option<u8>,
+    status: Option<String>,
+}
+
+impl Properties {
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        match name {
+            "UID" => self.uid = Some(value.to_string()),
+            "RELATED-TO" => self.related_to = Some(value.to_string()),
+            "SUMMARY" => self.summary = unescape(value).replace('\n', " "),
+            "DTSTART" => {
+                self.date = Some(parse_date_value(value)?);
+                self.start = parse_time_value(value)?;
+            }
+            "DTEND" => self.end = parse_time_value(value)?,
+            "DUE" => self.due = Some(parse_date_value(value)?),
+            "PRIORITY" => self.priority = value.trim().parse().ok(),
+            "STATUS" => self.status = Some(value.trim().to_uppercase()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn to_component(&self) -> Option<Component> {
+        let date = self.date.or(self.due)?;
+        let importance = match self.priority {
+            Some(1..=4) => Importance::High,
+            _ => Importance::Normal,
+        };
+        if self.kind == "VEVENT" {
+            Some(Component::Event(
+                date,
+                Event {
+                    title: self.summary.clone(),
+                    importance,
+                    start: self.start,
+                    end: self
+                        .end
+                        .filter(|end| self.start.is_some_and(|start| *end >= start)),
+                    uid: self.uid.clone().unwrap_or_default(),
+                },
+            ))
+        } else {
+            Some(Component::Task(
+                date,
+                Task {
+                    title: self.summary.clone(),
+                    completion_level: match self.status.as_deref() {
+                        Some("COMPLETED") => CompletionLevel::Full,
+                        Some("IN-PROCESS") => CompletionLevel::Partial,
+                        _ => CompletionLevel::None,
+                    },
+                    importance,
+                    due: self.due,
+                    depth: 0,
+                    uid: self.uid.clone().unwrap_or_default(),
+                },
+            ))
+        }
+    }
+}
+
+/// The event's UID, or for one the model has not given a UID, like an
+/// occurrence of a recurring template, one made from its date and title.
+fn event_uid(date: Date, event: &Event) -> String {
+    if !event.uid.is_empty() {
+        return event.uid.clone();
+    }
+    format!(
+        "event-{}-{:016x}@jotty",
+        format_date(date),
+        fnv1a(&event.title)
+    )
+}
+
+/// The task's UID, made like an event's when it has none.
+fn task_uid(date: Date, task: &Task) -> String {
+    if !task.uid.is_empty() {
+        return task.uid.clone();
+    }
+    format!(
+        "task-{}-{:016x}@jotty",
+        format_date(date),
+        fnv1a(&task.title)
+    )
+}
+
+/// A stable hash, so that UIDs do not change between builds of jotty.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn format_date(date: Date) -> String {
+    format!(
+        "{:04}{:02}{:02}",
+        date.year(),
+        date.month() as u8,
+        date.day()
+    )
+}
+
+fn parse_date_value(value: &str) -> Result<Date> {
+    let err = || anyhow!("invalid date `{value}`");
+    let digits = value
+        .get(..8)
+        .filter(|x| x.bytes().all(|x| x.is_ascii_digit()))
+        .ok_or_else(err)?;
+    let year = digits[..4].parse().map_err(|_| err())?;
+    let month: u8 = digits[4..6].parse().map_err(|_| err())?;
+    let day = digits[6..].parse().map_err(|_| err())?;
+    Ok(Date::from_calendar_date(
+        year,
+        Month::try_from(month)?,
+        day,
+    )?)
+}
+
+fn format_time(time: Time) -> String {
+    format!("{:02}{:02}{:02}", time.hour(), time.minute(), time.second())
+}
+
+/// The time of a DATE-TIME value like `20251014T143000`, or `None` for a DATE
+/// value. Times are read as written, ignoring any time zone and seconds.
+fn parse_time_value(value: &str) -> Result<Option<Time>> {
+    let Some((_, time)) = value.split_once('T') else {
+        return Ok(None);
+    };
+    let err = || anyhow!("invalid time `{value}`");
+    let digits = time
+        .get(..4)
+        .filter(|x| x.bytes().all(|x| x.is_ascii_digit()))
+        .ok_or_else(err)?;
+    let hour = digits[..2].parse().map_err(|_| err())?;
+    let minute = digits[2..].parse().map_err(|_| err())?;
+    Ok(Some(Time::from_hms(hour, minute, 0)?))
+}
+
+fn split_property(line: &str) -> Result<(String, &str)> {
+    let (head, value) = line
+        .split_once(':')
+        .ok_or(anyhow!("invalid content line `{line}`"))?;
+    let name = head.split(';').next().unwrap_or_default();
+    if name.is_empty() {
+        bail!("invalid content line `{line}`");
+    }
+    Ok((name.to_uppercase(), value))
+}
+
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(rest);
+        } else if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+/// Writes a content line, folding it so that no line is longer than 75 octets.
+fn write_line(out: &mut impl Write, line: &str) -> Result<()> {
+    let mut start = 0;
+    let mut limit = 75;
+    for (idx, c) in line.char_indices() {
+        if idx + c.len_utf8() - start > limit {
+            write!(out, "{}\r\n ", &line[start..idx])?;
+            start = idx;
+            limit = 74;
+        }
+    }
+    write!(out, "{}\r\n", &line[start..])?;
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::MemModel;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/calendar.ics");
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn imports_fixture() {
+        let mut model = MemModel::default();
+        assert_eq!(import(&mut model, FIXTURE).unwrap(), 4);
+
+        let events: Vec<Event> = model.events_iter(date(2025, Month::October, 14)).collect();
+        assert_eq!(
+            events,
+            [
+                Event {
+                    title: "Quarterly planning, part 1".to_string(),
+                    importance: Importance::High,
+                    start: Some(Time::from_hms(9, 30, 0).unwrap()),
+                    end: Some(Time::from_hms(10, 0, 0).unwrap()),
+                    uid: "19970610T172345Z-AF23B2@example.com".to_string(),
+                },
+                Event {
+                    title: "Conference day 3 with a very long title that the calendar app folded over two lines".to_string(),
+                    uid: "allday-0001@example.com".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let tasks: Vec<Task> = model.tasks_iter(date(2025, Month::October, 15)).collect();
+        assert_eq!(
+            tasks,
+            [
+                Task {
+                    title: "Send slides".to_string(),
+                    completion_level: CompletionLevel::Full,
+                    due: Some(date(2025, Month::October, 15)),
+                    uid: "todo-0001@example.com".to_string(),
+                    ..Default::default()
+                },
+                Task {
+                    title: "Book train; return trip".to_string(),
+                    completion_level: CompletionLevel::Partial,
+                    uid: "todo-0002@example.com".to_string(),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reimport_does_not_duplicate() {
+        let mut model = MemModel::default();
+        import(&mut model, FIXTURE).unwrap();
+        assert_eq!(import(&mut model, FIXTURE).unwrap(), 0);
+        assert_eq!(model.events_len(date(2025, Month::October, 14)), 2);
+        assert_eq!(model.tasks_len(date(2025, Month::October, 15)), 2);
+
+        let moved_to = date(2025, Month::October, 20);
+        model
+            .move_event(date(2025, Month::October, 14), 0, moved_to, 0)
+            .unwrap();
+        model
+            .move_task(date(2025, Month::October, 15), 1, moved_to, 0)
+            .unwrap();
+        assert_eq!(import(&mut model, FIXTURE).unwrap(), 0);
+        assert_eq!(model.events_len(date(2025, Month::October, 14)), 1);
+        assert_eq!(model.tasks_len(date(2025, Month::October, 15)), 1);
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let mut model = MemModel::default();
+        import(&mut model, FIXTURE).unwrap();
+        let from = date(2025, Month::October, 14);
+        let to = date(2025, Month::October, 15);
+
+        let mut out = Vec::new();
+        export(&model, from, to, &mut out).unwrap();
+        let exported = String::from_utf8(out).unwrap();
+        assert!(exported.lines().all(|x| x.len() <= 76));
+
+        let mut reimported = MemModel::default();
+        assert_eq!(import(&mut reimported, &exported).unwrap(), 4);
+        for d in dates(from, to) {
+            assert!(model.events_iter(d).eq(reimported.events_iter(d)));
+            assert!(model.tasks_iter(d).eq(reimported.tasks_iter(d)));
+        }
+        assert_eq!(import(&mut model, &exported).unwrap(), 0);
+    }
+
+    #[test]
+    fn subtasks_are_related_to_their_parents() {
+        let day = date(2025, Month::October, 15);
+        let mut model = MemModel::default();
+        for (i, depth) in [0, 1, 2, 1, 0].into_iter().enumerate() {
+            model.new_task(day, i).unwrap();
+            let task = Task {
+                title: format!("task {i}"),
+                depth,
+                ..Default::default()
+            };
+            model.replace_task(day, i, task).unwrap();
+        }
+
+        let mut out = Vec::new();
+        export(&model, day, day, &mut out).unwrap();
+        let exported = String::from_utf8(out).unwrap();
+        assert_eq!(exported.matches("RELATED-TO:").count(), 3);
+
+        let mut reimported = MemModel::default();
+        import(&mut reimported, &exported).unwrap();
+        assert!(model.tasks_iter(day).eq(reimported.tasks_iter(day)));
+    }
+
+    #[test]
+    fn uids_are_kept_through_edits() {
+        let day = date(2025, Month::October, 15);
+        let mut model = MemModel::default();
+        for i in 0..2 {
+            model.new_task(day, i).unwrap();
+            let task = Task {
+                title: "water plants".to_string(),
+                ..Default::default()
+            };
+            model.replace_task(day, i, task).unwrap();
+        }
+
+        let mut out = Vec::new();
+        export(&model, day, day, &mut out).unwrap();
+        let exported = String::from_utf8(out).unwrap();
+        let mut reimported = MemModel::default();
+        assert_eq!(import(&mut reimported, &exported).unwrap(), 2);
+        assert!(model.tasks_iter(day).eq(reimported.tasks_iter(day)));
+
+        let task = model.get_task(day, 0).unwrap();
+        let edited = Task {
+            title: "water the plants".to_string(),
+            ..task.clone()
+        };
+        model.replace_task(day, 0, edited).unwrap();
+        let mut out = Vec::new();
+        export(&model, day, day, &mut out).unwrap();
+        let exported = String::from_utf8(out).unwrap();
+        assert!(exported.contains(&format!("UID:{}", task.uid)));
+    }
+
+    #[test]
+    fn rejects_dates_that_are_not_digits() {
+        let calendar =
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:202é1014T0930\nEND:VEVENT\nEND:VCALENDAR\n";
+        assert!(parse(calendar).is_err());
+        let calendar =
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:20251014T0é30\nEND:VEVENT\nEND:VCALENDAR\n";
+        assert!(parse(calendar).is_err());
+    }
+}
@@ -0,0 +1,654 @@
+use std::io::Write;
+
+use anyhow::{Error, Result, anyhow};
+use time::Date;
+
+use super::{dates, parse_date};
+use crate::model::{
+    CompletionLevel, Entry, Event, Importance, Model, Mood, Task, Template, Trashed, split_due,
+    split_times,
+};
+
+#[derive(Default, Debug, Clone)]
+pub struct Day {
+    pub date: Option<Date>,
+    pub events: Vec<Event>,
+    pub tasks: Vec<Task>,
+    pub mood: Mood,
+    pub journal: String,
+    /// sections under headings other than these, like `## Notes`, as written
+    pub other: String,
+}
+
+impl Day {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+            && self.tasks.is_empty()
+            && self.mood.is_empty()
+            && self.journal.is_empty()
+            && self.other.is_empty()
+    }
+}
+
+enum Section {
+    None,
+    Events,
+    Tasks,
+    Journal,
+    Other,
+}
+
+pub fn export(model: &dyn Model, from: Date, to: Date, out: &mut impl Write) -> Result<()> {
+    let mut first = true;
+    for date in dates(from, to) {
+        // UIDs are left out, since exports are for people to read
+        let day = Day {
+            date: Some(date),
+            events: model
+                .events_iter(date)
+                .map(|x| Event {
+                    uid: String::new(),
+                    ..x
+                })
+                .collect(),
+            tasks: model
+                .tasks_iter(date)
+                .map(|x| Task {
+                    uid: String::new(),
+                    ..x
+                })
+                .collect(),
+            mood: model.mood(date),
+            journal: model.journal(date),
+            ..Default::default()
+        };
+        model.err()?;
+
+        if !day.is_empty() {
+            if !first {
+                writeln!(out)?;
+            }
+            write_day(&day, out)?;
+            first = false;
+        }
+    }
+    Ok(())
+}
+
+/// Imports days written by [`export`]. Each day in the input replaces the
+/// entries, mood and journal of that day, so a week can be exported, edited
+/// and imported again. Entries keep their place, and with it their UID, when
+/// the input has none for them.
+pub fn import(model: &mut dyn Model, input: &str) -> Result<usize> {
+    let mut imported = 0;
+    for day in parse(input)? {
+        let date = day
+            .date
+            .ok_or(anyhow!("entries must be under a date heading"))?;
+        let (events, tasks) = (day.events.len(), day.tasks.len());
+        for (idx, event) in day.events.into_iter().enumerate() {
+            if idx == model.events_len(date) {
+                model.new_event(date, idx)?;
+            }
+            model.replace_event(date, idx, event)?;
+        }
+        while model.events_len(date) > events {
+            model.delete_event(date, events)?;
+            model.err()?;
+        }
+        for (idx, task) in day.tasks.into_iter().enumerate() {
+            if idx == model.tasks_len(date) {
+                model.new_task(date, idx)?;
+            }
+            model.replace_task(date, idx, task)?;
+        }
+        while model.tasks_len(date) > tasks {
+            model.delete_task(date, tasks)?;
+            model.err()?;
+        }
+        imported += events + tasks;
+        model.set_mood(date, day.mood)?;
+        model.set_journal(date, &day.journal)?;
+        model.err()?;
+    }
+    Ok(imported)
+}
+
+pub fn write_day(day: &Day, out: &mut impl Write) -> Result<()> {
+    if let Some(date) = day.date {
+        writeln!(out, "# {date}")?;
+        writeln!(out)?;
+    }
+    let mut started = false;
+
+    if !day.mood.is_empty() {
+        gap(out, &mut started)?;
+        if let Some(mood) = day.mood.mood {
+            writeln!(out, "Mood: {mood}/{}", Mood::MAX_RATING)?;
+        }
+        if let Some(energy) = day.mood.energy {
+            writeln!(out, "Energy: {energy}/{}", Mood::MAX_RATING)?;
+        }
+        if !day.mood.reflection.is_empty() {
+            writeln!(out, "> {}", day.mood.reflection)?;
+        }
+    }
+
+    if !day.events.is_empty() {
+        gap(out, &mut started)?;
+        writeln!(out, "## Events")?;
+        writeln!(out)?;
+        for event in &day.events {
+            writeln!(out, "- {}", format_event(event))?;
+        }
+    }
+
+    if !day.tasks.is_empty() {
+        gap(out, &mut started)?;
+        writeln!(out, "## Tasks")?;
+        writeln!(out)?;
+        for task in &day.tasks {
+            let indent = "  ".repeat(task.depth.into());
+            writeln!(out, "{indent}- {}", format_task(task))?;
+        }
+    }
+
+    if !day.journal.is_empty() {
+        gap(out, &mut started)?;
+        writeln!(out, "## Journal")?;
+        writeln!(out)?;
+        for line in day.journal.lines() {
+            // a line that looks like a heading would end the journal
+            if line.starts_with(['#', '\\']) {
+                write!(out, "\\")?;
+            }
+            writeln!(out, "{line}")?;
+        }
+    }
+
+    if !day.other.is_empty() {
+        gap(out, &mut started)?;
+        writeln!(out, "{}", day.other)?;
+    }
+    Ok(())
+}
+
+/// Writes the blank line between two parts of a day.
+fn gap(out: &mut impl Write, started: &mut bool) -> Result<()> {
+    if *started {
+        writeln!(out)?;
+    }
+    *started = true;
+    Ok(())
+}
+
+/// Writes recurring templates as a list, one template per item, like
+/// `- event DTSTART=20251014;FREQ=DAILY 09:00 standup`.
+pub fn write_templates(templates: &[Template], out: &mut impl Write) -> Result<()> {
+    writeln!(out, "# Recurring")?;
+    writeln!(out)?;
+    for template in templates {
+        let (kind, item) = match &template.entry {
+            Entry::Event(event) => ("event", format_event(event)),
+            Entry::Task(task) => ("task", format_task(task)),
+        };
+        writeln!(out, "- {kind} {} {item}", template.recurrence)?;
+    }
+    Ok(())
+}
+
+/// Parses templates written by [`write_templates`].
+pub fn parse_templates(input: &str) -> Result<Vec<Template>> {
+    let mut templates = Vec::new();
+    for (line_no, line) in input.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+
+        if line.trim().is_empty() || line.starts_with("# ") {
+            continue;
+        }
+        let item = line
+            .strip_prefix("- ")
+            .ok_or(err("expected a `- ` list item"))?;
+        let (kind, item) = item.split_once(' ').unwrap_or((item, ""));
+        let (recurrence, item) = item.split_once(' ').unwrap_or((item, ""));
+        let recurrence = recurrence.parse().map_err(|e: Error| err(&e.to_string()))?;
+        let entry = match kind {
+            "event" => Entry::Event(parse_event(item)),
+            "task" => Entry::Task(parse_task(item)),
+            _ => return Err(err("expected an `event` or `task` item")),
+        };
+        templates.push(Template { recurrence, entry });
+    }
+    Ok(templates)
+}
+
+/// Writes the trash as a list, one entry per item, with where and when it was
+/// deleted, like `- task 2025-10-14#2 deleted:2025-10-20 depth:1 ○ pay rent`.
+pub fn write_trash(trash: &[Trashed], out: &mut impl Write) -> Result<()> {
+    writeln!(out, "# Trash")?;
+    writeln!(out)?;
+    for trashed in trash {
+        let (kind, item) = match &trashed.entry {
+            Entry::Event(event) => ("event", format_event(event)),
+            Entry::Task(task) => (
+                "task",
+                format!("depth:{} {}", task.depth, format_task(task)),
+            ),
+        };
+        writeln!(
+            out,
+            "- {kind} {}#{} deleted:{} {item}",
+            trashed.date, trashed.index, trashed.deleted
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses the trash written by [`write_trash`].
+pub fn parse_trash(input: &str) -> Result<Vec<Trashed>> {
+    let mut trash = Vec::new();
+    for (line_no, line) in input.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+
+        if line.trim().is_empty() || line.starts_with("# ") {
+            continue;
+        }
+        let item = line
+            .strip_prefix("- ")
+            .ok_or(err("expected a `- ` list item"))?;
+        let (kind, item) = item.split_once(' ').unwrap_or((item, ""));
+        let (place, item) = item.split_once(' ').unwrap_or((item, ""));
+        let (deleted, item) = item.split_once(' ').unwrap_or((item, ""));
+
+        let (date, index) = place
+            .split_once('#')
+            .ok_or(err("expected where the entry was, like `2025-10-14#2`"))?;
+        let date = parse_date(date).map_err(|e| err(&e.to_string()))?;
+        let index = index.parse().map_err(|_| err("expected a position"))?;
+        let deleted = deleted
+            .strip_prefix("deleted:")
+            .ok_or(err("expected when the entry was deleted"))
+            .and_then(|x| parse_date(x).map_err(|e| err(&e.to_string())))?;
+        let entry = match kind {
+            "event" => Entry::Event(parse_event(item)),
+            "task" => {
+                let (depth, item) = item.split_once(' ').unwrap_or((item, ""));
+                let depth = depth
+                    .strip_prefix("depth:")
+                    .and_then(|x| x.parse().ok())
+                    .ok_or(err("expected the task's depth"))?;
+                Entry::Task(Task {
+                    depth,
+                    ..parse_task(item)
+                })
+            }
+            _ => return Err(err("expected an `event` or `task` item")),
+        };
+        trash.push(Trashed {
+            date,
+            index,
+            deleted,
+            entry,
+        });
+    }
+    Ok(trash)
+}
+
+/// Writes which templates have been written to which days as a list, like
+/// `- 2025-10-14 1a2b@jotty`.
+pub fn write_realized(realized: &[(Date, String)], out: &mut impl Write) -> Result<()> {
+    writeln!(out, "# Realized")?;
+    writeln!(out)?;
+    for (date, uid) in realized {
+        writeln!(out, "- {date} {uid}")?;
+    }
+    Ok(())
+}
+
+/// Parses the list written by [`write_realized`].
+pub fn parse_realized(input: &str) -> Result<Vec<(Date, String)>> {
+    let mut realized = Vec::new();
+    for (line_no, line) in input.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+
+        if line.trim().is_empty() || line.starts_with("# ") {
+            continue;
+        }
+        let item = line
+            .strip_prefix("- ")
+            .ok_or(err("expected a `- ` list item"))?;
+        let (date, uid) = item
+            .split_once(' ')
+            .ok_or(err("expected a date and a template's UID"))?;
+        let date = parse_date(date).map_err(|e| err(&e.to_string()))?;
+        realized.push((date, uid.to_string()));
+    }
+    Ok(realized)
+}
+
+/// Writes the days that have been given their page templates as a list, one
+/// date per item.
+pub fn write_filled(dates: &[Date], out: &mut impl Write) -> Result<()> {
+    writeln!(out, "# Filled")?;
+    writeln!(out)?;
+    for date in dates {
+        writeln!(out, "- {date}")?;
+    }
+    Ok(())
+}
+
+/// Parses the days written by [`write_filled`].
+pub fn parse_filled(input: &str) -> Result<Vec<Date>> {
+    let mut dates = Vec::new();
+    for (line_no, line) in input.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+
+        if line.trim().is_empty() || line.starts_with("# ") {
+            continue;
+        }
+        let date = line
+            .strip_prefix("- ")
+            .ok_or(err("expected a `- ` list item"))?;
+        dates.push(parse_date(date).map_err(|e| err(&e.to_string()))?);
+    }
+    Ok(dates)
+}
+
+/// Formats an event as the text of a list item, like `09:00 **standup**`,
+/// followed by its UID in a comment if it has one.
+pub fn format_event(event: &Event) -> String {
+    let times = event.times().map(|x| x + " ").unwrap_or_default();
+    let mut title = escape(&event.title);
+    if split_times(&title).0.is_some() {
+        // or the time would be read back as the event's start
+        title.insert(title.find(':').expect("a time has a colon"), '\\');
+    }
+    let item = match event.importance {
+        Importance::Normal => format!("{times}{title}"),
+        Importance::High => format!("{times}**{title}**"),
+    };
+    with_uid(item, &event.uid)
+}
+
+/// Formats a task as the text of a list item, like `○ **rent** due:2025-11-01`,
+/// followed by its UID in a comment if it has one.
+pub fn format_task(task: &Task) -> String {
+    let symbol = task.completion_level.symbol();
+    let due = task.due.map(|x| format!(" due:{x}")).unwrap_or_default();
+    let mut title = escape(&task.title);
+    if split_due(&title).1.is_some() {
+        // or the date would be read back as the task's due date
+        title.insert(title.rfind(':').expect("a due date has a colon"), '\\');
+    }
+    let item = match task.importance {
+        Importance::Normal => format!("{symbol} {title}{due}"),
+        Importance::High => format!("{symbol} **{title}**{due}"),
+    };
+    with_uid(item, &task.uid)
+}
+
+/// Adds a UID to the end of an item as an HTML comment, which Markdown
+/// viewers do not show.
+fn with_uid(item: String, uid: &str) -> String {
+    match uid {
+        "" => item,
+        uid => format!("{item} <!-- {uid} -->"),
+    }
+}
+
+/// Splits the UID written by [`with_uid`] off the end of an item.
+fn split_uid(item: &str) -> (&str, &str) {
+    item.strip_suffix(" -->")
+        .and_then(|x| x.rsplit_once(" <!-- "))
+        .unwrap_or((item, ""))
+}
+
+/// Parses days written by [`write_day`]. Entries that come before any date
+/// heading are collected into a day with no date. A day's `Mood: `,
+/// `Energy: ` and `> ` reflection lines may come anywhere in it before its
+/// journal, which runs as free text up to the next heading. A section under
+/// any other `## ` heading is kept as it was written, up to the next heading
+/// of its level or above.
+pub fn parse(input: &str) -> Result<Vec<Day>> {
+    let mut days: Vec<Day> = Vec::new();
+    let mut section = Section::None;
+
+    for (line_no, line) in input.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+
+        let in_text = !line.starts_with("# ") && !line.starts_with("## ");
+        if matches!(section, Section::Journal) && in_text {
+            let journal = &mut day_of(&mut days).journal;
+            journal.push_str(line.strip_prefix('\\').unwrap_or(line));
+            journal.push('\n');
+        } else if matches!(section, Section::Other) && in_text {
+            let other = &mut day_of(&mut days).other;
+            other.push_str(line);
+            other.push('\n');
+        } else if line.trim().is_empty() {
+            continue;
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            section = match heading.trim().to_lowercase().as_str() {
+                "events" => Section::Events,
+                "tasks" => Section::Tasks,
+                "journal" => Section::Journal,
+                _ => Section::Other,
+            };
+            let day = day_of(&mut days);
+            if matches!(section, Section::Other) {
+                day.other.push_str(line);
+                day.other.push('\n');
+            }
+        } else if let Some(heading) = line.strip_prefix("# ") {
+            let date = parse_date(heading).map_err(|e| err(&e.to_string()))?;
+            days.push(Day {
+                date: Some(date),
+                ..Default::default()
+            });
+            section = Section::None;
+        } else if let Some(rating) = line.strip_prefix("Mood: ") {
+            let rating = parse_rating(rating).ok_or(err("expected a rating like `3/5`"))?;
+            day_of(&mut days).mood.mood = Some(rating);
+        } else if let Some(rating) = line.strip_prefix("Energy: ") {
+            let rating = parse_rating(rating).ok_or(err("expected a rating like `3/5`"))?;
+            day_of(&mut days).mood.energy = Some(rating);
+        } else if let Some(reflection) = line.strip_prefix(">") {
+            day_of(&mut days).mood.reflection = reflection.trim().to_string();
+        } else if let Some((depth, item)) = split_item(line) {
+            let day = days.last_mut().ok_or(err("entry is not under a heading"))?;
+            match section {
+                Section::Events => day.events.push(parse_event(item)),
+                Section::Tasks => day.tasks.push(Task {
+                    depth,
+                    ..parse_task(item)
+                }),
+                Section::None | Section::Journal | Section::Other => {
+                    return Err(err("entry is not under an `Events` or `Tasks` heading"));
+                }
+            }
+        } else {
+            return Err(err("expected a heading or a `- ` list item"));
+        }
+    }
+    for day in &mut days {
+        day.journal = day.journal.trim_end().trim_start_matches('\n').to_string();
+        day.other = day.other.trim_end().to_string();
+    }
+    Ok(days)
+}
+
+/// The last day parsed, or a day with no date if none has been.
+fn day_of(days: &mut Vec<Day>) -> &mut Day {
+    if days.is_empty() {
+        days.push(Day::default());
+    }
+    days.last_mut().expect("a day was just pushed")
+}
+
+/// Parses a rating like `3/5`, or just `3`.
+fn parse_rating(rating: &str) -> Option<u8> {
+    let rating = rating.trim();
+    let rating = rating
+        .strip_suffix(&format!("/{}", Mood::MAX_RATING))
+        .unwrap_or(rating);
+    rating
+        .trim()
+        .parse()
+        .ok()
+        .filter(|x| (1..=Mood::MAX_RATING).contains(x))
+}
+
+/// Splits a list item line into how deeply it is nested, at two spaces per
+/// level, and the text after its `- `.
+pub fn split_item(line: &str) -> Option<(u8, &str)> {
+    let item = line.trim_start_matches(' ');
+    let depth = (line.len() - item.len()) / 2;
+    let item = item.strip_prefix("- ").or((item == "-").then_some(""))?;
+    Some((u8::try_from(depth).unwrap_or(u8::MAX), item))
+}
+
+pub fn parse_event(item: &str) -> Event {
+    let (item, uid) = split_uid(item);
+    let (start, end, item) = split_times(item);
+    let (title, importance) = parse_importance(item);
+    Event {
+        title: unescape(title),
+        importance,
+        start,
+        end,
+        uid: uid.to_string(),
+    }
+}
+
+pub fn parse_task(item: &str) -> Task {
+    let (item, uid) = split_uid(item);
+    let mut chars = item.chars();
+    let (item, completion_level) =
+        if let Some(completion_level) = chars.next().and_then(CompletionLevel::from_symbol) {
+            let rest = chars.as_str();
+            (rest.strip_prefix(' ').unwrap_or(rest), completion_level)
+        } else {
+            (item, CompletionLevel::None)
+        };
+    let (item, due) = split_due(item);
+    let (title, importance) = parse_importance(item);
+    Task {
+        title: unescape(title),
+        completion_level,
+        importance,
+        due,
+        depth: 0,
+        uid: uid.to_string(),
+    }
+}
+
+fn parse_importance(item: &str) -> (&str, Importance) {
+    if let Some(title) = item.strip_prefix("**").and_then(|x| x.strip_suffix("**")) {
+        (title, Importance::High)
+    } else {
+        (item, Importance::Normal)
+    }
+}
+
+/// Escapes the backslashes, `*` and `<` in a title, so that it cannot be read
+/// back as bold or as a UID comment.
+fn escape(title: &str) -> String {
+    let mut escaped = String::with_capacity(title.len());
+    for c in title.chars() {
+        if matches!(c, '\\' | '*' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Drops the backslash before any ASCII punctuation, as Markdown does.
+fn unescape(title: &str) -> String {
+    let mut unescaped = String::with_capacity(title.len());
+    let mut chars = title.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next_if(char::is_ascii_punctuation).unwrap_or(c)),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Month, Time};
+
+    use super::*;
+    use crate::model::MemModel;
+
+    fn date(day: u8) -> Date {
+        Date::from_calendar_date(2025, Month::October, day).unwrap()
+    }
+
+    fn export_days(model: &MemModel) -> String {
+        let mut out = Vec::new();
+        export(model, date(1), date(31), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn titles_parse_back_as_written() {
+        for title in [
+            "",
+            "**x**",
+            "*",
+            "10:00 call",
+            "10:00-11:00",
+            "pay due:2025-01-01",
+            "a <!-- b -->",
+            "\\ back\\slash \\*",
+            "○ ◐",
+        ] {
+            for importance in [Importance::Normal, Importance::High] {
+                let event = Event {
+                    title: title.to_string(),
+                    importance: importance.clone(),
+                    ..Default::default()
+                };
+                assert_eq!(parse_event(&format_event(&event)), event);
+
+                let task = Task {
+                    title: title.to_string(),
+                    importance,
+                    ..Default::default()
+                };
+                assert_eq!(parse_task(&format_task(&task)), task);
+            }
+        }
+
+        let event = Event {
+            title: "10:00 call".to_string(),
+            start: Some(Time::from_hms(9, 0, 0).unwrap()),
+            uid: "abc".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(format_event(&event), "09:00 10\\:00 call <!-- abc -->");
+        assert_eq!(parse_event(&format_event(&event)), event);
+    }
+
+    #[test]
+    fn reimporting_replaces_days() {
+        let mut model = MemModel::default();
+        let input =
+            "# 2025-10-14\n\n## Events\n\n- \n- 09:00 standup\n\n## Tasks\n\n- ○ \n- ● **rent**\n";
+        assert_eq!(import(&mut model, input).unwrap(), 4);
+        let uid = model.get_task(date(14), 1).unwrap().uid;
+        let exported = export_days(&model);
+        assert_eq!(exported, input);
+
+        assert_eq!(import(&mut model, &exported).unwrap(), 4);
+        assert_eq!(export_days(&model), input);
+        assert_eq!(model.get_task(date(14), 1).unwrap().uid, uid);
+
+        let edited = exported.replace("- ○ \n", "");
+        assert_eq!(import(&mut model, &edited).unwrap(), 3);
+        assert_eq!(export_days(&model), edited);
+    }
+}
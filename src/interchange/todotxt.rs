@@ -0,0 +1,551 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use time::Date;
+
+use super::{dates, parse_date, write_atomically};
+use crate::model::{CompletionLevel, Importance, Model, Task};
+
+/// The changes a sync made to the journal.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+/// A task as a line of a todo.txt file, along with what todo.txt records
+/// about it that a task does not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// the day the task belongs on: its creation date, or else its due date
+    pub date: Date,
+    pub task: Task,
+    /// the priority's letter, which marks the task as important
+    pub priority: Option<char>,
+    pub completed_on: Option<Date>,
+}
+
+impl Line {
+    /// A line for a task on `date`, with nothing more known about it.
+    pub fn new(date: Date, task: Task) -> Self {
+        Self {
+            date,
+            task,
+            priority: None,
+            completed_on: None,
+        }
+    }
+}
+
+/// Writes the tasks as todo.txt lines, without their UIDs.
+pub fn export(model: &dyn Model, from: Date, to: Date, out: &mut impl Write) -> Result<()> {
+    for date in dates(from, to) {
+        for task in model.tasks_iter(date) {
+            let task = Task {
+                uid: String::new(),
+                ..task
+            };
+            writeln!(out, "{}", format_line(&Line::new(date, task)))?;
+        }
+        model.err()?;
+    }
+    Ok(())
+}
+
+/// Adds every task in `input` to the model. Tasks without a creation or due
+/// date are added to `today`.
+pub fn import(model: &mut dyn Model, input: &str, today: Date) -> Result<usize> {
+    let mut imported = 0;
+    for line in parse_lines(input, today) {
+        append(model, line.date, line.task)?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
+/// Merges the tasks in a todo.txt file with the tasks in the journal, then
+/// rewrites the file with the result.
+///
+/// Each line a sync writes ends in a `uid:` tag that ties it to its task, so
+/// tasks with the same title are kept apart. Only the tasks that came from
+/// the file, or that were added to the journal after the first sync, are
+/// written to it, in the file's order.
+///
+/// A copy of the file is kept next to it after every sync, along with the
+/// UIDs of the journal's tasks that stay out of it. Comparing each side
+/// against that copy tells a task deleted on one side apart from a task
+/// added on the other, and tells which side changed a task. When both sides
+/// changed the same task, the journal wins.
+pub fn sync(model: &mut dyn Model, path: &Path, today: Date) -> Result<SyncSummary> {
+    let base_path = base_path(path);
+    let first_sync = !base_path.exists();
+    let file_lines = match path.exists() {
+        true => parse_lines(&fs::read_to_string(path)?, today),
+        false => Vec::new(),
+    };
+    let (base, mut kept_out) = match first_sync {
+        true => (HashMap::new(), HashSet::new()),
+        false => parse_base(&fs::read_to_string(&base_path)?, today),
+    };
+
+    let mut journal = HashMap::new();
+    for date in model.dates() {
+        let tasks = model.tasks_iter(date).filter(|x| !x.uid.is_empty());
+        journal.extend(tasks.map(|x| (x.uid, date)));
+    }
+    model.err()?;
+
+    let mut summary = SyncSummary::default();
+    let mut lines = Vec::new();
+    for mut line in file_lines {
+        let uid = line.task.uid.clone();
+        let current = journal
+            .get(&uid)
+            .and_then(|x| Some((*x, find(model, *x, &uid)?)));
+        match (current, base.get(&uid)) {
+            (Some((date, (idx, current))), Some(old))
+                if summary_of(&line.task) != summary_of(&old.task)
+                    && summary_of(&current) == summary_of(&old.task) =>
+            {
+                let updated = Task {
+                    title: line.task.title.clone(),
+                    completion_level: match line.task.completion_level {
+                        CompletionLevel::Full => CompletionLevel::Full,
+                        _ if current.completion_level == CompletionLevel::Full => {
+                            CompletionLevel::None
+                        }
+                        _ => current.completion_level.clone(),
+                    },
+                    importance: line.task.importance.clone(),
+                    due: line.task.due,
+                    ..current
+                };
+                model.replace_task(date, idx, updated)?;
+                summary.updated += 1;
+            }
+            // deleted from the journal
+            (None, Some(_)) => continue,
+            (None, None) => {
+                line.task.uid = append(model, line.date, line.task.clone())?;
+                journal.insert(line.task.uid.clone(), line.date);
+                summary.added += 1;
+            }
+            _ => {}
+        }
+        lines.push(line);
+    }
+
+    let in_file: HashSet<&str> = lines.iter().map(|x| x.task.uid.as_str()).collect();
+    for uid in base.keys().filter(|x| !in_file.contains(x.as_str())) {
+        if let Some(date) = journal.remove(uid)
+            && let Some((idx, _)) = find(model, date, uid)
+        {
+            model.delete_task(date, idx)?;
+            summary.removed += 1;
+        }
+    }
+    model.err()?;
+
+    let mut out = Vec::new();
+    for line in &lines {
+        if let Some(date) = journal.get(&line.task.uid)
+            && let Some((_, task)) = find(model, *date, &line.task.uid)
+        {
+            writeln!(
+                out,
+                "{}",
+                format_line(&synced(*date, task, Some(line), today))
+            )?;
+        }
+    }
+    for date in model.dates() {
+        for task in model.tasks_iter(date).filter(|x| !x.uid.is_empty()) {
+            if in_file.contains(task.uid.as_str()) || kept_out.contains(&task.uid) {
+                continue;
+            }
+            // the journal's tasks from before the first sync stay out of the file
+            if first_sync {
+                kept_out.insert(task.uid);
+                continue;
+            }
+            writeln!(out, "{}", format_line(&synced(date, task, None, today)))?;
+        }
+    }
+    model.err()?;
+
+    let mut base_out = out.clone();
+    let kept_out = kept_out
+        .iter()
+        .filter(|x| journal.contains_key(*x) && !in_file.contains(x.as_str()));
+    for uid in kept_out {
+        writeln!(base_out, "{KEPT_OUT}{uid}")?;
+    }
+    write_atomically(path, &out)?;
+    write_atomically(&base_path, &base_out)?;
+    Ok(summary)
+}
+
+/// Starts the lines of the copy kept after a sync that hold the UIDs of
+/// tasks kept out of the file.
+const KEPT_OUT: &str = "# kept out: ";
+
+/// Parses the copy of the file kept after a sync, and the UIDs of the tasks
+/// kept out of the file.
+fn parse_base(input: &str, today: Date) -> (HashMap<String, Line>, HashSet<String>) {
+    let mut base = HashMap::new();
+    let mut kept_out = HashSet::new();
+    for line in input.lines() {
+        if let Some(uid) = line.strip_prefix(KEPT_OUT) {
+            kept_out.insert(uid.to_string());
+        } else if let Some(line) = parse_line(line, today)
+            && !line.task.uid.is_empty()
+        {
+            base.insert(line.task.uid.clone(), line);
+        }
+    }
+    (base, kept_out)
+}
+
+fn parse_lines(input: &str, today: Date) -> Vec<Line> {
+    input.lines().filter_map(|x| parse_line(x, today)).collect()
+}
+
+/// The line to write for a task, keeping the priority and the completion
+/// date of the line it was read from. A task completed in the journal is
+/// taken to be completed on the day of the sync.
+fn synced(date: Date, task: Task, old: Option<&Line>, today: Date) -> Line {
+    let priority = old
+        .and_then(|x| x.priority)
+        .filter(|_| task.importance == Importance::High);
+    let completed_on = match task.completion_level {
+        CompletionLevel::Full => Some(old.and_then(|x| x.completed_on).unwrap_or(today)),
+        _ => None,
+    };
+    Line {
+        date,
+        task,
+        priority,
+        completed_on,
+    }
+}
+
+/// Parses a line of a todo.txt file into the date the task belongs on and the
+/// task itself. The description, including any projects, contexts and
+/// `key:value` tags, becomes the title, apart from the `due:` date and the
+/// `uid:` written by [`sync`].
+pub fn parse_line(line: &str, today: Date) -> Option<Line> {
+    let mut rest = line.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let done = if let Some(x) = rest.strip_prefix("x ") {
+        rest = x.trim_start();
+        true
+    } else {
+        false
+    };
+
+    let mut priority = None;
+    if let [b'(', letter @ b'A'..=b'Z', b')', b' ', ..] = rest.as_bytes() {
+        priority = Some(char::from(*letter));
+        rest = rest[4..].trim_start();
+    }
+
+    let mut leading_dates = Vec::new();
+    while leading_dates.len() < 2
+        && let Some((token, remaining)) = rest.split_once(' ')
+        && let Ok(date) = parse_date(token)
+    {
+        leading_dates.push(date);
+        rest = remaining.trim_start();
+    }
+    let (completed_on, created_on) = match (done, leading_dates.as_slice()) {
+        (true, [completed, created]) => (Some(*completed), Some(*created)),
+        (true, [completed]) => (Some(*completed), None),
+        (false, [created, ..]) => (None, Some(*created)),
+        _ => (None, None),
+    };
+
+    let mut due = None;
+    let mut uid = String::new();
+    let mut words = Vec::new();
+    for word in rest.split(' ') {
+        if let Some(letter) = word.strip_prefix("pri:")
+            && let [letter @ b'A'..=b'Z'] = letter.as_bytes()
+            && done
+        {
+            priority = Some(char::from(*letter));
+            continue;
+        }
+        if let Some(value) = word.strip_prefix("due:")
+            && due.is_none()
+            && let Ok(date) = parse_date(value)
+        {
+            due = Some(date);
+            continue;
+        }
+        if let Some(value) = word.strip_prefix("uid:")
+            && uid.is_empty()
+            && !value.is_empty()
+        {
+            uid = value.to_string();
+            continue;
+        }
+        words.push(word);
+    }
+
+    Some(Line {
+        date: created_on.or(due).or(completed_on).unwrap_or(today),
+        task: Task {
+            title: words.join(" "),
+            completion_level: if done {
+                CompletionLevel::Full
+            } else {
+                CompletionLevel::None
+            },
+            importance: match priority {
+                Some(_) => Importance::High,
+                None => Importance::Normal,
+            },
+            due,
+            depth: 0,
+            uid,
+        },
+        priority,
+        completed_on,
+    })
+}
+
+/// Formats a line of a todo.txt file, with the line's date as the task's
+/// creation date. An important task without a priority gets `A`, and a
+/// completed task with no completion date is taken to be completed on its
+/// day, as tasks in a bullet journal are. todo.txt cannot express partially
+/// complete tasks, so they are written as incomplete.
+pub fn format_line(line: &Line) -> String {
+    let Line { date, task, .. } = line;
+    let priority = line.priority.unwrap_or('A');
+    let mut text = match (&task.completion_level, &task.importance) {
+        (CompletionLevel::Full, _) => {
+            format!("x {} {date} ", line.completed_on.unwrap_or(*date))
+        }
+        (_, Importance::High) => format!("({priority}) {date} "),
+        (_, Importance::Normal) => format!("{date} "),
+    };
+    text.push_str(&task.title);
+    if let Some(due) = task.due {
+        text.push_str(&format!(" due:{due}"));
+    }
+    if task.completion_level == CompletionLevel::Full && task.importance == Importance::High {
+        text.push_str(&format!(" pri:{priority}"));
+    }
+    if !task.uid.is_empty() {
+        text.push_str(&format!(" uid:{}", task.uid));
+    }
+    text
+}
+
+fn base_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.jotty"))
+}
+
+/// The parts of a task that todo.txt can express.
+fn summary_of(task: &Task) -> (&str, bool, bool, Option<Date>) {
+    (
+        &task.title,
+        task.completion_level == CompletionLevel::Full,
+        task.importance == Importance::High,
+        task.due,
+    )
+}
+
+/// The position of the task with `uid` on a day, and the task.
+fn find(model: &dyn Model, date: Date, uid: &str) -> Option<(usize, Task)> {
+    model
+        .tasks_iter(date)
+        .enumerate()
+        .find(|(_, x)| x.uid == uid)
+}
+
+/// Adds a task to the end of a day, and returns the UID it is kept under.
+fn append(model: &mut dyn Model, date: Date, task: Task) -> Result<String> {
+    let idx = model.tasks_len(date);
+    model.new_task(date, idx)?;
+    model.replace_task(date, idx, task)?;
+    model.err()?;
+    Ok(model.get_task(date, idx)?.uid)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+    use crate::model::MemModel;
+
+    fn date(day: u8) -> Date {
+        Date::from_calendar_date(2025, Month::October, day).unwrap()
+    }
+
+    #[test]
+    fn parses_lines() {
+        let line = parse_line("(B) 2025-10-01 call mom +family @phone", date(9)).unwrap();
+        assert_eq!(line.date, date(1));
+        assert_eq!(line.task.title, "call mom +family @phone");
+        assert_eq!(line.task.importance, Importance::High);
+        assert_eq!(line.priority, Some('B'));
+        assert_eq!(line.task.completion_level, CompletionLevel::None);
+
+        let line = parse_line("x 2025-10-03 2025-10-02 pay rent pri:C", date(9)).unwrap();
+        assert_eq!(line.date, date(2));
+        assert_eq!(line.completed_on, Some(date(3)));
+        assert_eq!(line.task.title, "pay rent");
+        assert_eq!(line.task.importance, Importance::High);
+        assert_eq!(line.priority, Some('C'));
+        assert_eq!(line.task.completion_level, CompletionLevel::Full);
+
+        let line = parse_line("buy milk due:2025-10-05 rec:1w uid:abc", date(9)).unwrap();
+        assert_eq!(line.date, date(5));
+        assert_eq!(line.task.title, "buy milk rec:1w");
+        assert_eq!(line.task.due, Some(date(5)));
+        assert_eq!(line.task.uid, "abc");
+
+        assert_eq!(parse_line("someday", date(9)).unwrap().date, date(9));
+        assert!(parse_line("   ", date(9)).is_none());
+    }
+
+    #[test]
+    fn formats_lines_that_parse_back() {
+        let line = Line {
+            date: date(2),
+            task: Task {
+                title: "pay rent +home key:value".to_string(),
+                completion_level: CompletionLevel::Full,
+                importance: Importance::High,
+                due: Some(date(3)),
+                depth: 0,
+                uid: "abc".to_string(),
+            },
+            priority: Some('D'),
+            completed_on: Some(date(4)),
+        };
+        let text = format_line(&line);
+        assert_eq!(
+            text,
+            "x 2025-10-04 2025-10-02 pay rent +home key:value due:2025-10-03 pri:D uid:abc"
+        );
+        assert_eq!(parse_line(&text, date(9)), Some(line));
+    }
+
+    fn titles(path: &Path) -> Vec<String> {
+        parse_lines(&fs::read_to_string(path).unwrap(), date(9))
+            .into_iter()
+            .map(|x| x.task.title)
+            .collect()
+    }
+
+    #[test]
+    fn sync_merges_both_sides() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("todo.txt");
+        fs::write(
+            &path,
+            "2025-10-01 call mom\n(C) 2025-10-01 buy milk\n2025-10-01 buy milk\n",
+        )
+        .unwrap();
+
+        let mut model = MemModel::default();
+        let task = |title: &str| Task {
+            title: title.to_string(),
+            ..Default::default()
+        };
+        append(&mut model, date(1), task("from before")).unwrap();
+        assert_eq!(
+            sync(&mut model, &path, date(9)).unwrap(),
+            SyncSummary {
+                added: 3,
+                ..Default::default()
+            }
+        );
+        assert_eq!(titles(&path), ["call mom", "buy milk", "buy milk"]);
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("(C) 2025-10-01 buy milk uid:")
+        );
+
+        // complete one and delete another in the file, add in the journal
+        let text = fs::read_to_string(&path).unwrap();
+        let mut lines = text.lines();
+        let call = lines
+            .next()
+            .unwrap()
+            .replacen("2025-10-01", "x 2025-10-02 2025-10-01", 1);
+        lines.next();
+        let milk = lines.next().unwrap();
+        fs::write(&path, format!("{call}\n{milk}\n")).unwrap();
+        append(&mut model, date(3), task("water plants")).unwrap();
+
+        assert_eq!(
+            sync(&mut model, &path, date(9)).unwrap(),
+            SyncSummary {
+                added: 0,
+                updated: 1,
+                removed: 1,
+            }
+        );
+        assert_eq!(titles(&path), ["call mom", "buy milk", "water plants"]);
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("x 2025-10-02 2025-10-01 call mom uid:"));
+        let day: Vec<Task> = model.tasks_iter(date(1)).collect();
+        assert_eq!(day.len(), 3);
+        assert_eq!(day[1].completion_level, CompletionLevel::Full);
+        assert_eq!(day[2].importance, Importance::Normal);
+
+        assert_eq!(
+            sync(&mut model, &path, date(9)).unwrap(),
+            SyncSummary::default()
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), text);
+    }
+
+    #[test]
+    fn sync_takes_titles_renamed_in_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("todo.txt");
+        fs::write(
+            &path,
+            "2025-10-01 call mom
+2025-10-01 buy milk
+",
+        )
+        .unwrap();
+        let mut model = MemModel::default();
+        sync(&mut model, &path, date(9)).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        fs::write(&path, text.replacen("call mom", "call mom and dad", 1)).unwrap();
+        assert_eq!(
+            sync(&mut model, &path, date(9)).unwrap(),
+            SyncSummary {
+                updated: 1,
+                ..Default::default()
+            }
+        );
+        let titles: Vec<String> = model.tasks_iter(date(1)).map(|x| x.title).collect();
+        assert_eq!(titles, ["call mom and dad", "buy milk"]);
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("call mom and dad uid:")
+        );
+    }
+}
@@ -1,52 +1,169 @@
 mod cli;
+mod clipboard;
 mod controller;
+mod editor;
+mod interchange;
 mod model;
+mod pages;
+mod replay;
 mod view;
 
-use std::fs::create_dir_all;
-use std::io;
+use std::fs::{self, File, create_dir_all};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
+use anyhow::{Result, anyhow};
 use clap::Parser;
-use ratatui::DefaultTerminal;
+use time::OffsetDateTime;
 
+use crate::cli::{Command, Format};
 use crate::controller::Controller;
 use crate::model::sqlite_model::establish_connection;
-use crate::model::{MemModel, SqliteModel};
+use crate::model::{MarkdownModel, MemModel, Model, RecurringModel, SqliteModel};
 use crate::view::View;
 
-fn main() -> io::Result<()> {
+fn main() -> Result<()> {
     let args = cli::Cli::parse();
-    let terminal = ratatui::init();
-    let mut controller = get_controller(args, terminal);
-    controller.run()?;
-    ratatui::restore();
+    match &args.command {
+        None => {
+            let pages = pages::load(&templates_path(&args)?)?;
+            let terminal = ratatui::init();
+            let view = match get_model(&args) {
+                Ok(mut model) if !args.ephemeral => {
+                    // nothing can have been deleted before the earliest date
+                    let days = time::Duration::days(args.trash_days.into());
+                    if let Some(before) = today().checked_sub(days) {
+                        // a failure leaves the model in error, which the view shows
+                        let _ = model.purge_before(before);
+                    }
+                    View::new(model, terminal)
+                }
+                _ => View::new(
+                    Box::new(RecurringModel::new(Box::new(MemModel::default()))),
+                    terminal,
+                )
+                .background_text("entries will not be saved when you quit".to_string()),
+            }
+            .page_templates(pages)
+            .system_clipboard(args.osc52)
+            .external_editor(editor::open);
+            let mut controller = Controller::new(view);
+            let result = controller.run();
+            ratatui::restore();
+            result?;
+        }
+        Some(Command::Export {
+            format,
+            from,
+            to,
+            output,
+        }) => {
+            let model = get_model(&args)?;
+            let from = from.unwrap_or(today());
+            let to = to.unwrap_or(from);
+            export(model.as_ref(), *format, from, to, output.as_deref())?;
+        }
+        Some(Command::Import { format, file }) => {
+            let mut model = get_model(&args)?;
+            let input = fs::read_to_string(file)?;
+            let imported = match format {
+                Format::Markdown => interchange::markdown::import(model.as_mut(), &input)?,
+                Format::Ics => interchange::ical::import(model.as_mut(), &input)?,
+                Format::Todotxt => interchange::todotxt::import(model.as_mut(), &input, today())?,
+            };
+            println!("imported {imported} entries");
+        }
+        Some(Command::Replay {
+            file,
+            date,
+            width,
+            height,
+        }) => {
+            let script = fs::read_to_string(file)?;
+            let controller = replay::replay(&script, date.unwrap_or(today()), *width, *height)?;
+            print!("{}", replay::screen(controller.view().terminal().backend()));
+
+            let model = controller.view().model();
+            let dates = model.dates();
+            if let (Some(first), Some(last)) = (dates.first(), dates.last()) {
+                println!();
+                interchange::markdown::export(model, *first, *last, &mut io::stdout())?;
+            }
+        }
+        Some(Command::Sync { file }) => {
+            let mut model = get_model(&args)?;
+            let summary = interchange::todotxt::sync(model.as_mut(), file, today())?;
+            println!(
+                "added {}, updated {} and removed {} tasks",
+                summary.added, summary.updated, summary.removed
+            );
+        }
+    }
     Ok(())
 }
 
-fn get_controller(args: cli::Cli, terminal: DefaultTerminal) -> Controller {
-    let path = if args.ephemeral {
-        None
-    } else if let Some(path) = args.database_path {
-        Some(path)
-    } else if let Some(mut path) = directories_next::ProjectDirs::from("com", "w13n", "jotty")
-        .map(|x| x.data_dir().to_path_buf())
-    {
-        if create_dir_all(&path).is_ok() {
-            path.push("v1.db");
-            Some(path)
-        } else {
-            None
-        }
+/// The journal chosen by the command line, with its recurring entries.
+fn get_model(args: &cli::Cli) -> Result<Box<dyn Model>> {
+    Ok(Box::new(RecurringModel::new(get_storage(args)?)))
+}
+
+fn get_storage(args: &cli::Cli) -> Result<Box<dyn Model>> {
+    if args.ephemeral {
+        return Ok(Box::new(MemModel::default()));
+    } else if let Some(path) = &args.markdown_dir {
+        create_dir_all(path)?;
+        return Ok(Box::new(MarkdownModel::new(path.clone())));
+    }
+
+    let path = if let Some(path) = &args.database_path {
+        path.clone()
     } else {
-        None
+        let mut path = directories_next::ProjectDirs::from("com", "w13n", "jotty")
+            .map(|x| x.data_dir().to_path_buf())
+            .ok_or(anyhow!("cannot find a directory to store the database in"))?;
+        create_dir_all(&path)?;
+        path.push("v1.db");
+        path
     };
 
-    if let Some(conn) = path.and_then(|x| establish_connection(x.as_path()).ok()) {
-        Controller::new(View::new(Box::new(SqliteModel::new(conn)), terminal))
-    } else {
-        Controller::new(
-            View::new(Box::new(MemModel::default()), terminal)
-                .background_text("entries will not be saved when you quit".to_string()),
-        )
+    Ok(Box::new(SqliteModel::new(establish_connection(
+        path.as_path(),
+    )?)))
+}
+
+fn templates_path(args: &cli::Cli) -> Result<PathBuf> {
+    if let Some(path) = &args.templates {
+        return Ok(path.clone());
     }
+    directories_next::ProjectDirs::from("com", "w13n", "jotty")
+        .map(|x| x.config_dir().join("templates.md"))
+        .ok_or(anyhow!(
+            "cannot find a directory to read page templates from"
+        ))
+}
+
+fn export(
+    model: &dyn Model,
+    format: Format,
+    from: time::Date,
+    to: time::Date,
+    output: Option<&Path>,
+) -> Result<()> {
+    let mut out: BufWriter<Box<dyn Write>> = BufWriter::new(match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    });
+    match format {
+        Format::Markdown => interchange::markdown::export(model, from, to, &mut out)?,
+        Format::Ics => interchange::ical::export(model, from, to, &mut out)?,
+        Format::Todotxt => interchange::todotxt::export(model, from, to, &mut out)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn today() -> time::Date {
+    OffsetDateTime::now_local()
+        .unwrap_or(OffsetDateTime::now_utc())
+        .date()
 }
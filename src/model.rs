@@ -1,17 +1,37 @@
+pub mod cached_model;
+pub mod markdown_model;
 pub mod mem_model;
+pub mod recurrence;
+pub mod recurring_model;
 pub mod sqlite_model;
 
+pub use cached_model::CachedModel;
+pub use markdown_model::MarkdownModel;
 pub use mem_model::MemModel;
+pub use recurrence::{Recurrence, Template};
+pub use recurring_model::RecurringModel;
 pub use sqlite_model::SqliteModel;
 
-use anyhow::Result;
-use time::Date;
+#[cfg(test)]
+mod tests;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Result, anyhow};
+use time::{Date, OffsetDateTime, Time};
+
+use crate::interchange::parse_date;
 
 pub trait Model {
     fn new_event(&mut self, date: Date, index: usize) -> Result<()>;
     fn new_task(&mut self, date: Date, index: usize) -> Result<()>;
 
+    /// Deletes an event by moving it to the trash.
     fn delete_event(&mut self, date: Date, index: usize) -> Result<()>;
+    /// Deletes a task by moving it to the trash. Its subtasks are left as they are.
     fn delete_task(&mut self, date: Date, index: usize) -> Result<()>;
 
     fn get_event(&self, date: Date, index: usize) -> Result<Event>;
@@ -20,46 +40,610 @@ pub trait Model {
     fn replace_event(&mut self, date: Date, index: usize, event: Event) -> Result<()>;
     fn replace_task(&mut self, date: Date, index: usize, task: Task) -> Result<()>;
 
+    /// Moves an event to `to_index` in the events of `to`, which may be the
+    /// same day. `to_index` is counted after the event has been taken out.
+    fn move_event(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()>;
+    fn move_task(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()>;
+
+    /// Groups the changes made until `commit` so that they are written all
+    /// at once, or not at all. Backends without transactions write each
+    /// change as it is made.
+    fn begin(&mut self) {}
+    fn commit(&mut self) -> Result<()> {
+        self.err()
+    }
+
     fn events_len(&self, date: Date) -> usize;
     fn tasks_len(&self, date: Date) -> usize;
 
     fn events_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Event> + 'a>;
     fn tasks_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Task> + 'a>;
 
+    /// every date with at least one event or task, in ascending order
+    fn dates(&self) -> Vec<Date>;
+
+    /// every recurring template, in the order they were added
+    fn templates(&self) -> Vec<Template>;
+    fn add_template(&mut self, template: Template) -> Result<()>;
+    fn replace_template(&mut self, index: usize, template: Template) -> Result<()>;
+    fn delete_template(&mut self, index: usize) -> Result<()>;
+    /// The UIDs of the templates whose occurrences on a day have been written
+    /// to it as entries. Kept apart from the days a template skips, so those
+    /// stay the days the user skipped.
+    fn realized(&self, date: Date) -> BTreeSet<String>;
+    fn set_realized(&mut self, date: Date, uid: &str) -> Result<()>;
+
+    /// every tag in the journal and the number of entries tagged with it, by tag
+    fn tags(&self) -> Vec<(String, usize)> {
+        let mut counts = BTreeMap::new();
+        for date in self.dates() {
+            let titles = self
+                .events_iter(date)
+                .map(|x| x.title)
+                .chain(self.tasks_iter(date).map(|x| x.title));
+            for title in titles {
+                for tag in tags(&title) {
+                    *counts.entry(tag).or_default() += 1;
+                }
+            }
+        }
+        counts.into_iter().collect()
+    }
+
+    /// every entry tagged with `tag`, by date, with a day's events before its tasks
+    fn tagged(&self, tag: &str) -> Vec<(Date, Entry)> {
+        let tag = tag.to_lowercase();
+        let mut tagged = Vec::new();
+        for date in self.dates() {
+            let entries = self
+                .events_iter(date)
+                .map(Entry::Event)
+                .chain(self.tasks_iter(date).map(Entry::Task));
+            tagged.extend(
+                entries
+                    .filter(|x| tags(x.title()).contains(&tag))
+                    .map(|x| (date, x)),
+            );
+        }
+        tagged
+    }
+
+    /// every task that is not fully complete and is due on or before `until`,
+    /// with the date it was written on, by due date
+    fn due_tasks(&self, until: Date) -> Vec<(Date, Task)> {
+        let mut due = Vec::new();
+        for date in self.dates() {
+            due.extend(
+                self.tasks_iter(date)
+                    .filter(|x| x.is_open() && x.due.is_some_and(|x| x <= until))
+                    .map(|x| (date, x)),
+            );
+        }
+        due.sort_by_key(|(_, task)| task.due);
+        due
+    }
+
+    /// How a day went, if it was rated or reflected on.
+    fn mood(&self, date: Date) -> Mood;
+    fn set_mood(&mut self, date: Date, mood: Mood) -> Result<()>;
+
+    /// The moods of the days from `from` to `to` that have one.
+    fn moods(&self, from: Date, to: Date) -> BTreeMap<Date, Mood> {
+        let mut moods = BTreeMap::new();
+        let mut date = from;
+        while date <= to {
+            let mood = self.mood(date);
+            if !mood.is_empty() {
+                moods.insert(date, mood);
+            }
+            date = date.next_day().expect("we will never reach maximum date");
+        }
+        moods
+    }
+
+    /// A day's journal: free text written about it, which may run over many
+    /// lines. Days without one have an empty journal.
+    fn journal(&self, date: Date) -> String;
+    fn set_journal(&mut self, date: Date, text: &str) -> Result<()>;
+    /// every day with a journal, and its journal, in ascending order
+    fn journals(&self) -> Vec<(Date, String)>;
+
+    /// Whether a day has been given its page templates, which happens once,
+    /// so that a day cleared on purpose stays clear.
+    fn filled(&self, date: Date) -> bool;
+    fn set_filled(&mut self, date: Date) -> Result<()>;
+
+    /// Every entry whose title contains `query`, and every line of a journal
+    /// that does, ignoring case. By date, with a day's events before its
+    /// tasks and its tasks before its journal.
+    fn search(&self, query: &str) -> Vec<(Date, Found)> {
+        let query = query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let matches = |text: &str| text.to_lowercase().contains(&query);
+        let journals: BTreeMap<Date, String> = self.journals().into_iter().collect();
+        let dates: BTreeSet<Date> = self
+            .dates()
+            .into_iter()
+            .chain(journals.keys().copied())
+            .collect();
+
+        let mut found = Vec::new();
+        for date in dates {
+            let entries = self
+                .events_iter(date)
+                .map(Entry::Event)
+                .chain(self.tasks_iter(date).map(Entry::Task));
+            found.extend(
+                entries
+                    .filter(|x| matches(x.title()))
+                    .map(|x| (date, Found::Entry(x))),
+            );
+            if let Some(journal) = journals.get(&date) {
+                found.extend(
+                    journal
+                        .lines()
+                        .filter(|x| matches(x))
+                        .map(|x| (date, Found::Journal(x.trim().to_string()))),
+                );
+            }
+        }
+        found
+    }
+
+    /// How many entries, and how many fully complete tasks, each day from
+    /// `from` to `to` has. Days with no entries are left out.
+    fn activity(&self, from: Date, to: Date) -> BTreeMap<Date, Activity> {
+        let mut activity = BTreeMap::new();
+        for date in self.dates() {
+            if date < from || date > to {
+                continue;
+            }
+            let tasks: Vec<Task> = self.tasks_iter(date).collect();
+            activity.insert(
+                date,
+                Activity {
+                    entries: self.events_len(date) + tasks.len(),
+                    completed: tasks
+                        .iter()
+                        .filter(|x| x.completion_level == CompletionLevel::Full)
+                        .count(),
+                },
+            );
+        }
+        activity
+    }
+
+    /// The changes made to an event, oldest first. Backends that do not keep
+    /// a history have none.
+    fn event_history(&self, _date: Date, _index: usize) -> Vec<Change> {
+        Vec::new()
+    }
+
+    /// The changes made to a task, oldest first.
+    fn task_history(&self, _date: Date, _index: usize) -> Vec<Change> {
+        Vec::new()
+    }
+
+    /// The names of the habits being kept, in the order they were added.
+    /// Only SQLite journals keep habits.
+    fn habits(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Starts keeping a habit. Fails if a habit of the same name is kept.
+    fn add_habit(&mut self, _name: &str) -> Result<()> {
+        Err(anyhow!("habits are only kept in SQLite journals"))
+    }
+
+    /// Stops keeping a habit, and forgets the days it was done on.
+    fn remove_habit(&mut self, _name: &str) -> Result<()> {
+        Err(anyhow!("no such habit"))
+    }
+
+    /// The days a habit was done on.
+    fn habit_days(&self, _name: &str) -> BTreeSet<Date> {
+        BTreeSet::new()
+    }
+
+    /// Every habit being kept, in the order they were added, and whether it
+    /// was done on `date`.
+    fn habits_on(&self, date: Date) -> Vec<(String, bool)> {
+        self.habits()
+            .into_iter()
+            .map(|x| {
+                let done = self.habit_days(&x).contains(&date);
+                (x, done)
+            })
+            .collect()
+    }
+
+    /// Marks a habit as done, or not done, on a day.
+    fn set_habit_done(&mut self, _name: &str, _date: Date, _done: bool) -> Result<()> {
+        Err(anyhow!("no such habit"))
+    }
+
+    /// every deleted entry that has not been purged, in the order they were deleted
+    fn trash(&self) -> Vec<Trashed>;
+    /// Removes an entry from the trash for good.
+    fn purge(&mut self, index: usize) -> Result<()>;
+
+    /// Puts an entry in the trash back where it was deleted from, or at the
+    /// end of its day if the day is now shorter. A restored subtask is nested
+    /// no deeper than one level under the task before it.
+    fn restore(&mut self, index: usize) -> Result<()> {
+        let trashed = self
+            .trash()
+            .into_iter()
+            .nth(index)
+            .ok_or(anyhow!("index out of bounds"))?;
+        self.begin();
+        let result = put_back(self, trashed).and_then(|_| self.purge(index));
+        self.commit().and(result)
+    }
+
+    /// Purges every entry deleted before `before`.
+    fn purge_before(&mut self, before: Date) -> Result<()> {
+        let trash = self.trash();
+        self.begin();
+        let mut result = Ok(());
+        for (idx, trashed) in trash.iter().enumerate().rev() {
+            if trashed.deleted < before {
+                result = result.and(self.purge(idx));
+            }
+        }
+        self.commit().and(result)
+    }
+
     fn err(&self) -> Result<()>;
 }
 
-#[derive(Default, Debug, Hash, Clone)]
+/// Adds a trashed entry back to its day as [`Model::restore`] describes,
+/// returning the index it was put at.
+fn put_back(model: &mut (impl Model + ?Sized), trashed: Trashed) -> Result<usize> {
+    let date = trashed.date;
+    match trashed.entry {
+        Entry::Event(event) => {
+            let idx = trashed.index.min(model.events_len(date));
+            model.new_event(date, idx)?;
+            model.replace_event(date, idx, event)?;
+            Ok(idx)
+        }
+        Entry::Task(task) => {
+            let idx = trashed.index.min(model.tasks_len(date));
+            let max_depth = match idx {
+                0 => 0,
+                _ => model.get_task(date, idx - 1).map_or(0, |x| x.depth + 1),
+            };
+            let depth = task.depth.min(max_depth);
+            model.new_task(date, idx)?;
+            model.replace_task(date, idx, Task { depth, ..task })?;
+            Ok(idx)
+        }
+    }
+}
+
+/// An entry in the trash, with the day and position it was deleted from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trashed {
+    pub date: Date,
+    pub index: usize,
+    /// the day the entry was deleted on
+    pub deleted: Date,
+    pub entry: Entry,
+}
+
+/// The number of entries on a day, and of its tasks that are complete.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Activity {
+    pub entries: usize,
+    pub completed: usize,
+}
+
+/// How a day went: a mood and an energy rating from 1 to
+/// [`Mood::MAX_RATING`], and a line of reflection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mood {
+    pub mood: Option<u8>,
+    pub energy: Option<u8>,
+    pub reflection: String,
+}
+
+impl Mood {
+    pub const MAX_RATING: u8 = 5;
+
+    pub fn is_empty(&self) -> bool {
+        self.mood.is_none() && self.energy.is_none() && self.reflection.is_empty()
+    }
+
+    /// The next rating up, going from the highest back to none.
+    pub fn cycle_rating(rating: Option<u8>) -> Option<u8> {
+        match rating {
+            None => Some(1),
+            Some(x) if x < Self::MAX_RATING => Some(x + 1),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Something [`Model::search`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Found {
+    Entry(Entry),
+    /// a line of the day's journal
+    Journal(String),
+}
+
+/// A change made to an entry, and when.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub time: OffsetDateTime,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    /// The history of an entry made before histories were kept starts here,
+    /// with the title it had.
+    Tracked(String),
+    Titled(String),
+    Completed(CompletionLevel),
+    Moved(Date),
+}
+
+/// The local date, which backends record entries as deleted on.
+fn today() -> Date {
+    OffsetDateTime::now_local()
+        .unwrap_or(OffsetDateTime::now_utc())
+        .date()
+}
+
+/// A UID for a new entry, unique on this machine: when it was made, the
+/// process that made it and how many UIDs that process made before it.
+fn new_uid() -> String {
+    static MADE: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{:x}-{:x}-{:x}@jotty",
+        OffsetDateTime::now_utc().unix_timestamp_nanos(),
+        process::id(),
+        MADE.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// The UID to keep for an entry being replaced by one with `uid`: the new
+/// one, unless it has none.
+fn kept_uid(old: &str, uid: String) -> String {
+    if uid.is_empty() { old.to_string() } else { uid }
+}
+
+/// The distinct `#tags` in a title, lowercased and without their `#`. A tag
+/// starts at the beginning of a word and runs until the first character that
+/// is not alphanumeric, `-`, `_` or `/`.
+pub fn tags(title: &str) -> BTreeSet<String> {
+    title
+        .split_whitespace()
+        .filter_map(|word| {
+            let tag = word.strip_prefix('#')?;
+            let len = tag.find(|c: char| !is_tag_char(c)).unwrap_or(tag.len());
+            (len > 0).then(|| tag[..len].to_lowercase())
+        })
+        .collect()
+}
+
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '/')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Event(Event),
+    Task(Task),
+}
+
+impl Entry {
+    pub fn title(&self) -> &str {
+        match self {
+            Entry::Event(event) => &event.title,
+            Entry::Task(task) => &task.title,
+        }
+    }
+
+    pub fn uid(&self) -> &str {
+        match self {
+            Entry::Event(event) => &event.uid,
+            Entry::Task(task) => &task.uid,
+        }
+    }
+
+    /// The entry without its UID, to make a copy of it with, which the model
+    /// gives a UID of its own.
+    pub fn without_uid(self) -> Self {
+        match self {
+            Entry::Event(event) => Entry::Event(Event {
+                uid: String::new(),
+                ..event
+            }),
+            Entry::Task(task) => Entry::Task(Task {
+                uid: String::new(),
+                ..task
+            }),
+        }
+    }
+}
+
+#[derive(Default, Debug, Hash, Clone, PartialEq, Eq)]
 pub struct Task {
     pub title: String,
     pub completion_level: CompletionLevel,
+    pub importance: Importance,
+    pub due: Option<Date>,
+    /// How far the task is nested. A task with a depth above zero is a
+    /// subtask of the nearest task before it that is one level shallower.
+    pub depth: u8,
+    /// Tells the task apart in calendars. See [`Event::uid`].
+    pub uid: String,
 }
 
 impl Task {
     pub fn cycle(self) -> Self {
         Self {
-            title: self.title,
             completion_level: self.completion_level.cycle(),
+            ..self
+        }
+    }
+
+    pub fn cycle_importance(self) -> Self {
+        Self {
+            importance: self.importance.cycle(),
+            ..self
         }
     }
+
+    /// whether the task still needs doing
+    pub fn is_open(&self) -> bool {
+        self.completion_level != CompletionLevel::Full
+    }
+
+    /// Moves a due date typed at the end of the title, like
+    /// `pay rent due:2025-11-01`, into the task's due date. Titles without a
+    /// trailing due date are left as they are.
+    pub fn take_due_from_title(self) -> Self {
+        match split_due(&self.title) {
+            (title, Some(due)) => Self {
+                title: title.to_string(),
+                due: Some(due),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Writes the task's due date back onto the end of its title, so that it
+    /// can be edited as text. The opposite of [`Task::take_due_from_title`].
+    pub fn put_due_in_title(self) -> Self {
+        match self.due {
+            Some(due) => Self {
+                title: format!("{} due:{due}", self.title),
+                due: None,
+                ..self
+            },
+            None => self,
+        }
+    }
+}
+
+/// The indices of the subtasks nested under `tasks[index]`, at any depth:
+/// every task after it up to the next one that is no deeper than it.
+pub fn subtasks(tasks: &[Task], index: usize) -> Range<usize> {
+    let depth = tasks[index].depth;
+    let end = tasks[index + 1..]
+        .iter()
+        .position(|x| x.depth <= depth)
+        .map_or(tasks.len(), |x| index + 1 + x);
+    index + 1..end
 }
 
-#[derive(Default, Debug, Hash, Clone)]
+/// Splits a trailing `due:YYYY-MM-DD`, after a space or alone, off the end of
+/// a title.
+pub fn split_due(title: &str) -> (&str, Option<Date>) {
+    let (rest, word) = title.rsplit_once(' ').unwrap_or(("", title));
+    match word.strip_prefix("due:").and_then(|x| parse_date(x).ok()) {
+        Some(due) => (rest, Some(due)),
+        None => (title, None),
+    }
+}
+
+#[derive(Default, Debug, Hash, Clone, PartialEq, Eq)]
 pub struct Event {
     pub title: String,
     pub importance: Importance,
+    pub start: Option<Time>,
+    pub end: Option<Time>,
+    /// Tells the event apart in calendars, through edits and moves. Models
+    /// give every new entry one, and keep it when the entry is replaced by one
+    /// whose UID is empty, so copies of an entry should have theirs cleared.
+    pub uid: String,
 }
 
 impl Event {
     pub fn cycle(self) -> Self {
         Self {
-            title: self.title,
             importance: self.importance.cycle(),
+            ..self
+        }
+    }
+
+    /// Moves a time typed at the start of the title, like `14:30 standup` or
+    /// `9:00-10:15 review`, into the event's start and end. Titles without a
+    /// leading time are left as they are.
+    pub fn take_times_from_title(self) -> Self {
+        match split_times(&self.title) {
+            (Some(start), end, title) => Self {
+                title: title.to_string(),
+                start: Some(start),
+                end,
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Writes the event's times back into the start of its title, so that they
+    /// can be edited as text. The opposite of [`Event::take_times_from_title`].
+    pub fn put_times_in_title(self) -> Self {
+        match self.times() {
+            Some(times) => Self {
+                title: format!("{times} {}", self.title),
+                start: None,
+                end: None,
+                ..self
+            },
+            None => self,
         }
     }
+
+    /// The event's start time, or start and end times, as `14:30` or `14:30-15:00`.
+    pub fn times(&self) -> Option<String> {
+        let start = self.start?;
+        Some(match self.end {
+            Some(end) => format!("{}-{}", format_time(start), format_time(end)),
+            None => format_time(start),
+        })
+    }
+}
+
+pub fn format_time(time: Time) -> String {
+    format!("{:02}:{:02}", time.hour(), time.minute())
+}
+
+/// Parses a time written as `H:MM` or `HH:MM`.
+pub fn parse_time(s: &str) -> Option<Time> {
+    let (hour, minute) = s.split_once(':')?;
+    if !(1..=2).contains(&hour.len()) || minute.len() != 2 {
+        return None;
+    }
+    Time::from_hms(hour.parse().ok()?, minute.parse().ok()?, 0).ok()
+}
+
+/// Splits a leading time or time range, followed by a space or the end of
+/// the title, off the start of a title. An end time before the start is
+/// ignored.
+pub fn split_times(title: &str) -> (Option<Time>, Option<Time>, &str) {
+    let (word, rest) = title.split_once(' ').unwrap_or((title, ""));
+    let (start, end) = match word.split_once(['-', '–']) {
+        Some((start, end)) => (parse_time(start), parse_time(end)),
+        None => (parse_time(word), None),
+    };
+    match (start, end) {
+        (Some(start), Some(end)) if end >= start => (Some(start), Some(end), rest),
+        (Some(start), None) if !word.contains(['-', '–']) => (Some(start), None, rest),
+        _ => (None, None, title),
+    }
 }
 
-#[derive(Default, Debug, Hash, Clone)]
+#[derive(Default, Debug, Hash, Clone, PartialEq, Eq)]
 pub enum Importance {
     #[default]
     Normal,
@@ -75,7 +659,7 @@ impl Importance {
     }
 }
 
-#[derive(Default, Debug, Hash, Clone)]
+#[derive(Default, Debug, Hash, Clone, PartialEq, Eq)]
 pub enum CompletionLevel {
     #[default]
     None,
@@ -91,4 +675,21 @@ impl CompletionLevel {
             CompletionLevel::Full => CompletionLevel::None,
         }
     }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            CompletionLevel::None => '○',
+            CompletionLevel::Partial => '◐',
+            CompletionLevel::Full => '●',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            '○' => Some(CompletionLevel::None),
+            '◐' => Some(CompletionLevel::Partial),
+            '●' => Some(CompletionLevel::Full),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,519 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use anyhow::{Result, anyhow};
+use time::Date;
+
+use super::{Activity, Change, Entry, Event, Found, Model, Mood, Task, Template, Trashed};
+
+/// Wraps a model and keeps each day's events and tasks in memory after they
+/// are first read, so that drawing a frame loads each list at most once no
+/// matter how many times it is asked for. Writes go straight through to the
+/// wrapped model and drop the day they touch.
+pub struct CachedModel {
+    model: Box<dyn Model>,
+    events: RefCell<HashMap<Date, Vec<Event>>>,
+    tasks: RefCell<HashMap<Date, Vec<Task>>>,
+}
+
+impl CachedModel {
+    pub fn new(model: Box<dyn Model>) -> Self {
+        Self {
+            model,
+            events: RefCell::default(),
+            tasks: RefCell::default(),
+        }
+    }
+
+    /// Drops every cached day, so that the next read sees changes made to the
+    /// underlying storage by other programs.
+    pub fn refresh(&self) {
+        self.events.borrow_mut().clear();
+        self.tasks.borrow_mut().clear();
+    }
+
+    fn with_events<T>(&self, date: Date, f: impl FnOnce(&Vec<Event>) -> T) -> T {
+        let mut events = self.events.borrow_mut();
+        f(events
+            .entry(date)
+            .or_insert_with(|| self.model.events_iter(date).collect()))
+    }
+
+    fn with_tasks<T>(&self, date: Date, f: impl FnOnce(&Vec<Task>) -> T) -> T {
+        let mut tasks = self.tasks.borrow_mut();
+        f(tasks
+            .entry(date)
+            .or_insert_with(|| self.model.tasks_iter(date).collect()))
+    }
+}
+
+impl Model for CachedModel {
+    fn new_event(&mut self, date: Date, index: usize) -> Result<()> {
+        self.events.get_mut().remove(&date);
+        self.model.new_event(date, index)
+    }
+
+    fn new_task(&mut self, date: Date, index: usize) -> Result<()> {
+        self.tasks.get_mut().remove(&date);
+        self.model.new_task(date, index)
+    }
+
+    fn delete_event(&mut self, date: Date, index: usize) -> Result<()> {
+        self.events.get_mut().remove(&date);
+        self.model.delete_event(date, index)
+    }
+
+    fn delete_task(&mut self, date: Date, index: usize) -> Result<()> {
+        self.tasks.get_mut().remove(&date);
+        self.model.delete_task(date, index)
+    }
+
+    fn get_event(&self, date: Date, index: usize) -> Result<Event> {
+        self.with_events(date, |x| x.get(index).cloned())
+            .ok_or(anyhow!("index out of bounds"))
+    }
+
+    fn get_task(&self, date: Date, index: usize) -> Result<Task> {
+        self.with_tasks(date, |x| x.get(index).cloned())
+            .ok_or(anyhow!("index out of bounds"))
+    }
+
+    fn replace_event(&mut self, date: Date, index: usize, event: Event) -> Result<()> {
+        self.events.get_mut().remove(&date);
+        self.model.replace_event(date, index, event)
+    }
+
+    fn replace_task(&mut self, date: Date, index: usize, task: Task) -> Result<()> {
+        self.tasks.get_mut().remove(&date);
+        self.model.replace_task(date, index, task)
+    }
+
+    fn move_event(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        self.events.get_mut().remove(&from);
+        self.events.get_mut().remove(&to);
+        self.model.move_event(from, index, to, to_index)
+    }
+
+    fn move_task(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        self.tasks.get_mut().remove(&from);
+        self.tasks.get_mut().remove(&to);
+        self.model.move_task(from, index, to, to_index)
+    }
+
+    fn begin(&mut self) {
+        self.model.begin();
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.model.commit()
+    }
+
+    fn events_len(&self, date: Date) -> usize {
+        self.with_events(date, Vec::len)
+    }
+
+    fn tasks_len(&self, date: Date) -> usize {
+        self.with_tasks(date, Vec::len)
+    }
+
+    fn events_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Event> + 'a> {
+        Box::new(self.with_events(date, Vec::clone).into_iter())
+    }
+
+    fn tasks_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Task> + 'a> {
+        Box::new(self.with_tasks(date, Vec::clone).into_iter())
+    }
+
+    fn dates(&self) -> Vec<Date> {
+        self.model.dates()
+    }
+
+    fn templates(&self) -> Vec<Template> {
+        self.model.templates()
+    }
+
+    // templates can add entries to any day, so these drop every cached day
+    fn add_template(&mut self, template: Template) -> Result<()> {
+        self.refresh();
+        self.model.add_template(template)
+    }
+
+    fn replace_template(&mut self, index: usize, template: Template) -> Result<()> {
+        self.refresh();
+        self.model.replace_template(index, template)
+    }
+
+    fn delete_template(&mut self, index: usize) -> Result<()> {
+        self.refresh();
+        self.model.delete_template(index)
+    }
+
+    fn tags(&self) -> Vec<(String, usize)> {
+        self.model.tags()
+    }
+
+    fn tagged(&self, tag: &str) -> Vec<(Date, Entry)> {
+        self.model.tagged(tag)
+    }
+
+    fn due_tasks(&self, until: Date) -> Vec<(Date, Task)> {
+        self.model.due_tasks(until)
+    }
+
+    fn mood(&self, date: Date) -> Mood {
+        self.model.mood(date)
+    }
+
+    fn set_mood(&mut self, date: Date, mood: Mood) -> Result<()> {
+        self.model.set_mood(date, mood)
+    }
+
+    fn moods(&self, from: Date, to: Date) -> BTreeMap<Date, Mood> {
+        self.model.moods(from, to)
+    }
+
+    fn journal(&self, date: Date) -> String {
+        self.model.journal(date)
+    }
+
+    fn set_journal(&mut self, date: Date, text: &str) -> Result<()> {
+        self.model.set_journal(date, text)
+    }
+
+    fn journals(&self) -> Vec<(Date, String)> {
+        self.model.journals()
+    }
+
+    fn realized(&self, date: Date) -> BTreeSet<String> {
+        self.model.realized(date)
+    }
+
+    fn set_realized(&mut self, date: Date, uid: &str) -> Result<()> {
+        self.model.set_realized(date, uid)
+    }
+
+    fn filled(&self, date: Date) -> bool {
+        self.model.filled(date)
+    }
+
+    fn set_filled(&mut self, date: Date) -> Result<()> {
+        self.model.set_filled(date)
+    }
+
+    fn search(&self, query: &str) -> Vec<(Date, Found)> {
+        self.model.search(query)
+    }
+
+    fn activity(&self, from: Date, to: Date) -> BTreeMap<Date, Activity> {
+        self.model.activity(from, to)
+    }
+
+    fn event_history(&self, date: Date, index: usize) -> Vec<Change> {
+        self.model.event_history(date, index)
+    }
+
+    fn task_history(&self, date: Date, index: usize) -> Vec<Change> {
+        self.model.task_history(date, index)
+    }
+
+    fn habits(&self) -> Vec<String> {
+        self.model.habits()
+    }
+
+    fn add_habit(&mut self, name: &str) -> Result<()> {
+        self.model.add_habit(name)
+    }
+
+    fn remove_habit(&mut self, name: &str) -> Result<()> {
+        self.model.remove_habit(name)
+    }
+
+    fn habit_days(&self, name: &str) -> BTreeSet<Date> {
+        self.model.habit_days(name)
+    }
+
+    fn habits_on(&self, date: Date) -> Vec<(String, bool)> {
+        self.model.habits_on(date)
+    }
+
+    fn set_habit_done(&mut self, name: &str, date: Date, done: bool) -> Result<()> {
+        self.model.set_habit_done(name, date, done)
+    }
+
+    fn trash(&self) -> Vec<Trashed> {
+        self.model.trash()
+    }
+
+    fn purge(&mut self, index: usize) -> Result<()> {
+        self.model.purge(index)
+    }
+
+    fn restore(&mut self, index: usize) -> Result<()> {
+        if let Some(trashed) = self.model.trash().get(index) {
+            self.events.get_mut().remove(&trashed.date);
+            self.tasks.get_mut().remove(&trashed.date);
+        }
+        self.model.restore(index)
+    }
+
+    fn err(&self) -> Result<()> {
+        self.model.err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    use diesel::RunQueryDsl;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use time::Month;
+
+    use super::*;
+    use crate::model::SqliteModel;
+    use crate::model::sqlite_model::establish_connection;
+    use crate::view::View;
+
+    fn date() -> Date {
+        Date::from_calendar_date(2025, Month::October, 14).unwrap()
+    }
+
+    /// Counts the reads that reach the wrapped model.
+    struct CountingModel(Box<dyn Model>, Rc<Cell<usize>>);
+
+    impl CountingModel {
+        fn read(&self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    impl Model for CountingModel {
+        fn new_event(&mut self, date: Date, index: usize) -> Result<()> {
+            self.0.new_event(date, index)
+        }
+        fn new_task(&mut self, date: Date, index: usize) -> Result<()> {
+            self.0.new_task(date, index)
+        }
+        fn delete_event(&mut self, date: Date, index: usize) -> Result<()> {
+            self.0.delete_event(date, index)
+        }
+        fn delete_task(&mut self, date: Date, index: usize) -> Result<()> {
+            self.0.delete_task(date, index)
+        }
+        fn get_event(&self, date: Date, index: usize) -> Result<Event> {
+            self.read();
+            self.0.get_event(date, index)
+        }
+        fn get_task(&self, date: Date, index: usize) -> Result<Task> {
+            self.read();
+            self.0.get_task(date, index)
+        }
+        fn replace_event(&mut self, date: Date, index: usize, event: Event) -> Result<()> {
+            self.0.replace_event(date, index, event)
+        }
+        fn replace_task(&mut self, date: Date, index: usize, task: Task) -> Result<()> {
+            self.0.replace_task(date, index, task)
+        }
+        fn move_event(&mut self, from: Date, i: usize, to: Date, j: usize) -> Result<()> {
+            self.0.move_event(from, i, to, j)
+        }
+        fn move_task(&mut self, from: Date, i: usize, to: Date, j: usize) -> Result<()> {
+            self.0.move_task(from, i, to, j)
+        }
+        fn begin(&mut self) {
+            self.0.begin();
+        }
+        fn commit(&mut self) -> Result<()> {
+            self.0.commit()
+        }
+        fn events_len(&self, date: Date) -> usize {
+            self.read();
+            self.0.events_len(date)
+        }
+        fn tasks_len(&self, date: Date) -> usize {
+            self.read();
+            self.0.tasks_len(date)
+        }
+        fn events_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Event> + 'a> {
+            self.read();
+            self.0.events_iter(date)
+        }
+        fn tasks_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Task> + 'a> {
+            self.read();
+            self.0.tasks_iter(date)
+        }
+        fn dates(&self) -> Vec<Date> {
+            self.read();
+            self.0.dates()
+        }
+        fn templates(&self) -> Vec<Template> {
+            self.0.templates()
+        }
+        fn add_template(&mut self, template: Template) -> Result<()> {
+            self.0.add_template(template)
+        }
+        fn replace_template(&mut self, index: usize, template: Template) -> Result<()> {
+            self.0.replace_template(index, template)
+        }
+        fn delete_template(&mut self, index: usize) -> Result<()> {
+            self.0.delete_template(index)
+        }
+        fn mood(&self, date: Date) -> Mood {
+            self.read();
+            self.0.mood(date)
+        }
+        fn set_mood(&mut self, date: Date, mood: Mood) -> Result<()> {
+            self.0.set_mood(date, mood)
+        }
+        fn journal(&self, date: Date) -> String {
+            self.read();
+            self.0.journal(date)
+        }
+        fn set_journal(&mut self, date: Date, text: &str) -> Result<()> {
+            self.0.set_journal(date, text)
+        }
+        fn journals(&self) -> Vec<(Date, String)> {
+            self.read();
+            self.0.journals()
+        }
+        fn realized(&self, date: Date) -> BTreeSet<String> {
+            self.0.realized(date)
+        }
+        fn set_realized(&mut self, date: Date, uid: &str) -> Result<()> {
+            self.0.set_realized(date, uid)
+        }
+        fn filled(&self, date: Date) -> bool {
+            self.0.filled(date)
+        }
+        fn set_filled(&mut self, date: Date) -> Result<()> {
+            self.0.set_filled(date)
+        }
+        fn activity(&self, from: Date, to: Date) -> BTreeMap<Date, Activity> {
+            self.read();
+            self.0.activity(from, to)
+        }
+        fn event_history(&self, date: Date, index: usize) -> Vec<Change> {
+            self.0.event_history(date, index)
+        }
+        fn task_history(&self, date: Date, index: usize) -> Vec<Change> {
+            self.0.task_history(date, index)
+        }
+        fn habits(&self) -> Vec<String> {
+            self.read();
+            self.0.habits()
+        }
+        fn add_habit(&mut self, name: &str) -> Result<()> {
+            self.0.add_habit(name)
+        }
+        fn remove_habit(&mut self, name: &str) -> Result<()> {
+            self.0.remove_habit(name)
+        }
+        fn habit_days(&self, name: &str) -> BTreeSet<Date> {
+            self.read();
+            self.0.habit_days(name)
+        }
+        fn habits_on(&self, date: Date) -> Vec<(String, bool)> {
+            self.read();
+            self.0.habits_on(date)
+        }
+        fn set_habit_done(&mut self, name: &str, date: Date, done: bool) -> Result<()> {
+            self.0.set_habit_done(name, date, done)
+        }
+        fn trash(&self) -> Vec<Trashed> {
+            self.0.trash()
+        }
+        fn purge(&mut self, index: usize) -> Result<()> {
+            self.0.purge(index)
+        }
+        fn err(&self) -> Result<()> {
+            self.0.err()
+        }
+    }
+
+    #[test]
+    fn frames_read_each_list_once() {
+        let conn = establish_connection(Path::new(":memory:")).unwrap();
+        let mut model = SqliteModel::new(conn);
+        model.new_event(date(), 0).unwrap();
+        model.new_task(date(), 0).unwrap();
+        for habit in ["read", "run", "stretch"] {
+            model.add_habit(habit).unwrap();
+        }
+        model.set_habit_done("run", date(), true).unwrap();
+        let reads = Rc::new(Cell::new(0));
+        let model = CountingModel(Box::new(model), reads.clone());
+
+        let terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut view = View::new(Box::new(model), terminal).on_date(date());
+        view.toggle_editing_mode();
+        // the day's events, its tasks, its mood and its habits
+        for _ in 0..3 {
+            reads.set(0);
+            view.render().unwrap();
+            assert_eq!(reads.get(), 4);
+        }
+    }
+
+    /// The reads a frame made before it was cached: checking both lengths,
+    /// loading both lists, and looking up the entry being edited.
+    fn draw(model: &dyn Model, date: Date) -> usize {
+        model.err().unwrap();
+        let mut drawn = 0;
+        if model.events_len(date) != 0 || model.tasks_len(date) != 0 {
+            drawn += model.events_iter(date).count() + model.tasks_iter(date).count();
+            drawn += model.get_event(date, 0).unwrap().title.len();
+        }
+        drawn
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with `cargo test --release -- --ignored --nocapture`"]
+    fn benchmark_frames_on_a_large_database() {
+        const DAYS: i32 = 3650;
+        const PER_DAY: i32 = 20;
+        const FRAMES: u32 = 2000;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = establish_connection(&dir.path().join("bench.db")).unwrap();
+        let first = date().to_julian_day() - DAYS / 2;
+        for table in [
+            "events (`date`, `index`, `title`, `importance`)",
+            "tasks (`date`, `index`, `title`, `completion_level`, `importance`)",
+        ] {
+            let extra = if table.starts_with("tasks") {
+                ", 0"
+            } else {
+                ""
+            };
+            diesel::sql_query(format!(
+                "WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < {})
+                INSERT INTO {table} SELECT {first} + i / {PER_DAY}, i % {PER_DAY}, 'entry number ' || i, 0{extra} FROM n",
+                DAYS * PER_DAY - 1
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        }
+
+        let uncached = SqliteModel::new(conn);
+        let time = |model: &dyn Model, refresh: &dyn Fn()| -> Duration {
+            let start = Instant::now();
+            for _ in 0..FRAMES {
+                refresh();
+                assert!(draw(model, date()) > 0);
+            }
+            start.elapsed() / FRAMES
+        };
+
+        let before = time(&uncached, &|| {});
+        let cached = CachedModel::new(Box::new(uncached));
+        let after = time(&cached, &|| cached.refresh());
+        println!("{DAYS} days of {PER_DAY} events and tasks each");
+        println!("uncached: {before:?} per frame");
+        println!("cached:   {after:?} per frame");
+        assert!(after < before);
+    }
+}
@@ -0,0 +1,723 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    fs,
+    hash::{DefaultHasher, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Result, anyhow};
+use time::{Date, Month};
+
+use super::{Entry, Event, Model, Mood, Task, Template, Trashed, kept_uid, new_uid, today};
+use crate::interchange::markdown::{self, Day};
+use crate::interchange::write_atomically;
+
+/// A model that keeps each day in its own Markdown file, at `YYYY/MM/DD.md`
+/// under a root directory, in the same format as `jotty export`. Recurring
+/// templates are kept in `recurring.md` at the root, with the days they have
+/// been written to in `realized.md`, deleted entries in `trash.md`, and the
+/// days given their page templates in `filled.md`.
+///
+/// Files are read again whenever they change on disk, and every write reads
+/// the file first, so edits made in another program while jotty is running are
+/// kept. Sections of a day under headings jotty does not use are kept as they
+/// were written. Files are written by renaming a finished temporary file over
+/// them, so other programs never see a half written day.
+pub struct MarkdownModel {
+    root: PathBuf,
+    cache: RefCell<HashMap<Date, (FileStamp, Day)>>,
+    templates: RefCell<(FileStamp, Vec<Template>)>,
+    trash: RefCell<(FileStamp, Vec<Trashed>)>,
+    realized: RefCell<(FileStamp, Vec<(Date, String)>)>,
+    filled: RefCell<(FileStamp, Vec<Date>)>,
+    error: RefCell<Option<String>>,
+}
+
+/// What a file was like when it was last read or written. When it was
+/// changed and how long it was are checked on every read, but an edit in
+/// another program can keep the length and land in the same tick of the
+/// clock, so the hash of its contents is checked before every write.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct FileStamp {
+    changed: Option<(SystemTime, u64)>,
+    hash: u64,
+}
+
+impl MarkdownModel {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            cache: RefCell::default(),
+            templates: RefCell::default(),
+            trash: RefCell::default(),
+            realized: RefCell::default(),
+            filled: RefCell::default(),
+            error: RefCell::default(),
+        }
+    }
+
+    fn path(&self, date: Date) -> PathBuf {
+        self.root
+            .join(format!("{:04}", date.year()))
+            .join(format!("{:02}", date.month() as u8))
+            .join(format!("{:02}.md", date.day()))
+    }
+
+    fn day(&self, date: Date) -> Day {
+        match self.read(date, false) {
+            Ok(day) => day,
+            Err(e) => {
+                self.fail(e);
+                Day::default()
+            }
+        }
+    }
+
+    /// Reads a day from its file, or from the cache if the file has not
+    /// changed. A `fresh` read, for a write, always checks the contents.
+    fn read(&self, date: Date, fresh: bool) -> Result<Day> {
+        let path = self.path(date);
+        let (stamp, day) = self.cache.borrow().get(&date).cloned().unwrap_or_default();
+        let (stamp, contents) = reread(&path, stamp, fresh)?;
+        let day = match contents {
+            Some(contents) => {
+                let mut day = Day::default();
+                for parsed in markdown::parse(&contents)
+                    .map_err(|e| anyhow!("cannot read {}: {e}", path.display()))?
+                {
+                    day.events.extend(parsed.events);
+                    day.tasks.extend(parsed.tasks);
+                    if !parsed.mood.is_empty() {
+                        day.mood = parsed.mood;
+                    }
+                    if !parsed.journal.is_empty() {
+                        day.journal = parsed.journal;
+                    }
+                    if !parsed.other.is_empty() {
+                        if !day.other.is_empty() {
+                            day.other.push_str("\n\n");
+                        }
+                        day.other.push_str(&parsed.other);
+                    }
+                }
+                day
+            }
+            None => day,
+        };
+        self.cache.borrow_mut().insert(date, (stamp, day.clone()));
+        Ok(day)
+    }
+
+    /// Keeps an error that [`Model::err`] reports from then on.
+    fn fail(&self, e: anyhow::Error) {
+        self.error.replace(Some(e.to_string()));
+    }
+
+    /// Changes a day. Only `f` returns an error, for an index out of bounds; a
+    /// file that cannot be read or written is kept as the model's error and the
+    /// day is left as it was.
+    fn modify(&mut self, date: Date, f: impl FnOnce(&mut Day) -> Result<()>) -> Result<()> {
+        let mut day = match self.read(date, true) {
+            Ok(day) => day,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        f(&mut day)?;
+        if let Err(e) = self.write(date, day) {
+            self.fail(e);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, date: Date, mut day: Day) -> Result<()> {
+        let path = self.path(date);
+        let mut contents = Vec::new();
+        if day.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        } else {
+            day.date = Some(date);
+            // entries written by other programs are given UIDs when their day
+            // is next changed
+            for event in day.events.iter_mut().filter(|x| x.uid.is_empty()) {
+                event.uid = new_uid();
+            }
+            for task in day.tasks.iter_mut().filter(|x| x.uid.is_empty()) {
+                task.uid = new_uid();
+            }
+            markdown::write_day(&day, &mut contents)?;
+            write_atomically(&path, &contents)?;
+        }
+        self.cache
+            .get_mut()
+            .insert(date, (stamp(&path, &contents)?, day));
+        Ok(())
+    }
+
+    /// Changes the `from` day, and the `to` day when it is a different day,
+    /// together. Nothing is written unless `f` succeeds. The destination is
+    /// written before the source, so if the second write fails the entry is
+    /// left on both days rather than lost.
+    fn move_entry(
+        &mut self,
+        from: Date,
+        to: Date,
+        f: impl FnOnce(&mut Day, Option<&mut Day>) -> Result<()>,
+    ) -> Result<()> {
+        if from == to {
+            return self.modify(from, |day| f(day, None));
+        }
+        let (mut source, mut dest) = match self
+            .read(from, true)
+            .and_then(|source| Ok((source, self.read(to, true)?)))
+        {
+            Ok(days) => days,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        f(&mut source, Some(&mut dest))?;
+        if let Err(e) = self.write(to, dest).and_then(|()| self.write(from, source)) {
+            self.fail(e);
+        }
+        Ok(())
+    }
+
+    /// Every day that has a file, in ascending order.
+    fn days(&self) -> Vec<(Date, Day)> {
+        let mut days = Vec::new();
+        for (year, year_dir) in numbered_entries(&self.root) {
+            for (month, month_dir) in numbered_entries(&year_dir) {
+                for (day, _) in numbered_entries(&month_dir) {
+                    if let Ok(month) = Month::try_from(month as u8)
+                        && let Ok(date) = Date::from_calendar_date(year as i32, month, day as u8)
+                    {
+                        days.push((date, self.day(date)));
+                    }
+                }
+            }
+        }
+        days
+    }
+
+    fn templates_path(&self) -> PathBuf {
+        self.root.join("recurring.md")
+    }
+
+    fn read_templates(&self, fresh: bool) -> Result<Vec<Template>> {
+        read_list(
+            &self.templates_path(),
+            &self.templates,
+            fresh,
+            markdown::parse_templates,
+        )
+    }
+
+    fn modify_templates(&mut self, f: impl FnOnce(&mut Vec<Template>) -> Result<()>) -> Result<()> {
+        let mut templates = match self.read_templates(true) {
+            Ok(templates) => templates,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        f(&mut templates)?;
+        if let Err(e) = self.write_templates(templates) {
+            self.fail(e);
+        }
+        Ok(())
+    }
+
+    fn write_templates(&mut self, templates: Vec<Template>) -> Result<()> {
+        let path = self.templates_path();
+        write_list(
+            &path,
+            self.templates.get_mut(),
+            templates,
+            markdown::write_templates,
+        )
+    }
+
+    fn trash_path(&self) -> PathBuf {
+        self.root.join("trash.md")
+    }
+
+    fn read_trash(&self, fresh: bool) -> Result<Vec<Trashed>> {
+        read_list(
+            &self.trash_path(),
+            &self.trash,
+            fresh,
+            markdown::parse_trash,
+        )
+    }
+
+    fn modify_trash(&mut self, f: impl FnOnce(&mut Vec<Trashed>) -> Result<()>) -> Result<()> {
+        let mut trash = match self.read_trash(true) {
+            Ok(trash) => trash,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        f(&mut trash)?;
+        let path = self.trash_path();
+        if let Err(e) = write_list(&path, self.trash.get_mut(), trash, markdown::write_trash) {
+            self.fail(e);
+        }
+        Ok(())
+    }
+
+    fn realized_path(&self) -> PathBuf {
+        self.root.join("realized.md")
+    }
+
+    fn read_realized(&self, fresh: bool) -> Result<Vec<(Date, String)>> {
+        read_list(
+            &self.realized_path(),
+            &self.realized,
+            fresh,
+            markdown::parse_realized,
+        )
+    }
+
+    fn filled_path(&self) -> PathBuf {
+        self.root.join("filled.md")
+    }
+
+    fn read_filled(&self, fresh: bool) -> Result<Vec<Date>> {
+        read_list(
+            &self.filled_path(),
+            &self.filled,
+            fresh,
+            markdown::parse_filled,
+        )
+    }
+
+    /// Deletes an entry from a day, after adding it to the trash. The trash is
+    /// written first, so a failure leaves the entry on its day, or at worst in
+    /// both places, rather than losing it.
+    fn delete_entry(
+        &mut self,
+        date: Date,
+        index: usize,
+        take: impl Fn(&mut Day) -> Option<Entry>,
+    ) -> Result<()> {
+        let mut day = match self.read(date, true) {
+            Ok(day) => day,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        let entry = take(&mut day).ok_or(anyhow!("index out of bounds"))?;
+        self.modify_trash(|trash| {
+            trash.push(Trashed {
+                date,
+                index,
+                deleted: today(),
+                entry,
+            });
+            Ok(())
+        })?;
+        if self.error.get_mut().is_some() {
+            return Ok(());
+        }
+        self.modify(date, |day| {
+            take(day).ok_or(anyhow!("index out of bounds"))?;
+            Ok(())
+        })
+    }
+}
+
+/// Reads a list kept in its own file at the root, like the recurring
+/// templates, from `cache` unless the file has changed.
+fn read_list<T: Clone>(
+    path: &Path,
+    cache: &RefCell<(FileStamp, Vec<T>)>,
+    fresh: bool,
+    parse: impl FnOnce(&str) -> Result<Vec<T>>,
+) -> Result<Vec<T>> {
+    let mut cache = cache.borrow_mut();
+    let (stamp, contents) = reread(path, cache.0, fresh)?;
+    if let Some(contents) = contents {
+        cache.1 = parse(&contents).map_err(|e| anyhow!("cannot read {}: {e}", path.display()))?;
+    }
+    cache.0 = stamp;
+    Ok(cache.1.clone())
+}
+
+/// Writes a list kept in its own file, removing the file when the list is empty.
+fn write_list<T>(
+    path: &Path,
+    cache: &mut (FileStamp, Vec<T>),
+    list: Vec<T>,
+    write: impl FnOnce(&[T], &mut Vec<u8>) -> Result<()>,
+) -> Result<()> {
+    let mut contents = Vec::new();
+    if list.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    } else {
+        write(&list, &mut contents)?;
+        write_atomically(path, &contents)?;
+    }
+    *cache = (stamp(path, &contents)?, list);
+    Ok(())
+}
+
+/// Reads a file again if it has changed since `stamp`, or always when
+/// `fresh`, and returns its new stamp along with its contents if they are not
+/// the ones last seen. A missing file reads as empty.
+fn reread(path: &Path, stamp: FileStamp, fresh: bool) -> Result<(FileStamp, Option<String>)> {
+    let changed = changed(path)?;
+    if changed == stamp.changed && !fresh {
+        return Ok((stamp, None));
+    }
+    let contents = match changed {
+        Some(_) => fs::read_to_string(path)?,
+        None => String::new(),
+    };
+    let hash = hash(contents.as_bytes());
+    let contents = (hash != stamp.hash).then_some(contents);
+    Ok((FileStamp { changed, hash }, contents))
+}
+
+/// The stamp of a file just written with `contents`, or just removed.
+fn stamp(path: &Path, contents: &[u8]) -> Result<FileStamp> {
+    Ok(FileStamp {
+        changed: changed(path)?,
+        hash: hash(contents),
+    })
+}
+
+fn changed(path: &Path) -> Result<Option<(SystemTime, u64)>> {
+    match fs::metadata(path) {
+        Ok(metadata) => Ok(Some((metadata.modified()?, metadata.len()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(contents);
+    hasher.finish()
+}
+
+fn numbered_entries(dir: &Path) -> Vec<(u32, PathBuf)> {
+    let mut entries: Vec<(u32, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|x| {
+            let path = x.path();
+            let number = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((number, path))
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+impl Model for MarkdownModel {
+    fn new_event(&mut self, date: Date, index: usize) -> Result<()> {
+        self.modify(date, |day| {
+            if index > day.events.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            day.events.insert(
+                index,
+                Event {
+                    uid: new_uid(),
+                    ..Default::default()
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn new_task(&mut self, date: Date, index: usize) -> Result<()> {
+        self.modify(date, |day| {
+            if index > day.tasks.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            day.tasks.insert(
+                index,
+                Task {
+                    uid: new_uid(),
+                    ..Default::default()
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn delete_event(&mut self, date: Date, index: usize) -> Result<()> {
+        self.delete_entry(date, index, |day| {
+            (index < day.events.len()).then(|| Entry::Event(day.events.remove(index)))
+        })
+    }
+
+    fn delete_task(&mut self, date: Date, index: usize) -> Result<()> {
+        self.delete_entry(date, index, |day| {
+            (index < day.tasks.len()).then(|| Entry::Task(day.tasks.remove(index)))
+        })
+    }
+
+    fn get_event(&self, date: Date, index: usize) -> Result<Event> {
+        self.day(date)
+            .events
+            .get(index)
+            .cloned()
+            .ok_or(anyhow!("index out of bounds"))
+    }
+
+    fn get_task(&self, date: Date, index: usize) -> Result<Task> {
+        self.day(date)
+            .tasks
+            .get(index)
+            .cloned()
+            .ok_or(anyhow!("index out of bounds"))
+    }
+
+    fn replace_event(&mut self, date: Date, index: usize, event: Event) -> Result<()> {
+        self.modify(date, |day| {
+            let old = day
+                .events
+                .get_mut(index)
+                .ok_or(anyhow!("index out of bounds"))?;
+            let uid = kept_uid(&old.uid, event.uid);
+            *old = Event { uid, ..event };
+            Ok(())
+        })
+    }
+
+    fn replace_task(&mut self, date: Date, index: usize, task: Task) -> Result<()> {
+        self.modify(date, |day| {
+            let old = day
+                .tasks
+                .get_mut(index)
+                .ok_or(anyhow!("index out of bounds"))?;
+            let uid = kept_uid(&old.uid, task.uid);
+            *old = Task { uid, ..task };
+            Ok(())
+        })
+    }
+
+    fn move_event(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        self.move_entry(from, to, |source, dest| {
+            if index >= source.events.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            let event = source.events.remove(index);
+            let dest = dest.unwrap_or(source);
+            if to_index > dest.events.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            dest.events.insert(to_index, event);
+            Ok(())
+        })
+    }
+
+    fn move_task(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        self.move_entry(from, to, |source, dest| {
+            if index >= source.tasks.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            let task = source.tasks.remove(index);
+            let dest = dest.unwrap_or(source);
+            if to_index > dest.tasks.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            dest.tasks.insert(to_index, task);
+            Ok(())
+        })
+    }
+
+    fn events_len(&self, date: Date) -> usize {
+        self.day(date).events.len()
+    }
+
+    fn tasks_len(&self, date: Date) -> usize {
+        self.day(date).tasks.len()
+    }
+
+    fn events_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Event> + 'a> {
+        Box::new(self.day(date).events.into_iter())
+    }
+
+    fn tasks_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Task> + 'a> {
+        Box::new(self.day(date).tasks.into_iter())
+    }
+
+    fn dates(&self) -> Vec<Date> {
+        self.days()
+            .into_iter()
+            .filter(|(_, day)| !day.events.is_empty() || !day.tasks.is_empty())
+            .map(|(date, _)| date)
+            .collect()
+    }
+
+    fn templates(&self) -> Vec<Template> {
+        self.read_templates(false).unwrap_or_else(|e| {
+            self.fail(e);
+            Vec::new()
+        })
+    }
+
+    fn add_template(&mut self, template: Template) -> Result<()> {
+        self.modify_templates(|templates| {
+            templates.push(template);
+            Ok(())
+        })
+    }
+
+    fn replace_template(&mut self, index: usize, template: Template) -> Result<()> {
+        self.modify_templates(|templates| {
+            let old = templates
+                .get_mut(index)
+                .ok_or(anyhow!("index out of bounds"))?;
+            *old = template;
+            Ok(())
+        })
+    }
+
+    fn delete_template(&mut self, index: usize) -> Result<()> {
+        self.modify_templates(|templates| {
+            if index >= templates.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            templates.remove(index);
+            Ok(())
+        })
+    }
+
+    fn realized(&self, date: Date) -> BTreeSet<String> {
+        self.read_realized(false)
+            .unwrap_or_else(|e| {
+                self.fail(e);
+                Vec::new()
+            })
+            .into_iter()
+            .filter(|(x, _)| *x == date)
+            .map(|(_, uid)| uid)
+            .collect()
+    }
+
+    fn set_realized(&mut self, date: Date, uid: &str) -> Result<()> {
+        let mut realized = match self.read_realized(true) {
+            Ok(realized) => realized,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        let item = (date, uid.to_string());
+        if !realized.contains(&item) {
+            realized.push(item);
+            realized.sort();
+            let path = self.realized_path();
+            if let Err(e) = write_list(
+                &path,
+                self.realized.get_mut(),
+                realized,
+                markdown::write_realized,
+            ) {
+                self.fail(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn mood(&self, date: Date) -> Mood {
+        self.day(date).mood
+    }
+
+    fn set_mood(&mut self, date: Date, mood: Mood) -> Result<()> {
+        self.modify(date, |day| {
+            day.mood = mood;
+            Ok(())
+        })
+    }
+
+    fn journal(&self, date: Date) -> String {
+        self.day(date).journal
+    }
+
+    fn set_journal(&mut self, date: Date, text: &str) -> Result<()> {
+        self.modify(date, |day| {
+            day.journal = text.to_string();
+            Ok(())
+        })
+    }
+
+    fn journals(&self) -> Vec<(Date, String)> {
+        self.days()
+            .into_iter()
+            .filter(|(_, day)| !day.journal.is_empty())
+            .map(|(date, day)| (date, day.journal))
+            .collect()
+    }
+
+    fn filled(&self, date: Date) -> bool {
+        self.read_filled(false)
+            .unwrap_or_else(|e| {
+                self.fail(e);
+                Vec::new()
+            })
+            .contains(&date)
+    }
+
+    fn set_filled(&mut self, date: Date) -> Result<()> {
+        let mut filled = match self.read_filled(true) {
+            Ok(filled) => filled,
+            Err(e) => {
+                self.fail(e);
+                return Ok(());
+            }
+        };
+        if !filled.contains(&date) {
+            filled.push(date);
+            filled.sort();
+            let path = self.filled_path();
+            if let Err(e) = write_list(&path, self.filled.get_mut(), filled, markdown::write_filled)
+            {
+                self.fail(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn trash(&self) -> Vec<Trashed> {
+        self.read_trash(false).unwrap_or_else(|e| {
+            self.fail(e);
+            Vec::new()
+        })
+    }
+
+    fn purge(&mut self, index: usize) -> Result<()> {
+        self.modify_trash(|trash| {
+            if index >= trash.len() {
+                return Err(anyhow!("index out of bounds"));
+            }
+            trash.remove(index);
+            Ok(())
+        })
+    }
+
+    fn err(&self) -> Result<()> {
+        if let Some(e) = self.error.borrow().as_ref() {
+            return Err(anyhow!(
+                "The journal directory has encountered an unrecoverable error: {e}. This can occur when a day's file is edited into an unreadable state or the permissions are changed while this program is running. You must quit the app now."
+            ));
+        }
+        Ok(())
+    }
+}
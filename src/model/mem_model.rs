@@ -1,12 +1,12 @@
 use anyhow::{Result, anyhow};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::iter;
 use time::Date;
 
-use super::{Event, Model, Task};
+use super::{Event, Model, Mood, Task, Template, Trashed, kept_uid, new_uid, today};
 
 #[derive(Default)]
-pub struct MemModel(HashMap<Date, Entry>);
+pub struct MemModel(HashMap<Date, Entry>, Vec<Template>, Vec<Trashed>);
 
 impl Model for MemModel {
     fn new_event(&mut self, date: Date, index: usize) -> Result<()> {
@@ -16,7 +16,13 @@ impl Model for MemModel {
             return Err(anyhow!("index out of bounds"));
         }
 
-        events.insert(index, Event::default());
+        events.insert(
+            index,
+            Event {
+                uid: new_uid(),
+                ..Default::default()
+            },
+        );
         Ok(())
     }
 
@@ -27,7 +33,13 @@ impl Model for MemModel {
             return Err(anyhow!("index out of bounds"));
         }
 
-        tasks.insert(index, Task::default());
+        tasks.insert(
+            index,
+            Task {
+                uid: new_uid(),
+                ..Default::default()
+            },
+        );
         Ok(())
     }
 
@@ -35,7 +47,13 @@ impl Model for MemModel {
         if let Some(entry) = self.0.get_mut(&date)
             && index < entry.events.len()
         {
-            entry.events.remove(index);
+            let event = entry.events.remove(index);
+            self.2.push(Trashed {
+                date,
+                index,
+                deleted: today(),
+                entry: super::Entry::Event(event),
+            });
             return Ok(());
         }
 
@@ -46,7 +64,13 @@ impl Model for MemModel {
         if let Some(entry) = self.0.get_mut(&date)
             && index < entry.tasks.len()
         {
-            entry.tasks.remove(index);
+            let task = entry.tasks.remove(index);
+            self.2.push(Trashed {
+                date,
+                index,
+                deleted: today(),
+                entry: super::Entry::Task(task),
+            });
             return Ok(());
         }
 
@@ -85,7 +109,8 @@ impl Model for MemModel {
         if let Some(entry) = self.0.get_mut(&date)
             && index < entry.events.len()
         {
-            entry.events[index] = event;
+            let uid = kept_uid(&entry.events[index].uid, event.uid);
+            entry.events[index] = Event { uid, ..event };
             return Ok(());
         }
 
@@ -96,13 +121,36 @@ impl Model for MemModel {
         if let Some(entry) = self.0.get_mut(&date)
             && index < entry.tasks.len()
         {
-            entry.tasks[index] = task;
+            let uid = kept_uid(&entry.tasks[index].uid, task.uid);
+            entry.tasks[index] = Task { uid, ..task };
             return Ok(());
         }
 
         Err(anyhow!("index out of bounds"))
     }
 
+    fn move_event(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        let len = self.events_len(to).saturating_sub(usize::from(from == to));
+        if index >= self.events_len(from) || to_index > len {
+            return Err(anyhow!("index out of bounds"));
+        }
+
+        let event = self.0.entry(from).or_default().events.remove(index);
+        self.0.entry(to).or_default().events.insert(to_index, event);
+        Ok(())
+    }
+
+    fn move_task(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        let len = self.tasks_len(to).saturating_sub(usize::from(from == to));
+        if index >= self.tasks_len(from) || to_index > len {
+            return Err(anyhow!("index out of bounds"));
+        }
+
+        let task = self.0.entry(from).or_default().tasks.remove(index);
+        self.0.entry(to).or_default().tasks.insert(to_index, task);
+        Ok(())
+    }
+
     fn events_len(&self, date: Date) -> usize {
         self.0
             .get(&date)
@@ -125,6 +173,117 @@ impl Model for MemModel {
         })
     }
 
+    fn dates(&self) -> Vec<Date> {
+        let mut dates: Vec<Date> = self
+            .0
+            .iter()
+            .filter(|(_, entry)| !entry.events.is_empty() || !entry.tasks.is_empty())
+            .map(|(date, _)| *date)
+            .collect();
+        dates.sort();
+        dates
+    }
+
+    fn templates(&self) -> Vec<Template> {
+        self.1.clone()
+    }
+
+    fn add_template(&mut self, template: Template) -> Result<()> {
+        self.1.push(template);
+        Ok(())
+    }
+
+    fn replace_template(&mut self, index: usize, template: Template) -> Result<()> {
+        let old = self
+            .1
+            .get_mut(index)
+            .ok_or(anyhow!("index out of bounds"))?;
+        *old = template;
+        Ok(())
+    }
+
+    fn delete_template(&mut self, index: usize) -> Result<()> {
+        if index < self.1.len() {
+            self.1.remove(index);
+            return Ok(());
+        }
+
+        Err(anyhow!("index out of bounds"))
+    }
+
+    fn realized(&self, date: Date) -> BTreeSet<String> {
+        self.0
+            .get(&date)
+            .map(|x| x.realized.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_realized(&mut self, date: Date, uid: &str) -> Result<()> {
+        self.0
+            .entry(date)
+            .or_default()
+            .realized
+            .insert(uid.to_string());
+        Ok(())
+    }
+
+    fn trash(&self) -> Vec<Trashed> {
+        self.2.clone()
+    }
+
+    fn purge(&mut self, index: usize) -> Result<()> {
+        if index < self.2.len() {
+            self.2.remove(index);
+            return Ok(());
+        }
+
+        Err(anyhow!("index out of bounds"))
+    }
+
+    fn mood(&self, date: Date) -> Mood {
+        self.0
+            .get(&date)
+            .map(|x| x.mood.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_mood(&mut self, date: Date, mood: Mood) -> Result<()> {
+        self.0.entry(date).or_default().mood = mood;
+        Ok(())
+    }
+
+    fn journal(&self, date: Date) -> String {
+        self.0
+            .get(&date)
+            .map(|x| x.journal.clone())
+            .unwrap_or_default()
+    }
+
+    fn set_journal(&mut self, date: Date, text: &str) -> Result<()> {
+        self.0.entry(date).or_default().journal = text.to_string();
+        Ok(())
+    }
+
+    fn journals(&self) -> Vec<(Date, String)> {
+        let mut journals: Vec<(Date, String)> = self
+            .0
+            .iter()
+            .filter(|(_, x)| !x.journal.is_empty())
+            .map(|(date, x)| (*date, x.journal.clone()))
+            .collect();
+        journals.sort();
+        journals
+    }
+
+    fn filled(&self, date: Date) -> bool {
+        self.0.get(&date).is_some_and(|x| x.filled)
+    }
+
+    fn set_filled(&mut self, date: Date) -> Result<()> {
+        self.0.entry(date).or_default().filled = true;
+        Ok(())
+    }
+
     fn err(&self) -> Result<()> {
         Ok(())
     }
@@ -134,4 +293,8 @@ impl Model for MemModel {
 struct Entry {
     events: Vec<Event>,
     tasks: Vec<Task>,
+    mood: Mood,
+    journal: String,
+    filled: bool,
+    realized: BTreeSet<String>,
 }
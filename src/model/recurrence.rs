@@ -0,0 +1,501 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Error, Result, anyhow, bail};
+use time::{Date, Month, Weekday};
+
+use super::Entry;
+use crate::interchange::parse_date;
+
+/// An entry that is added to every date of its recurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub recurrence: Recurrence,
+    pub entry: Entry,
+}
+
+/// The dates a template happens on, modelled on a subset of the iCalendar
+/// RRULE. Written as text, it reads like
+/// `DTSTART=20251014;FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10;EXDATE=20251015`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub start: Date,
+    pub frequency: Frequency,
+    pub end: End,
+    /// dates that are left out, either because they were skipped or because
+    /// the entry on that date has been written to the journal
+    pub except: BTreeSet<Date>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekdays,
+    /// on each of the given days of the week
+    Weekly(Vec<Weekday>),
+    /// on the given day of the month, skipping months that are too short
+    MonthlyByDate(u8),
+    /// on the nth given day of the week in the month, counting from the end
+    /// of the month when negative
+    MonthlyByWeekday(i8, Weekday),
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum End {
+    #[default]
+    Never,
+    Until(Date),
+    /// after this many dates, including any that are left out
+    Count(u32),
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+impl Recurrence {
+    pub fn new(start: Date, frequency: Frequency, end: End) -> Self {
+        Self {
+            start,
+            frequency,
+            end,
+            except: BTreeSet::new(),
+        }
+    }
+
+    /// Whether the template should be added to `date`.
+    pub fn occurs_on(&self, date: Date) -> bool {
+        if date < self.start || !self.frequency.matches(date) || self.except.contains(&date) {
+            return false;
+        }
+        match self.end {
+            End::Never => true,
+            End::Until(until) => date <= until,
+            End::Count(count) => {
+                let mut seen = 0;
+                let mut day = self.start;
+                while day <= date {
+                    if self.frequency.matches(day) {
+                        seen += 1;
+                        if seen > count {
+                            return false;
+                        }
+                    }
+                    day = day.next_day().expect("we will never reach max date");
+                }
+                true
+            }
+        }
+    }
+
+    /// A short description for people, like `every Mon, Wed until 2025-12-31`.
+    pub fn describe(&self) -> String {
+        let mut description = match &self.frequency {
+            Frequency::Daily => "every day".to_string(),
+            Frequency::Weekdays => "every weekday".to_string(),
+            Frequency::Weekly(days) => format!(
+                "every {}",
+                days.iter()
+                    .map(|x| short_name(*x))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Frequency::MonthlyByDate(day) => format!("monthly on day {day}"),
+            Frequency::MonthlyByWeekday(-1, day) => {
+                format!("monthly on the last {}", short_name(*day))
+            }
+            Frequency::MonthlyByWeekday(n, day) => {
+                format!("monthly on the {} {}", ordinal(*n), short_name(*day))
+            }
+        };
+        description += &format!(" from {}", self.start);
+        match self.end {
+            End::Never => {}
+            End::Until(until) => description += &format!(" until {until}"),
+            End::Count(count) => description += &format!(", {count} times"),
+        }
+        description
+    }
+}
+
+impl Frequency {
+    fn matches(&self, date: Date) -> bool {
+        match self {
+            Frequency::Daily => true,
+            Frequency::Weekdays => !matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday),
+            Frequency::Weekly(days) => days.contains(&date.weekday()),
+            Frequency::MonthlyByDate(day) => date.day() == *day,
+            Frequency::MonthlyByWeekday(n, day) => {
+                let nth = (date.day() as i8 - 1) / 7 + 1;
+                let nth_from_end = -((days_in_month(date) as i8 - date.day() as i8) / 7 + 1);
+                date.weekday() == *day && (*n == nth || *n == nth_from_end)
+            }
+        }
+    }
+}
+
+/// Splits a trailing recurrence typed into a title, like `standup every:weekday`
+/// or `rent every:month until:2026-06-30`, off the end of the title. The
+/// recurrence starts on `start`.
+///
+/// `every:` takes `day`, `weekday`, `week`, `month`, a list of days like
+/// `mon,wed`, or a day of the month like `2nd-tue` or `last-fri`. It can be
+/// followed by `until:YYYY-MM-DD` or `count:N`.
+pub fn split_recurrence(title: &str, start: Date) -> (&str, Option<Recurrence>) {
+    let (rest, last) = title.rsplit_once(' ').unwrap_or(("", title));
+    let (rest, every, end) = if let Some(every) = last.strip_prefix("every:") {
+        (rest, every, Some(End::Never))
+    } else {
+        let (rest, every) = rest.rsplit_once(' ').unwrap_or(("", rest));
+        let end = if let Some(until) = last.strip_prefix("until:") {
+            parse_date(until).ok().map(End::Until)
+        } else if let Some(count) = last.strip_prefix("count:") {
+            count.parse().ok().filter(|x| *x > 0).map(End::Count)
+        } else {
+            None
+        };
+        match every.strip_prefix("every:") {
+            Some(every) => (rest, every, end),
+            None => return (title, None),
+        }
+    };
+    match (parse_every(every, start), end) {
+        (Some(frequency), Some(end)) => (rest, Some(Recurrence::new(start, frequency, end))),
+        _ => (title, None),
+    }
+}
+
+fn parse_every(every: &str, start: Date) -> Option<Frequency> {
+    let every = every.to_lowercase();
+    Some(match every.as_str() {
+        "day" | "daily" => Frequency::Daily,
+        "weekday" | "weekdays" => Frequency::Weekdays,
+        "week" | "weekly" => Frequency::Weekly(vec![start.weekday()]),
+        "month" | "monthly" => Frequency::MonthlyByDate(start.day()),
+        _ => {
+            if let Some((nth, day)) = every.split_once('-') {
+                let n = match nth {
+                    "1st" => 1,
+                    "2nd" => 2,
+                    "3rd" => 3,
+                    "4th" => 4,
+                    "5th" => 5,
+                    "last" => -1,
+                    _ => return None,
+                };
+                Frequency::MonthlyByWeekday(n, WEEKDAYS[parse_weekday(day)?])
+            } else {
+                let days = every
+                    .split(',')
+                    .map(parse_weekday)
+                    .collect::<Option<BTreeSet<_>>>()?;
+                Frequency::Weekly(weekdays(days))
+            }
+        }
+    })
+}
+
+/// Parses the first three letters of an English day name, like `mon`, into
+/// an index into [`WEEKDAYS`].
+fn parse_weekday(name: &str) -> Option<usize> {
+    let name = name.get(..3)?;
+    WEEKDAYS
+        .iter()
+        .position(|x| short_name(*x).eq_ignore_ascii_case(name))
+}
+
+/// Turns indices into [`WEEKDAYS`] back into days, from Monday to Sunday.
+fn weekdays(days: BTreeSet<usize>) -> Vec<Weekday> {
+    days.into_iter().map(|x| WEEKDAYS[x]).collect()
+}
+
+fn short_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Monday => "Mon",
+        Weekday::Tuesday => "Tue",
+        Weekday::Wednesday => "Wed",
+        Weekday::Thursday => "Thu",
+        Weekday::Friday => "Fri",
+        Weekday::Saturday => "Sat",
+        Weekday::Sunday => "Sun",
+    }
+}
+
+fn ordinal(n: i8) -> String {
+    match n {
+        1 => "1st".to_string(),
+        2 => "2nd".to_string(),
+        3 => "3rd".to_string(),
+        n => format!("{n}th"),
+    }
+}
+
+fn days_in_month(date: Date) -> u8 {
+    time::util::days_in_month(date.month(), date.year())
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DTSTART={}", format_date(self.start))?;
+        match &self.frequency {
+            Frequency::Daily => write!(f, ";FREQ=DAILY")?,
+            Frequency::Weekdays => write!(f, ";FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR")?,
+            Frequency::Weekly(days) => write!(f, ";FREQ=WEEKLY;BYDAY={}", rrule_days(days))?,
+            Frequency::MonthlyByDate(day) => write!(f, ";FREQ=MONTHLY;BYMONTHDAY={day}")?,
+            Frequency::MonthlyByWeekday(n, day) => {
+                write!(f, ";FREQ=MONTHLY;BYDAY={n}{}", rrule_day(*day))?
+            }
+        }
+        match self.end {
+            End::Never => {}
+            End::Until(until) => write!(f, ";UNTIL={}", format_date(until))?,
+            End::Count(count) => write!(f, ";COUNT={count}")?,
+        }
+        if !self.except.is_empty() {
+            let except: Vec<String> = self.except.iter().map(|x| format_date(*x)).collect();
+            write!(f, ";EXDATE={}", except.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || anyhow!("invalid recurrence `{s}`");
+        let (mut start, mut freq, mut by_day, mut by_month_day) = (None, None, None, None);
+        let mut end = End::Never;
+        let mut except = BTreeSet::new();
+        for part in s.split(';') {
+            let (key, value) = part.split_once('=').ok_or_else(err)?;
+            match key {
+                "DTSTART" => start = Some(parse_rrule_date(value)?),
+                "FREQ" => freq = Some(value),
+                "BYDAY" => by_day = Some(value),
+                "BYMONTHDAY" => by_month_day = Some(value.parse().map_err(|_| err())?),
+                "UNTIL" => end = End::Until(parse_rrule_date(value)?),
+                "COUNT" => end = End::Count(value.parse().map_err(|_| err())?),
+                "EXDATE" => {
+                    for date in value.split(',') {
+                        except.insert(parse_rrule_date(date)?);
+                    }
+                }
+                _ => bail!("unsupported recurrence part `{part}` in `{s}`"),
+            }
+        }
+
+        let frequency = match (freq, by_day, by_month_day) {
+            (Some("DAILY"), None, None) => Frequency::Daily,
+            (Some("DAILY"), Some("MO,TU,WE,TH,FR"), None) => Frequency::Weekdays,
+            (Some("WEEKLY"), Some(days), None) => {
+                let days = days
+                    .split(',')
+                    .map(|x| parse_rrule_day(x).ok_or_else(err))
+                    .collect::<Result<BTreeSet<_>>>()?;
+                Frequency::Weekly(weekdays(days))
+            }
+            (Some("MONTHLY"), None, Some(day @ 1..=31)) => Frequency::MonthlyByDate(day),
+            (Some("MONTHLY"), Some(day), None) => {
+                let split = day.len().checked_sub(2).ok_or_else(err)?;
+                let n = day[..split].parse().map_err(|_| err())?;
+                let day = parse_rrule_day(&day[split..]).ok_or_else(err)?;
+                if !matches!(n, -5..=-1 | 1..=5) {
+                    return Err(err());
+                }
+                Frequency::MonthlyByWeekday(n, WEEKDAYS[day])
+            }
+            _ => return Err(err()),
+        };
+        Ok(Self {
+            start: start.ok_or_else(err)?,
+            frequency,
+            end,
+            except,
+        })
+    }
+}
+
+fn rrule_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Monday => "MO",
+        Weekday::Tuesday => "TU",
+        Weekday::Wednesday => "WE",
+        Weekday::Thursday => "TH",
+        Weekday::Friday => "FR",
+        Weekday::Saturday => "SA",
+        Weekday::Sunday => "SU",
+    }
+}
+
+fn rrule_days(days: &[Weekday]) -> String {
+    days.iter()
+        .map(|x| rrule_day(*x))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_rrule_day(day: &str) -> Option<usize> {
+    WEEKDAYS
+        .iter()
+        .position(|x| rrule_day(*x).eq_ignore_ascii_case(day))
+}
+
+fn format_date(date: Date) -> String {
+    format!(
+        "{:04}{:02}{:02}",
+        date.year(),
+        date.month() as u8,
+        date.day()
+    )
+}
+
+fn parse_rrule_date(value: &str) -> Result<Date> {
+    let err = || anyhow!("invalid date `{value}`");
+    if value.len() != 8 || !value.bytes().all(|x| x.is_ascii_digit()) {
+        return Err(err());
+    }
+    Ok(Date::from_calendar_date(
+        value[..4].parse()?,
+        Month::try_from(value[4..6].parse::<u8>()?)?,
+        value[6..].parse()?,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::*;
+
+    /// a Tuesday
+    fn date() -> Date {
+        Date::from_calendar_date(2025, Month::October, 14).unwrap()
+    }
+
+    fn occurrences(recurrence: &Recurrence, days: i64) -> Vec<Date> {
+        (0..days)
+            .map(|x| date() + Duration::days(x))
+            .filter(|x| recurrence.occurs_on(*x))
+            .collect()
+    }
+
+    fn day(day: u8) -> Date {
+        date().replace_day(day).unwrap()
+    }
+
+    #[test]
+    fn expands_frequencies() {
+        let weekdays = Recurrence::new(date(), Frequency::Weekdays, End::Count(4));
+        assert_eq!(
+            occurrences(&weekdays, 14),
+            [day(14), day(15), day(16), day(17)]
+        );
+
+        let mut weekly = Recurrence::new(
+            date(),
+            Frequency::Weekly(vec![Weekday::Monday, Weekday::Wednesday]),
+            End::Until(day(22)),
+        );
+        weekly.except.insert(day(20));
+        assert_eq!(occurrences(&weekly, 14), [day(15), day(22)]);
+
+        let by_date = Recurrence::new(date(), Frequency::MonthlyByDate(31), End::Never);
+        assert_eq!(
+            occurrences(&by_date, 100),
+            [
+                day(31),
+                Date::from_calendar_date(2025, Month::December, 31).unwrap()
+            ]
+        );
+
+        let last_friday = Recurrence::new(
+            date(),
+            Frequency::MonthlyByWeekday(-1, Weekday::Friday),
+            End::Never,
+        );
+        assert_eq!(
+            occurrences(&last_friday, 50),
+            [
+                day(31),
+                Date::from_calendar_date(2025, Month::November, 28).unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut recurrences = [
+            Recurrence::new(date(), Frequency::Daily, End::Never),
+            Recurrence::new(date(), Frequency::Weekdays, End::Count(3)),
+            Recurrence::new(
+                date(),
+                Frequency::Weekly(vec![Weekday::Monday, Weekday::Sunday]),
+                End::Until(day(30)),
+            ),
+            Recurrence::new(date(), Frequency::MonthlyByDate(14), End::Never),
+            Recurrence::new(
+                date(),
+                Frequency::MonthlyByWeekday(2, Weekday::Tuesday),
+                End::Never,
+            ),
+        ];
+        recurrences[0].except.extend([day(15), day(16)]);
+        for recurrence in recurrences {
+            assert_eq!(
+                recurrence.to_string().parse::<Recurrence>().unwrap(),
+                recurrence
+            );
+        }
+        assert_eq!(
+            "DTSTART=20251014;FREQ=MONTHLY;BYDAY=-1FR;EXDATE=20251031"
+                .parse::<Recurrence>()
+                .unwrap()
+                .to_string(),
+            "DTSTART=20251014;FREQ=MONTHLY;BYDAY=-1FR;EXDATE=20251031"
+        );
+        assert!("FREQ=DAILY".parse::<Recurrence>().is_err());
+        assert!(
+            "DTSTART=20251014;FREQ=YEARLY"
+                .parse::<Recurrence>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn splits_recurrences_off_titles() {
+        let (title, recurrence) = split_recurrence("standup every:weekday", date());
+        assert_eq!(title, "standup");
+        assert_eq!(recurrence.unwrap().frequency, Frequency::Weekdays);
+
+        let (title, recurrence) = split_recurrence("gym every:mon,thu count:8", date());
+        let recurrence = recurrence.unwrap();
+        assert_eq!(title, "gym");
+        assert_eq!(
+            recurrence.frequency,
+            Frequency::Weekly(vec![Weekday::Monday, Weekday::Thursday])
+        );
+        assert_eq!(recurrence.end, End::Count(8));
+
+        let (_, recurrence) = split_recurrence("book club every:2nd-tue until:2026-06-30", date());
+        assert_eq!(
+            recurrence.unwrap().describe(),
+            "monthly on the 2nd Tue from 2025-10-14 until 2026-06-30"
+        );
+
+        for title in [
+            "every day",
+            "run every:fortnight",
+            "rent every:month until:soon",
+        ] {
+            assert_eq!(split_recurrence(title, date()), (title, None));
+        }
+    }
+}
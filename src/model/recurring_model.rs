@@ -0,0 +1,450 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Result, anyhow};
+use time::Date;
+
+use super::{
+    Activity, Change, Entry, Event, Found, Model, Mood, Task, Template, Trashed, kept_uid, new_uid,
+};
+
+/// Wraps a model and adds the occurrences of its recurring templates to each
+/// day, after the day's own events and tasks. Occurrences are worked out
+/// when a day is read, so templates that never end cost nothing until their
+/// days are looked at.
+///
+/// The first write to a day turns its occurrences into ordinary entries and
+/// records the day as realized for their templates, so each occurrence can be
+/// completed, edited or deleted (skipped) on its own without touching the
+/// template. Templates are given a UID when they are added, to record this
+/// by. [`Model::dates`] and the queries built on it only see days that have
+/// been written to.
+pub struct RecurringModel {
+    model: Box<dyn Model>,
+}
+
+impl RecurringModel {
+    pub fn new(model: Box<dyn Model>) -> Self {
+        Self { model }
+    }
+
+    fn occurrences(&self, date: Date) -> impl Iterator<Item = Entry> {
+        let realized = self.model.realized(date);
+        self.model
+            .templates()
+            .into_iter()
+            .filter(move |x| x.recurrence.occurs_on(date) && !realized.contains(x.entry.uid()))
+            .map(|x| x.entry.without_uid())
+    }
+
+    fn events(&self, date: Date) -> Vec<Event> {
+        self.occurrences(date)
+            .filter_map(|x| match x {
+                Entry::Event(event) => Some(event),
+                Entry::Task(_) => None,
+            })
+            .collect()
+    }
+
+    fn tasks(&self, date: Date) -> Vec<Task> {
+        self.occurrences(date)
+            .filter_map(|x| match x {
+                Entry::Task(task) => Some(task),
+                Entry::Event(_) => None,
+            })
+            .collect()
+    }
+
+    /// Writes the day's occurrences to the wrapped model as ordinary entries,
+    /// in the order they are shown in.
+    fn realize(&mut self, date: Date) -> Result<()> {
+        let realized = self.model.realized(date);
+        for (i, template) in self.model.templates().into_iter().enumerate() {
+            if !template.recurrence.occurs_on(date) || realized.contains(template.entry.uid()) {
+                continue;
+            }
+            // templates from before they had UIDs are given one now
+            let template = match template.entry.uid() {
+                "" => {
+                    let template = with_new_uid(template);
+                    self.model.replace_template(i, template.clone())?;
+                    template
+                }
+                _ => template,
+            };
+            match template.entry.clone().without_uid() {
+                Entry::Event(event) => {
+                    let idx = self.model.events_len(date);
+                    self.model.new_event(date, idx)?;
+                    self.model.replace_event(date, idx, event)?;
+                }
+                Entry::Task(task) => {
+                    let idx = self.model.tasks_len(date);
+                    self.model.new_task(date, idx)?;
+                    self.model.replace_task(date, idx, task)?;
+                }
+            }
+            self.model.set_realized(date, template.entry.uid())?;
+        }
+        Ok(())
+    }
+}
+
+/// The template with a UID of its own, in place of any it had.
+fn with_new_uid(template: Template) -> Template {
+    with_uid(template, new_uid())
+}
+
+fn with_uid(template: Template, uid: String) -> Template {
+    let entry = match template.entry {
+        Entry::Event(event) => Entry::Event(Event { uid, ..event }),
+        Entry::Task(task) => Entry::Task(Task { uid, ..task }),
+    };
+    Template { entry, ..template }
+}
+
+impl Model for RecurringModel {
+    fn new_event(&mut self, date: Date, index: usize) -> Result<()> {
+        self.realize(date)?;
+        self.model.new_event(date, index)
+    }
+
+    fn new_task(&mut self, date: Date, index: usize) -> Result<()> {
+        self.realize(date)?;
+        self.model.new_task(date, index)
+    }
+
+    fn delete_event(&mut self, date: Date, index: usize) -> Result<()> {
+        self.realize(date)?;
+        self.model.delete_event(date, index)
+    }
+
+    fn delete_task(&mut self, date: Date, index: usize) -> Result<()> {
+        self.realize(date)?;
+        self.model.delete_task(date, index)
+    }
+
+    fn get_event(&self, date: Date, index: usize) -> Result<Event> {
+        let len = self.model.events_len(date);
+        if index < len {
+            return self.model.get_event(date, index);
+        }
+        self.events(date)
+            .into_iter()
+            .nth(index - len)
+            .ok_or(anyhow!("index out of bounds"))
+    }
+
+    fn get_task(&self, date: Date, index: usize) -> Result<Task> {
+        let len = self.model.tasks_len(date);
+        if index < len {
+            return self.model.get_task(date, index);
+        }
+        self.tasks(date)
+            .into_iter()
+            .nth(index - len)
+            .ok_or(anyhow!("index out of bounds"))
+    }
+
+    fn replace_event(&mut self, date: Date, index: usize, event: Event) -> Result<()> {
+        self.realize(date)?;
+        self.model.replace_event(date, index, event)
+    }
+
+    fn replace_task(&mut self, date: Date, index: usize, task: Task) -> Result<()> {
+        self.realize(date)?;
+        self.model.replace_task(date, index, task)
+    }
+
+    fn move_event(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        self.realize(from)?;
+        self.realize(to)?;
+        self.model.move_event(from, index, to, to_index)
+    }
+
+    fn move_task(&mut self, from: Date, index: usize, to: Date, to_index: usize) -> Result<()> {
+        self.realize(from)?;
+        self.realize(to)?;
+        self.model.move_task(from, index, to, to_index)
+    }
+
+    fn begin(&mut self) {
+        self.model.begin();
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.model.commit()
+    }
+
+    fn events_len(&self, date: Date) -> usize {
+        self.model.events_len(date) + self.events(date).len()
+    }
+
+    fn tasks_len(&self, date: Date) -> usize {
+        self.model.tasks_len(date) + self.tasks(date).len()
+    }
+
+    fn events_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Event> + 'a> {
+        Box::new(self.model.events_iter(date).chain(self.events(date)))
+    }
+
+    fn tasks_iter<'a>(&'a self, date: Date) -> Box<dyn Iterator<Item = Task> + 'a> {
+        Box::new(self.model.tasks_iter(date).chain(self.tasks(date)))
+    }
+
+    fn dates(&self) -> Vec<Date> {
+        self.model.dates()
+    }
+
+    fn templates(&self) -> Vec<Template> {
+        self.model.templates()
+    }
+
+    fn add_template(&mut self, template: Template) -> Result<()> {
+        self.model.add_template(with_new_uid(template))
+    }
+
+    fn replace_template(&mut self, index: usize, template: Template) -> Result<()> {
+        let old = self
+            .model
+            .templates()
+            .into_iter()
+            .nth(index)
+            .ok_or(anyhow!("index out of bounds"))?;
+        let uid = kept_uid(old.entry.uid(), template.entry.uid().to_string());
+        self.model.replace_template(index, with_uid(template, uid))
+    }
+
+    fn delete_template(&mut self, index: usize) -> Result<()> {
+        self.model.delete_template(index)
+    }
+
+    fn realized(&self, date: Date) -> BTreeSet<String> {
+        self.model.realized(date)
+    }
+
+    fn set_realized(&mut self, date: Date, uid: &str) -> Result<()> {
+        self.model.set_realized(date, uid)
+    }
+
+    fn tags(&self) -> Vec<(String, usize)> {
+        self.model.tags()
+    }
+
+    fn tagged(&self, tag: &str) -> Vec<(Date, Entry)> {
+        self.model.tagged(tag)
+    }
+
+    fn due_tasks(&self, until: Date) -> Vec<(Date, Task)> {
+        self.model.due_tasks(until)
+    }
+
+    fn mood(&self, date: Date) -> Mood {
+        self.model.mood(date)
+    }
+
+    fn set_mood(&mut self, date: Date, mood: Mood) -> Result<()> {
+        self.model.set_mood(date, mood)
+    }
+
+    fn moods(&self, from: Date, to: Date) -> BTreeMap<Date, Mood> {
+        self.model.moods(from, to)
+    }
+
+    fn journal(&self, date: Date) -> String {
+        self.model.journal(date)
+    }
+
+    fn set_journal(&mut self, date: Date, text: &str) -> Result<()> {
+        self.model.set_journal(date, text)
+    }
+
+    fn journals(&self) -> Vec<(Date, String)> {
+        self.model.journals()
+    }
+
+    fn filled(&self, date: Date) -> bool {
+        self.model.filled(date)
+    }
+
+    fn set_filled(&mut self, date: Date) -> Result<()> {
+        self.model.set_filled(date)
+    }
+
+    fn search(&self, query: &str) -> Vec<(Date, Found)> {
+        self.model.search(query)
+    }
+
+    fn activity(&self, from: Date, to: Date) -> BTreeMap<Date, Activity> {
+        self.model.activity(from, to)
+    }
+
+    fn event_history(&self, date: Date, index: usize) -> Vec<Change> {
+        self.model.event_history(date, index)
+    }
+
+    fn task_history(&self, date: Date, index: usize) -> Vec<Change> {
+        self.model.task_history(date, index)
+    }
+
+    fn habits(&self) -> Vec<String> {
+        self.model.habits()
+    }
+
+    fn add_habit(&mut self, name: &str) -> Result<()> {
+        self.model.add_habit(name)
+    }
+
+    fn remove_habit(&mut self, name: &str) -> Result<()> {
+        self.model.remove_habit(name)
+    }
+
+    fn habit_days(&self, name: &str) -> BTreeSet<Date> {
+        self.model.habit_days(name)
+    }
+
+    fn habits_on(&self, date: Date) -> Vec<(String, bool)> {
+        self.model.habits_on(date)
+    }
+
+    fn set_habit_done(&mut self, name: &str, date: Date, done: bool) -> Result<()> {
+        self.model.set_habit_done(name, date, done)
+    }
+
+    fn trash(&self) -> Vec<Trashed> {
+        self.model.trash()
+    }
+
+    fn purge(&mut self, index: usize) -> Result<()> {
+        self.model.purge(index)
+    }
+
+    fn restore(&mut self, index: usize) -> Result<()> {
+        if let Some(trashed) = self.model.trash().get(index) {
+            self.realize(trashed.date)?;
+        }
+        self.model.restore(index)
+    }
+
+    fn err(&self) -> Result<()> {
+        self.model.err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration, Month, Weekday};
+
+    use super::*;
+    use crate::model::recurrence::{End, Frequency};
+    use crate::model::{CompletionLevel, MemModel, Recurrence};
+
+    /// a Tuesday
+    fn date() -> Date {
+        Date::from_calendar_date(2025, Month::October, 14).unwrap()
+    }
+
+    fn journal() -> RecurringModel {
+        let mut model = RecurringModel::new(Box::new(MemModel::default()));
+        model
+            .add_template(Template {
+                recurrence: Recurrence::new(date(), Frequency::Weekdays, End::Never),
+                entry: Entry::Event(Event {
+                    title: "standup".to_string(),
+                    ..Default::default()
+                }),
+            })
+            .unwrap();
+        model
+            .add_template(Template {
+                recurrence: Recurrence::new(
+                    date(),
+                    Frequency::Weekly(vec![Weekday::Tuesday]),
+                    End::Count(2),
+                ),
+                entry: Entry::Task(Task {
+                    title: "water plants".to_string(),
+                    ..Default::default()
+                }),
+            })
+            .unwrap();
+        model
+    }
+
+    fn titles(model: &dyn Model, date: Date) -> (Vec<String>, Vec<String>) {
+        (
+            model.events_iter(date).map(|x| x.title).collect(),
+            model.tasks_iter(date).map(|x| x.title).collect(),
+        )
+    }
+
+    #[test]
+    fn occurrences_follow_the_day_entries() {
+        let mut model = journal();
+        model.model.new_event(date(), 0).unwrap();
+        model
+            .model
+            .replace_event(
+                date(),
+                0,
+                Event {
+                    title: "dentist".to_string(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            titles(&model, date()),
+            (
+                vec!["dentist".to_string(), "standup".to_string()],
+                vec!["water plants".to_string()]
+            )
+        );
+        assert_eq!(model.get_event(date(), 1).unwrap().title, "standup");
+        assert!(model.get_event(date(), 2).is_err());
+        assert_eq!(titles(&model, date() + Duration::days(1)).1.len(), 0);
+        assert_eq!(titles(&model, date() + Duration::days(4)).0.len(), 0);
+        assert_eq!(titles(&model, date() + Duration::days(7)).1.len(), 1);
+        assert_eq!(titles(&model, date() + Duration::days(14)).1.len(), 0);
+        assert!(model.dates().contains(&date()));
+        assert!(!model.dates().contains(&(date() + Duration::days(1))));
+    }
+
+    #[test]
+    fn occurrences_are_completed_and_skipped_one_at_a_time() {
+        let mut model = journal();
+        let next_week = date() + Duration::days(7);
+
+        let task = model.get_task(date(), 0).unwrap().cycle().cycle();
+        model.replace_task(date(), 0, task).unwrap();
+        model.delete_event(date(), 0).unwrap();
+
+        assert_eq!(titles(&model, date()).0.len(), 0);
+        assert_eq!(
+            model.get_task(date(), 0).unwrap().completion_level,
+            CompletionLevel::Full
+        );
+        assert_eq!(model.tasks_len(date()), 1);
+        assert_eq!(
+            model.get_task(next_week, 0).unwrap().completion_level,
+            CompletionLevel::None
+        );
+        assert_eq!(titles(&model, next_week).0, ["standup"]);
+        let standup = model.templates()[0].clone();
+        assert!(standup.recurrence.except.is_empty());
+        assert!(model.realized(date()).contains(standup.entry.uid()));
+        assert!(!model.realized(next_week).contains(standup.entry.uid()));
+
+        // a template added later still shows on a day that was written to
+        model
+            .add_template(Template {
+                recurrence: Recurrence::new(date(), Frequency::Daily, End::Never),
+                entry: Entry::Event(Event {
+                    title: "review".to_string(),
+                    ..Default::default()
+                }),
+            })
+            .unwrap();
+        assert_eq!(titles(&model, date()).0, ["review"]);
+    }
+}
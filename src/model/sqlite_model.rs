@@ -1,15 +1,20 @@
 use std::{
     cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet},
     iter,
     path::Path,
 };
 
 use anyhow::{Result, anyhow};
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
 use diesel::prelude::*;
-use time::Date;
+use time::{Date, OffsetDateTime, Time};
 
 use super::Importance;
-use crate::model::{CompletionLevel, Event, Model, Task};
+use crate::model::{
+    Activity, Change, ChangeKind, CompletionLevel, Entry, Event, Found, Model, Mood, Task,
+    Template, Trashed, kept_uid, new_uid, put_back, today,
+};
 
 pub struct SqliteModel(RefCell<SqliteConnection>, Cell<bool>);
 
@@ -19,6 +24,215 @@ impl SqliteModel {
     }
 }
 
+diesel::define_sql_function! {
+    fn last_insert_rowid() -> BigInt;
+}
+
+/// Entries are told apart in the history by an id, which is the id of the
+/// first change in their history. Entries made before histories were kept
+/// have none until they are first changed or moved.
+impl SqliteModel {
+    /// Adds the first change to a new history, returning the id of the entry
+    /// it belongs to.
+    fn start_history(&mut self, task: bool, kind: ChangeKind) -> Option<i64> {
+        use tables::history::dsl::*;
+
+        let row = SQLChange::from(0, task, kind);
+        self.0
+            .get_mut()
+            .transaction(|conn| {
+                diesel::insert_into(history)
+                    .values((
+                        entry.eq(row.entry),
+                        is_task.eq(row.is_task),
+                        time.eq(row.time),
+                        change.eq(row.change),
+                        value.eq(row.value),
+                    ))
+                    .execute(conn)?;
+                let new_id = diesel::select(last_insert_rowid()).get_result(conn)?;
+                diesel::update(history)
+                    .filter(id.eq(new_id))
+                    .set(entry.eq(new_id))
+                    .execute(conn)?;
+                Ok::<_, diesel::result::Error>(new_id)
+            })
+            .inspect_err(|_| self.1.set(true))
+            .ok()
+    }
+
+    fn record(&mut self, entry_id: i64, task: bool, kind: ChangeKind) {
+        use tables::history::dsl::*;
+
+        let row = SQLChange::from(entry_id, task, kind);
+        diesel::insert_into(history)
+            .values((
+                entry.eq(row.entry),
+                is_task.eq(row.is_task),
+                time.eq(row.time),
+                change.eq(row.change),
+                value.eq(row.value),
+            ))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+    }
+
+    fn history(&self, entry_id: i64, task: bool) -> Vec<Change> {
+        use tables::history::dsl::*;
+
+        let result: Result<Vec<SQLChange>, _> = history
+            .filter(entry.eq(entry_id).and(is_task.eq(task)))
+            .order(id.asc())
+            .select(SQLChange::as_select())
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(Some(all)) = result.map(|x| x.into_iter().map(SQLChange::to).collect()) {
+            return all;
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    /// The id of an event, which is given one if it has none.
+    fn event_id(&mut self, d: Date, i: usize) -> Option<i64> {
+        use tables::events::dsl::*;
+
+        let filter = date.eq(d.to_julian_day()).and(index.eq(i as i32));
+        let row: SQLEvent = events
+            .filter(filter)
+            .select(SQLEvent::as_select())
+            .first(self.0.get_mut())
+            .inspect_err(|_| self.1.set(true))
+            .ok()?;
+        if row.id.is_some() {
+            return row.id;
+        }
+        let new_id = self.start_history(false, ChangeKind::Tracked(row.title))?;
+        diesel::update(events)
+            .filter(filter)
+            .set(id.eq(new_id))
+            .execute(self.0.get_mut())
+            .inspect_err(|_| self.1.set(true))
+            .ok()?;
+        Some(new_id)
+    }
+
+    /// The id of a task, which is given one if it has none.
+    fn task_id(&mut self, d: Date, i: usize) -> Option<i64> {
+        use tables::tasks::dsl::*;
+
+        let filter = date.eq(d.to_julian_day()).and(index.eq(i as i32));
+        let row: SQLTask = tasks
+            .filter(filter)
+            .select(SQLTask::as_select())
+            .first(self.0.get_mut())
+            .inspect_err(|_| self.1.set(true))
+            .ok()?;
+        if row.id.is_some() {
+            return row.id;
+        }
+        let new_id = self.start_history(true, ChangeKind::Tracked(row.title))?;
+        diesel::update(tasks)
+            .filter(filter)
+            .set(id.eq(new_id))
+            .execute(self.0.get_mut())
+            .inspect_err(|_| self.1.set(true))
+            .ok()?;
+        Some(new_id)
+    }
+
+    /// Gives a restored entry back the id it had before it was deleted, and
+    /// drops the history it was started with on being added again.
+    fn restore_id(&mut self, d: Date, i: usize, task: bool, kept_id: i64) {
+        let julian_date = d.to_julian_day();
+        self.0
+            .get_mut()
+            .transaction(|conn| {
+                let new_id: Option<i64> = if task {
+                    use tables::tasks::dsl::*;
+                    let filter = date.eq(julian_date).and(index.eq(i as i32));
+                    let new_id = tasks.filter(filter).select(id).first(conn)?;
+                    diesel::update(tasks)
+                        .filter(filter)
+                        .set(id.eq(kept_id))
+                        .execute(conn)?;
+                    new_id
+                } else {
+                    use tables::events::dsl::*;
+                    let filter = date.eq(julian_date).and(index.eq(i as i32));
+                    let new_id = events.filter(filter).select(id).first(conn)?;
+                    diesel::update(events)
+                        .filter(filter)
+                        .set(id.eq(kept_id))
+                        .execute(conn)?;
+                    new_id
+                };
+                if let Some(new_id) = new_id {
+                    use tables::history::dsl::*;
+                    diesel::delete(history)
+                        .filter(entry.eq(new_id).and(is_task.eq(task)))
+                        .execute(conn)?;
+                }
+                Ok::<_, diesel::result::Error>(())
+            })
+            .unwrap_or_else(|_| self.1.set(true));
+    }
+
+    /// Rewrites the tags of a day's events or tasks after they change.
+    fn index_tags(&mut self, d: Date, task: bool) {
+        if index_tags(self.0.get_mut(), Some(d.to_julian_day()), task).is_err() {
+            self.1.set(true);
+        }
+    }
+
+    fn habit_id(&self, habit: &str) -> Option<i64> {
+        use tables::habits::dsl::*;
+
+        habits
+            .filter(name.eq(habit))
+            .select(id)
+            .first(&mut *self.0.borrow_mut())
+            .optional()
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                None
+            })
+    }
+
+    /// Adds an entry to the trash, keeping the id of its history so that
+    /// restoring it carries on the same history.
+    fn push_trash(&mut self, d: Date, i: usize, entry: Entry, entry_id: Option<i64>) {
+        use tables::trash::dsl::*;
+
+        let len: i64 = trash
+            .count()
+            .get_result(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        let trashed = Trashed {
+            date: d,
+            index: i,
+            deleted: today(),
+            entry,
+        };
+        diesel::insert_into(trash)
+            .values(&SQLTrashed {
+                id: entry_id,
+                ..SQLTrashed::from(trashed, len as usize)
+            })
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+    }
+}
+
 impl Model for SqliteModel {
     fn new_event(&mut self, d: Date, i: usize) -> Result<()> {
         use tables::events::dsl::*;
@@ -39,7 +253,11 @@ impl Model for SqliteModel {
                     0
                 });
 
-            let new_event = SQLEvent::new(julian_date, i as i32);
+            let new_event = SQLEvent {
+                id: self.start_history(false, ChangeKind::Created),
+                uid: new_uid(),
+                ..SQLEvent::new(julian_date, i as i32)
+            };
 
             diesel::insert_into(events)
                 .values(&new_event)
@@ -48,9 +266,11 @@ impl Model for SqliteModel {
                     self.1.set(true);
                     0
                 });
+            self.index_tags(d, false);
+            Ok(())
+        } else {
+            Err(anyhow!("index out of bounds"))
         }
-
-        Ok(())
     }
 
     fn new_task(&mut self, d: Date, i: usize) -> Result<()> {
@@ -72,7 +292,11 @@ impl Model for SqliteModel {
                     0
                 });
 
-            let new_task = SQLTask::new(julian_date, i as i32);
+            let new_task = SQLTask {
+                id: self.start_history(true, ChangeKind::Created),
+                uid: new_uid(),
+                ..SQLTask::new(julian_date, i as i32)
+            };
 
             diesel::insert_into(tasks)
                 .values(&new_task)
@@ -81,6 +305,7 @@ impl Model for SqliteModel {
                     self.1.set(true);
                     0
                 });
+            self.index_tags(d, true);
             Ok(())
         } else {
             Err(anyhow!("index out of bounds"))
@@ -94,6 +319,10 @@ impl Model for SqliteModel {
         let len = self.events_len(d);
 
         if i < len {
+            let event = self.get_event(d, i)?;
+            let entry_id = self.event_id(d, i);
+            self.push_trash(d, i, Entry::Event(event), entry_id);
+
             diesel::delete(events)
                 .filter(date.eq(julian_date).and(index.eq(i as i32)))
                 .execute(self.0.get_mut())
@@ -110,6 +339,7 @@ impl Model for SqliteModel {
                     self.1.set(true);
                     0
                 });
+            self.index_tags(d, false);
             Ok(())
         } else {
             Err(anyhow!("index out of bounds"))
@@ -123,6 +353,10 @@ impl Model for SqliteModel {
         let len = self.tasks_len(d);
 
         if i < len {
+            let task = self.get_task(d, i)?;
+            let entry_id = self.task_id(d, i);
+            self.push_trash(d, i, Entry::Task(task), entry_id);
+
             diesel::delete(tasks)
                 .filter(date.eq(julian_date).and(index.eq(i as i32)))
                 .execute(self.0.get_mut())
@@ -139,6 +373,7 @@ impl Model for SqliteModel {
                     self.1.set(true);
                     0
                 });
+            self.index_tags(d, true);
             Ok(())
         } else {
             Err(anyhow!("index out of bounds"))
@@ -189,11 +424,23 @@ impl Model for SqliteModel {
 
     fn replace_event(&mut self, d: Date, i: usize, e: Event) -> Result<()> {
         use tables::events::dsl::*;
-        let event = SQLEvent::from(e, d, i);
         let julian_date = d.to_julian_day();
         let len = self.events_len(d);
 
         if i < len {
+            let old = self.get_event(d, i)?;
+            let entry_id = self.event_id(d, i);
+            if let Some(entry_id) = entry_id
+                && old.title != e.title
+            {
+                self.record(entry_id, false, ChangeKind::Titled(e.title.clone()));
+            }
+            let event = SQLEvent {
+                id: entry_id,
+                uid: kept_uid(&old.uid, e.uid.clone()),
+                ..SQLEvent::from(e, d, i)
+            };
+
             diesel::delete(events)
                 .filter(date.eq(julian_date).and(index.eq(i as i32)))
                 .execute(self.0.get_mut())
@@ -209,6 +456,7 @@ impl Model for SqliteModel {
                     self.1.set(true);
                     0
                 });
+            self.index_tags(d, false);
             Ok(())
         } else {
             Err(anyhow!("index out of bounds"))
@@ -217,11 +465,27 @@ impl Model for SqliteModel {
 
     fn replace_task(&mut self, d: Date, i: usize, t: Task) -> Result<()> {
         use tables::tasks::dsl::*;
-        let task = SQLTask::from(t, d, i);
         let julian_date = d.to_julian_day();
         let len = self.tasks_len(d);
 
         if i < len {
+            let old = self.get_task(d, i)?;
+            let entry_id = self.task_id(d, i);
+            if let Some(entry_id) = entry_id {
+                if old.title != t.title {
+                    self.record(entry_id, true, ChangeKind::Titled(t.title.clone()));
+                }
+                if old.completion_level != t.completion_level {
+                    let change = ChangeKind::Completed(t.completion_level.clone());
+                    self.record(entry_id, true, change);
+                }
+            }
+            let task = SQLTask {
+                id: entry_id,
+                uid: kept_uid(&old.uid, t.uid.clone()),
+                ..SQLTask::from(t, d, i)
+            };
+
             diesel::delete(tasks)
                 .filter(date.eq(julian_date).and(index.eq(i as i32)))
                 .execute(self.0.get_mut())
@@ -237,12 +501,124 @@ impl Model for SqliteModel {
                     self.1.set(true);
                     0
                 });
+            self.index_tags(d, true);
             Ok(())
         } else {
             Err(anyhow!("index out of bounds"))
         }
     }
 
+    fn move_event(&mut self, from: Date, i: usize, to: Date, j: usize) -> Result<()> {
+        use tables::events::dsl::*;
+
+        let (from_day, to_day) = (from.to_julian_day(), to.to_julian_day());
+        let len = self.events_len(to).saturating_sub(usize::from(from == to));
+        if i >= self.events_len(from) || j > len {
+            return Err(anyhow!("index out of bounds"));
+        }
+        if from != to
+            && let Some(entry_id) = self.event_id(from, i)
+        {
+            self.record(entry_id, false, ChangeKind::Moved(to));
+        }
+
+        // the event waits at index -1 while the others make room for it
+        self.0
+            .get_mut()
+            .transaction(|conn| {
+                diesel::update(events)
+                    .filter(date.eq(from_day).and(index.eq(i as i32)))
+                    .set(index.eq(-1))
+                    .execute(conn)?;
+                diesel::update(events)
+                    .filter(date.eq(from_day).and(index.gt(i as i32)))
+                    .set(index.eq(index - 1))
+                    .execute(conn)?;
+                diesel::update(events)
+                    .filter(date.eq(to_day).and(index.ge(j as i32)))
+                    .set(index.eq(index + 1))
+                    .execute(conn)?;
+                diesel::update(events)
+                    .filter(date.eq(from_day).and(index.eq(-1)))
+                    .set((date.eq(to_day), index.eq(j as i32)))
+                    .execute(conn)
+            })
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        self.index_tags(from, false);
+        if from != to {
+            self.index_tags(to, false);
+        }
+        Ok(())
+    }
+
+    fn move_task(&mut self, from: Date, i: usize, to: Date, j: usize) -> Result<()> {
+        use tables::tasks::dsl::*;
+
+        let (from_day, to_day) = (from.to_julian_day(), to.to_julian_day());
+        let len = self.tasks_len(to).saturating_sub(usize::from(from == to));
+        if i >= self.tasks_len(from) || j > len {
+            return Err(anyhow!("index out of bounds"));
+        }
+        if from != to
+            && let Some(entry_id) = self.task_id(from, i)
+        {
+            self.record(entry_id, true, ChangeKind::Moved(to));
+        }
+
+        // the task waits at index -1 while the others make room for it
+        self.0
+            .get_mut()
+            .transaction(|conn| {
+                diesel::update(tasks)
+                    .filter(date.eq(from_day).and(index.eq(i as i32)))
+                    .set(index.eq(-1))
+                    .execute(conn)?;
+                diesel::update(tasks)
+                    .filter(date.eq(from_day).and(index.gt(i as i32)))
+                    .set(index.eq(index - 1))
+                    .execute(conn)?;
+                diesel::update(tasks)
+                    .filter(date.eq(to_day).and(index.ge(j as i32)))
+                    .set(index.eq(index + 1))
+                    .execute(conn)?;
+                diesel::update(tasks)
+                    .filter(date.eq(from_day).and(index.eq(-1)))
+                    .set((date.eq(to_day), index.eq(j as i32)))
+                    .execute(conn)
+            })
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        self.index_tags(from, true);
+        if from != to {
+            self.index_tags(to, true);
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self) {
+        if AnsiTransactionManager::begin_transaction(self.0.get_mut()).is_err() {
+            self.1.set(true);
+        }
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let conn = self.0.get_mut();
+        let result = if self.1.get() {
+            AnsiTransactionManager::rollback_transaction(conn)
+        } else {
+            AnsiTransactionManager::commit_transaction(conn)
+        };
+        if result.is_err() {
+            self.1.set(true);
+        }
+        self.err()
+    }
+
     fn tasks_len(&self, d: Date) -> usize {
         use tables::tasks::dsl::*;
 
@@ -305,6 +681,632 @@ impl Model for SqliteModel {
             })
     }
 
+    fn dates(&self) -> Vec<Date> {
+        use tables::{events, tasks};
+
+        let result: Result<Vec<i32>, _> = events::table
+            .select(events::date)
+            .union(tasks::table.select(tasks::date))
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(mut dates) = result {
+            dates.sort();
+            return dates
+                .into_iter()
+                .filter_map(|x| Date::from_julian_day(x).ok())
+                .collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn templates(&self) -> Vec<Template> {
+        use tables::templates::dsl::*;
+
+        let result: Result<Vec<SQLTemplate>, _> = templates
+            .select(SQLTemplate::as_select())
+            .order(index.asc())
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(Some(all)) = result.map(|x| x.into_iter().map(SQLTemplate::to).collect()) {
+            return all;
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn add_template(&mut self, t: Template) -> Result<()> {
+        use tables::templates::dsl::*;
+
+        let len: i64 = templates
+            .count()
+            .get_result(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        diesel::insert_into(templates)
+            .values(&SQLTemplate::from(t, len as usize))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        Ok(())
+    }
+
+    fn replace_template(&mut self, i: usize, t: Template) -> Result<()> {
+        use tables::templates::dsl::*;
+
+        let template = SQLTemplate::from(t, i);
+        let updated = diesel::update(templates)
+            .filter(index.eq(i as i32))
+            .set(&template)
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                1
+            });
+        if updated == 0 {
+            return Err(anyhow!("index out of bounds"));
+        }
+        Ok(())
+    }
+
+    fn delete_template(&mut self, i: usize) -> Result<()> {
+        use tables::templates::dsl::*;
+
+        let deleted = diesel::delete(templates)
+            .filter(index.eq(i as i32))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                1
+            });
+        if deleted == 0 {
+            return Err(anyhow!("index out of bounds"));
+        }
+
+        diesel::update(templates)
+            .filter(index.gt(i as i32))
+            .set(index.eq(index - 1))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        Ok(())
+    }
+
+    fn tags(&self) -> Vec<(String, usize)> {
+        use diesel::dsl::count_star;
+        use tables::tags::dsl::*;
+
+        let result: Result<Vec<(String, i64)>, _> = tags
+            .group_by(tag)
+            .select((tag, count_star()))
+            .order(tag.asc())
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(counts) = result {
+            return counts.into_iter().map(|(t, n)| (t, n as usize)).collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn tagged(&self, tag: &str) -> Vec<(Date, Entry)> {
+        use diesel::sql_types::Text;
+
+        let tag = tag.to_lowercase();
+        let events: Result<Vec<SQLEvent>, _> = diesel::sql_query(
+            "SELECT `events`.* FROM `events` JOIN `tags` ON `tags`.`date` = `events`.`date`
+            AND `tags`.`index` = `events`.`index` AND NOT `tags`.`is_task` WHERE `tags`.`tag` = ?",
+        )
+        .bind::<Text, _>(&tag)
+        .load(&mut *self.0.borrow_mut());
+        let tasks: Result<Vec<SQLTask>, _> = diesel::sql_query(
+            "SELECT `tasks`.* FROM `tasks` JOIN `tags` ON `tags`.`date` = `tasks`.`date`
+            AND `tags`.`index` = `tasks`.`index` AND `tags`.`is_task` WHERE `tags`.`tag` = ?",
+        )
+        .bind::<Text, _>(&tag)
+        .load(&mut *self.0.borrow_mut());
+
+        if let (Ok(events), Ok(tasks)) = (events, tasks) {
+            let mut tagged: Vec<(i32, bool, i32, Entry)> = events
+                .into_iter()
+                .map(|x| (x.date, false, x.index, Entry::Event(x.to())))
+                .chain(
+                    tasks
+                        .into_iter()
+                        .map(|x| (x.date, true, x.index, Entry::Task(x.to()))),
+                )
+                .collect();
+            tagged.sort_by_key(|(d, is_task, i, _)| (*d, *is_task, *i));
+            return tagged
+                .into_iter()
+                .filter_map(|(d, _, _, entry)| Some((Date::from_julian_day(d).ok()?, entry)))
+                .collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn search(&self, query: &str) -> Vec<(Date, Found)> {
+        let query = query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        // LIKE only ignores the case of ASCII letters, so every title is
+        // checked the way the other backends check it
+        let matches = |text: &str| text.to_lowercase().contains(&query);
+
+        let events: Result<Vec<SQLEvent>, _> = tables::events::table
+            .select(SQLEvent::as_select())
+            .load(&mut *self.0.borrow_mut());
+        let tasks: Result<Vec<SQLTask>, _> = tables::tasks::table
+            .select(SQLTask::as_select())
+            .load(&mut *self.0.borrow_mut());
+        let journals = self.journals();
+
+        if let (Ok(events), Ok(tasks)) = (events, tasks) {
+            let mut found: Vec<(i32, usize, i32, Found)> = events
+                .into_iter()
+                .filter(|x| matches(&x.title))
+                .map(|x| (x.date, 0, x.index, Found::Entry(Entry::Event(x.to()))))
+                .chain(
+                    tasks
+                        .into_iter()
+                        .filter(|x| matches(&x.title))
+                        .map(|x| (x.date, 1, x.index, Found::Entry(Entry::Task(x.to())))),
+                )
+                .collect();
+            for (d, journal) in journals {
+                found.extend(journal.lines().filter(|x| matches(x)).map(|x| {
+                    (
+                        d.to_julian_day(),
+                        2,
+                        0,
+                        Found::Journal(x.trim().to_string()),
+                    )
+                }));
+            }
+            // sorting is stable, so a journal's lines stay in order
+            found.sort_by_key(|(d, kind, i, _)| (*d, *kind, *i));
+            return found
+                .into_iter()
+                .filter_map(|(d, _, _, x)| Some((Date::from_julian_day(d).ok()?, x)))
+                .collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn due_tasks(&self, until: Date) -> Vec<(Date, Task)> {
+        use tables::tasks::dsl::*;
+
+        let result: Result<Vec<SQLTask>, _> = tasks
+            .filter(
+                due_date
+                    .le(until.to_julian_day())
+                    .and(completion_level.ne(2)),
+            )
+            .order((due_date.asc(), date.asc(), index.asc()))
+            .select(SQLTask::as_select())
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(due) = result {
+            return due
+                .into_iter()
+                .filter_map(|x| Some((Date::from_julian_day(x.date).ok()?, x.to())))
+                .collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn activity(&self, from: Date, to: Date) -> BTreeMap<Date, Activity> {
+        use diesel::dsl::count_star;
+        use tables::{events, tasks};
+
+        let (from, to) = (from.to_julian_day(), to.to_julian_day());
+        let conn = &mut *self.0.borrow_mut();
+        let result = (|| {
+            let events: Vec<(i32, i64)> = events::table
+                .filter(events::date.between(from, to))
+                .group_by(events::date)
+                .select((events::date, count_star()))
+                .load(conn)?;
+            let tasks: Vec<(i32, i64)> = tasks::table
+                .filter(tasks::date.between(from, to))
+                .group_by(tasks::date)
+                .select((tasks::date, count_star()))
+                .load(conn)?;
+            let completed: Vec<(i32, i64)> = tasks::table
+                .filter(
+                    tasks::date
+                        .between(from, to)
+                        .and(tasks::completion_level.eq(2)),
+                )
+                .group_by(tasks::date)
+                .select((tasks::date, count_star()))
+                .load(conn)?;
+            Ok::<_, diesel::result::Error>((events, tasks, completed))
+        })();
+        let Ok((events, tasks, completed)) = result else {
+            self.1.set(true);
+            return BTreeMap::new();
+        };
+
+        let mut activity: BTreeMap<Date, Activity> = BTreeMap::new();
+        for (d, count) in events.into_iter().chain(tasks) {
+            if let Ok(d) = Date::from_julian_day(d) {
+                activity.entry(d).or_default().entries += count as usize;
+            }
+        }
+        for (d, count) in completed {
+            if let Ok(d) = Date::from_julian_day(d) {
+                activity.entry(d).or_default().completed += count as usize;
+            }
+        }
+        activity
+    }
+
+    fn event_history(&self, d: Date, i: usize) -> Vec<Change> {
+        use tables::events::dsl::*;
+
+        let entry_id: Option<Option<i64>> = events
+            .filter(date.eq(d.to_julian_day()).and(index.eq(i as i32)))
+            .select(id)
+            .first(&mut *self.0.borrow_mut())
+            .optional()
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                None
+            });
+        entry_id
+            .flatten()
+            .map_or(Vec::new(), |x| self.history(x, false))
+    }
+
+    fn task_history(&self, d: Date, i: usize) -> Vec<Change> {
+        use tables::tasks::dsl::*;
+
+        let entry_id: Option<Option<i64>> = tasks
+            .filter(date.eq(d.to_julian_day()).and(index.eq(i as i32)))
+            .select(id)
+            .first(&mut *self.0.borrow_mut())
+            .optional()
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                None
+            });
+        entry_id
+            .flatten()
+            .map_or(Vec::new(), |x| self.history(x, true))
+    }
+
+    fn mood(&self, d: Date) -> Mood {
+        use tables::moods::dsl::*;
+
+        let result: Result<Option<SQLMood>, _> = moods
+            .filter(date.eq(d.to_julian_day()))
+            .select(SQLMood::as_select())
+            .first(&mut *self.0.borrow_mut())
+            .optional();
+        if let Ok(row) = result {
+            return row.map(SQLMood::to).unwrap_or_default();
+        }
+
+        self.1.set(true);
+        Mood::default()
+    }
+
+    fn set_mood(&mut self, d: Date, m: Mood) -> Result<()> {
+        use tables::moods::dsl::*;
+
+        let result = if m.is_empty() {
+            diesel::delete(moods)
+                .filter(date.eq(d.to_julian_day()))
+                .execute(self.0.get_mut())
+        } else {
+            diesel::replace_into(moods)
+                .values(&SQLMood::from(m, d))
+                .execute(self.0.get_mut())
+        };
+        if result.is_err() {
+            self.1.set(true);
+        }
+        Ok(())
+    }
+
+    fn journal(&self, d: Date) -> String {
+        use tables::journals::dsl::*;
+
+        let result: Result<Option<String>, _> = journals
+            .filter(date.eq(d.to_julian_day()))
+            .select(text)
+            .first(&mut *self.0.borrow_mut())
+            .optional();
+        if let Ok(row) = result {
+            return row.unwrap_or_default();
+        }
+
+        self.1.set(true);
+        String::new()
+    }
+
+    fn set_journal(&mut self, d: Date, t: &str) -> Result<()> {
+        use tables::journals::dsl::*;
+
+        let result = if t.is_empty() {
+            diesel::delete(journals)
+                .filter(date.eq(d.to_julian_day()))
+                .execute(self.0.get_mut())
+        } else {
+            diesel::replace_into(journals)
+                .values((date.eq(d.to_julian_day()), text.eq(t)))
+                .execute(self.0.get_mut())
+        };
+        if result.is_err() {
+            self.1.set(true);
+        }
+        Ok(())
+    }
+
+    fn journals(&self) -> Vec<(Date, String)> {
+        use tables::journals::dsl::*;
+
+        let result: Result<Vec<(i32, String)>, _> =
+            journals.order_by(date).load(&mut *self.0.borrow_mut());
+        if let Ok(rows) = result {
+            return rows
+                .into_iter()
+                .filter_map(|(d, t)| Some((Date::from_julian_day(d).ok()?, t)))
+                .collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn filled(&self, d: Date) -> bool {
+        use tables::filled_days::dsl::*;
+
+        let result: Result<Option<i32>, _> = filled_days
+            .filter(date.eq(d.to_julian_day()))
+            .select(date)
+            .first(&mut *self.0.borrow_mut())
+            .optional();
+        if let Ok(row) = result {
+            return row.is_some();
+        }
+
+        self.1.set(true);
+        false
+    }
+
+    fn realized(&self, d: Date) -> BTreeSet<String> {
+        use tables::realized::dsl::*;
+
+        let result: Result<Vec<String>, _> = realized
+            .filter(date.eq(d.to_julian_day()))
+            .select(template)
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(rows) = result {
+            return rows.into_iter().collect();
+        }
+
+        self.1.set(true);
+        BTreeSet::new()
+    }
+
+    fn set_realized(&mut self, d: Date, uid: &str) -> Result<()> {
+        use tables::realized::dsl::*;
+
+        let result = diesel::insert_or_ignore_into(realized)
+            .values((date.eq(d.to_julian_day()), template.eq(uid)))
+            .execute(self.0.get_mut());
+        if result.is_err() {
+            self.1.set(true);
+        }
+        Ok(())
+    }
+
+    fn set_filled(&mut self, d: Date) -> Result<()> {
+        use tables::filled_days::dsl::*;
+
+        let result = diesel::insert_or_ignore_into(filled_days)
+            .values(date.eq(d.to_julian_day()))
+            .execute(self.0.get_mut());
+        if result.is_err() {
+            self.1.set(true);
+        }
+        Ok(())
+    }
+
+    fn habits(&self) -> Vec<String> {
+        use tables::habits::dsl::*;
+
+        habits
+            .order(id.asc())
+            .select(name)
+            .load(&mut *self.0.borrow_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                Vec::new()
+            })
+    }
+
+    fn habits_on(&self, d: Date) -> Vec<(String, bool)> {
+        let result: Result<Vec<SQLHabitOn>, _> = diesel::sql_query(
+            "SELECT `name`, EXISTS (SELECT 1 FROM `habit_days`
+                WHERE `habit` = `habits`.`id` AND `date` = ?) AS `done`
+            FROM `habits` ORDER BY `id`",
+        )
+        .bind::<diesel::sql_types::Integer, _>(d.to_julian_day())
+        .load(&mut *self.0.borrow_mut());
+        if let Ok(rows) = result {
+            return rows.into_iter().map(|x| (x.name, x.done)).collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn add_habit(&mut self, habit: &str) -> Result<()> {
+        use tables::habits::dsl::*;
+
+        if self.habit_id(habit).is_some() {
+            return Err(anyhow!("habit is already kept"));
+        }
+        diesel::insert_into(habits)
+            .values(name.eq(habit))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        Ok(())
+    }
+
+    fn remove_habit(&mut self, habit: &str) -> Result<()> {
+        use tables::{habit_days, habits};
+
+        let Some(habit_id) = self.habit_id(habit) else {
+            return Err(anyhow!("no such habit"));
+        };
+        self.0
+            .get_mut()
+            .transaction(|conn| {
+                diesel::delete(habit_days::table)
+                    .filter(habit_days::habit.eq(habit_id))
+                    .execute(conn)?;
+                diesel::delete(habits::table)
+                    .filter(habits::id.eq(habit_id))
+                    .execute(conn)
+            })
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        Ok(())
+    }
+
+    fn habit_days(&self, habit_name: &str) -> BTreeSet<Date> {
+        use tables::habit_days::dsl::*;
+
+        let Some(habit_id) = self.habit_id(habit_name) else {
+            return BTreeSet::new();
+        };
+        let result: Result<Vec<i32>, _> = habit_days
+            .filter(habit.eq(habit_id))
+            .select(date)
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(days) = result {
+            return days
+                .into_iter()
+                .filter_map(|x| Date::from_julian_day(x).ok())
+                .collect();
+        }
+
+        self.1.set(true);
+        BTreeSet::new()
+    }
+
+    fn set_habit_done(&mut self, habit_name: &str, d: Date, done: bool) -> Result<()> {
+        use tables::habit_days::dsl::*;
+
+        let Some(habit_id) = self.habit_id(habit_name) else {
+            return Err(anyhow!("no such habit"));
+        };
+        let day = d.to_julian_day();
+        let result = if done {
+            diesel::insert_or_ignore_into(habit_days)
+                .values((habit.eq(habit_id), date.eq(day)))
+                .execute(self.0.get_mut())
+        } else {
+            diesel::delete(habit_days)
+                .filter(habit.eq(habit_id).and(date.eq(day)))
+                .execute(self.0.get_mut())
+        };
+        if result.is_err() {
+            self.1.set(true);
+        }
+        Ok(())
+    }
+
+    fn trash(&self) -> Vec<Trashed> {
+        use tables::trash::dsl::*;
+
+        let result: Result<Vec<SQLTrashed>, _> = trash
+            .select(SQLTrashed::as_select())
+            .order(trash_index.asc())
+            .load(&mut *self.0.borrow_mut());
+        if let Ok(all) = result {
+            return all.into_iter().map(SQLTrashed::to).collect();
+        }
+
+        self.1.set(true);
+        Vec::new()
+    }
+
+    fn purge(&mut self, i: usize) -> Result<()> {
+        use tables::trash::dsl::*;
+
+        let purged = diesel::delete(trash)
+            .filter(trash_index.eq(i as i32))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                1
+            });
+        if purged == 0 {
+            return Err(anyhow!("index out of bounds"));
+        }
+
+        diesel::update(trash)
+            .filter(trash_index.gt(i as i32))
+            .set(trash_index.eq(trash_index - 1))
+            .execute(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                0
+            });
+        Ok(())
+    }
+
+    /// Restores an entry as the default does, keeping the history it had
+    /// before it was deleted.
+    fn restore(&mut self, i: usize) -> Result<()> {
+        use tables::trash::dsl::*;
+
+        let trashed = self
+            .trash()
+            .into_iter()
+            .nth(i)
+            .ok_or(anyhow!("index out of bounds"))?;
+        let kept_id: Option<i64> = trash
+            .filter(trash_index.eq(i as i32))
+            .select(id)
+            .first(self.0.get_mut())
+            .unwrap_or_else(|_| {
+                self.1.set(true);
+                None
+            });
+        let (d, task) = (trashed.date, matches!(trashed.entry, Entry::Task(_)));
+        self.begin();
+        let result = put_back(self, trashed).map(|idx| {
+            if let Some(kept_id) = kept_id {
+                self.restore_id(d, idx, task, kept_id);
+            }
+        });
+        let result = result.and_then(|()| self.purge(i));
+        self.commit().and(result)
+    }
+
     fn err(&self) -> Result<()> {
         if self.1.get() {
             return Err(anyhow!(
@@ -315,14 +1317,18 @@ impl Model for SqliteModel {
     }
 }
 
-#[derive(Queryable, Selectable, Insertable)]
+#[derive(Queryable, QueryableByName, Selectable, Insertable)]
 #[diesel(table_name = tables::events)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 struct SQLEvent {
     date: i32,
     index: i32,
+    id: Option<i64>,
     title: String,
     importance: i32,
+    start_time: Option<i32>,
+    end_time: Option<i32>,
+    uid: String,
 }
 
 impl SQLEvent {
@@ -330,8 +1336,12 @@ impl SQLEvent {
         Self {
             date,
             index,
+            id: None,
             title: String::new(),
             importance: 0,
+            start_time: None,
+            end_time: None,
+            uid: String::new(),
         }
     }
 
@@ -343,6 +1353,9 @@ impl SQLEvent {
                 1 => Importance::High,
                 _ => panic!("db out of sync"),
             },
+            start: self.start_time.map(time_from_minutes),
+            end: self.end_time.map(time_from_minutes),
+            uid: self.uid,
         }
     }
 
@@ -357,18 +1370,36 @@ impl SQLEvent {
             importance,
             date: d.to_julian_day(),
             index: i as i32,
+            id: None,
+            start_time: e.start.map(minutes_from_time),
+            end_time: e.end.map(minutes_from_time),
+            uid: e.uid,
         }
     }
 }
 
-#[derive(Queryable, Selectable, Insertable)]
+/// Times are stored as the number of minutes since midnight.
+fn minutes_from_time(time: Time) -> i32 {
+    time.hour() as i32 * 60 + time.minute() as i32
+}
+
+fn time_from_minutes(minutes: i32) -> Time {
+    Time::from_hms((minutes / 60) as u8, (minutes % 60) as u8, 0).expect("db out of sync")
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Insertable)]
 #[diesel(table_name = tables::tasks)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 struct SQLTask {
     date: i32,
     index: i32,
+    id: Option<i64>,
     title: String,
     completion_level: i32,
+    importance: i32,
+    due_date: Option<i32>,
+    depth: i32,
+    uid: String,
 }
 
 impl SQLTask {
@@ -376,8 +1407,13 @@ impl SQLTask {
         Self {
             date,
             index,
+            id: None,
             title: String::new(),
             completion_level: 0,
+            importance: 0,
+            due_date: None,
+            depth: 0,
+            uid: String::new(),
         }
     }
 
@@ -390,6 +1426,16 @@ impl SQLTask {
                 2 => CompletionLevel::Full,
                 _ => panic!("db out of sync"),
             },
+            importance: match self.importance {
+                0 => Importance::Normal,
+                1 => Importance::High,
+                _ => panic!("db out of sync"),
+            },
+            due: self
+                .due_date
+                .map(|x| Date::from_julian_day(x).expect("db out of sync")),
+            depth: u8::try_from(self.depth).expect("db out of sync"),
+            uid: self.uid,
         }
     }
 
@@ -399,12 +1445,305 @@ impl SQLTask {
             CompletionLevel::Partial => 1,
             CompletionLevel::Full => 2,
         };
+        let importance = match e.importance {
+            Importance::Normal => 0,
+            Importance::High => 1,
+        };
 
         Self {
             title: e.title,
             completion_level,
+            importance,
             date: d.to_julian_day(),
             index: i as i32,
+            id: None,
+            due_date: e.due.map(Date::to_julian_day),
+            depth: e.depth.into(),
+            uid: e.uid,
+        }
+    }
+}
+
+/// A template and its entry, flattened into one row. `is_task` tells which
+/// of the entry's columns are used.
+#[derive(Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = tables::templates)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(treat_none_as_null = true)]
+struct SQLTemplate {
+    index: i32,
+    recurrence: String,
+    is_task: bool,
+    title: String,
+    importance: i32,
+    completion_level: i32,
+    due_date: Option<i32>,
+    start_time: Option<i32>,
+    end_time: Option<i32>,
+    uid: String,
+}
+
+impl SQLTemplate {
+    /// `None` when the recurrence cannot be read, which means the db is out of sync.
+    fn to(self) -> Option<Template> {
+        let entry = if self.is_task {
+            Entry::Task(
+                SQLTask {
+                    date: 0,
+                    index: self.index,
+                    id: None,
+                    title: self.title,
+                    completion_level: self.completion_level,
+                    importance: self.importance,
+                    due_date: self.due_date,
+                    depth: 0,
+                    uid: self.uid,
+                }
+                .to(),
+            )
+        } else {
+            Entry::Event(
+                SQLEvent {
+                    date: 0,
+                    index: self.index,
+                    id: None,
+                    title: self.title,
+                    importance: self.importance,
+                    start_time: self.start_time,
+                    end_time: self.end_time,
+                    uid: self.uid,
+                }
+                .to(),
+            )
+        };
+        Some(Template {
+            recurrence: self.recurrence.parse().ok()?,
+            entry,
+        })
+    }
+
+    fn from(t: Template, i: usize) -> Self {
+        let recurrence = t.recurrence.to_string();
+        let date = t.recurrence.start;
+        match t.entry {
+            Entry::Event(e) => {
+                let event = SQLEvent::from(e, date, i);
+                Self {
+                    index: i as i32,
+                    recurrence,
+                    is_task: false,
+                    title: event.title,
+                    importance: event.importance,
+                    completion_level: 0,
+                    due_date: None,
+                    start_time: event.start_time,
+                    end_time: event.end_time,
+                    uid: event.uid,
+                }
+            }
+            Entry::Task(t) => {
+                let task = SQLTask::from(t, date, i);
+                Self {
+                    index: i as i32,
+                    recurrence,
+                    is_task: true,
+                    title: task.title,
+                    importance: task.importance,
+                    completion_level: task.completion_level,
+                    due_date: task.due_date,
+                    start_time: None,
+                    end_time: None,
+                    uid: task.uid,
+                }
+            }
+        }
+    }
+}
+
+/// A trashed entry, flattened into one row like a [`SQLTemplate`].
+/// `trash_index` is its place in the trash, `date` and `index` are where it
+/// was deleted from, and `id` is the id of its history.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = tables::trash)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+struct SQLTrashed {
+    trash_index: i32,
+    date: i32,
+    index: i32,
+    deleted: i32,
+    is_task: bool,
+    title: String,
+    importance: i32,
+    completion_level: i32,
+    due_date: Option<i32>,
+    start_time: Option<i32>,
+    end_time: Option<i32>,
+    depth: i32,
+    uid: String,
+    id: Option<i64>,
+}
+
+impl SQLTrashed {
+    fn to(self) -> Trashed {
+        let entry = if self.is_task {
+            Entry::Task(
+                SQLTask {
+                    date: self.date,
+                    index: self.index,
+                    id: None,
+                    title: self.title,
+                    completion_level: self.completion_level,
+                    importance: self.importance,
+                    due_date: self.due_date,
+                    depth: self.depth,
+                    uid: self.uid,
+                }
+                .to(),
+            )
+        } else {
+            Entry::Event(
+                SQLEvent {
+                    date: self.date,
+                    index: self.index,
+                    id: None,
+                    title: self.title,
+                    importance: self.importance,
+                    start_time: self.start_time,
+                    end_time: self.end_time,
+                    uid: self.uid,
+                }
+                .to(),
+            )
+        };
+        Trashed {
+            date: Date::from_julian_day(self.date).expect("db out of sync"),
+            index: usize::try_from(self.index).expect("db out of sync"),
+            deleted: Date::from_julian_day(self.deleted).expect("db out of sync"),
+            entry,
+        }
+    }
+
+    fn from(t: Trashed, i: usize) -> Self {
+        let deleted = t.deleted.to_julian_day();
+        match t.entry {
+            Entry::Event(e) => {
+                let event = SQLEvent::from(e, t.date, t.index);
+                Self {
+                    trash_index: i as i32,
+                    date: event.date,
+                    index: event.index,
+                    deleted,
+                    is_task: false,
+                    title: event.title,
+                    importance: event.importance,
+                    completion_level: 0,
+                    due_date: None,
+                    start_time: event.start_time,
+                    end_time: event.end_time,
+                    depth: 0,
+                    uid: event.uid,
+                    id: None,
+                }
+            }
+            Entry::Task(task) => {
+                let task = SQLTask::from(task, t.date, t.index);
+                Self {
+                    trash_index: i as i32,
+                    date: task.date,
+                    index: task.index,
+                    deleted,
+                    is_task: true,
+                    title: task.title,
+                    importance: task.importance,
+                    completion_level: task.completion_level,
+                    due_date: task.due_date,
+                    start_time: None,
+                    end_time: None,
+                    depth: task.depth,
+                    uid: task.uid,
+                    id: None,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = tables::moods)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+struct SQLMood {
+    date: i32,
+    mood: Option<i32>,
+    energy: Option<i32>,
+    reflection: String,
+}
+
+impl SQLMood {
+    fn to(self) -> Mood {
+        Mood {
+            mood: self.mood.and_then(|x| u8::try_from(x).ok()),
+            energy: self.energy.and_then(|x| u8::try_from(x).ok()),
+            reflection: self.reflection,
+        }
+    }
+
+    fn from(m: Mood, d: Date) -> Self {
+        Self {
+            date: d.to_julian_day(),
+            mood: m.mood.map(i32::from),
+            energy: m.energy.map(i32::from),
+            reflection: m.reflection,
+        }
+    }
+}
+
+/// A change to an entry. `change` names the kind of change and `value` holds
+/// what it changed to, if anything, as text.
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = tables::history)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+struct SQLChange {
+    entry: i64,
+    is_task: bool,
+    time: i64,
+    change: String,
+    value: String,
+}
+
+impl SQLChange {
+    /// `None` when the change cannot be read, which means the db is out of sync.
+    fn to(self) -> Option<Change> {
+        let kind = match self.change.as_str() {
+            "created" => ChangeKind::Created,
+            "tracked" => ChangeKind::Tracked(self.value),
+            "title" => ChangeKind::Titled(self.value),
+            "completion" => {
+                ChangeKind::Completed(CompletionLevel::from_symbol(self.value.chars().next()?)?)
+            }
+            "moved" => ChangeKind::Moved(Date::from_julian_day(self.value.parse().ok()?).ok()?),
+            _ => return None,
+        };
+        Some(Change {
+            time: OffsetDateTime::from_unix_timestamp(self.time).ok()?,
+            kind,
+        })
+    }
+
+    /// A change made now.
+    fn from(entry: i64, is_task: bool, kind: ChangeKind) -> Self {
+        let (change, value) = match kind {
+            ChangeKind::Created => ("created", String::new()),
+            ChangeKind::Tracked(title) => ("tracked", title),
+            ChangeKind::Titled(title) => ("title", title),
+            ChangeKind::Completed(level) => ("completion", level.symbol().to_string()),
+            ChangeKind::Moved(date) => ("moved", date.to_julian_day().to_string()),
+        };
+        Self {
+            entry,
+            is_task,
+            time: OffsetDateTime::now_utc().unix_timestamp(),
+            change: change.to_string(),
+            value,
         }
     }
 }
@@ -414,8 +1753,12 @@ mod tables {
         events (date, index) {
             date -> Integer,
             index -> Integer,
+            id -> Nullable<BigInt>,
             title -> Text,
             importance -> Integer,
+            start_time -> Nullable<Integer>,
+            end_time -> Nullable<Integer>,
+            uid -> Text,
         }
     }
 
@@ -423,8 +1766,110 @@ mod tables {
         tasks (date, index) {
             date -> Integer,
             index -> Integer,
+            id -> Nullable<BigInt>,
+            title -> Text,
+            completion_level -> Integer,
+            importance -> Integer,
+            due_date -> Nullable<Integer>,
+            depth -> Integer,
+            uid -> Text,
+        }
+    }
+
+    diesel::table! {
+        tags (tag, date, is_task, index) {
+            tag -> Text,
+            date -> Integer,
+            is_task -> Bool,
+            index -> Integer,
+        }
+    }
+
+    diesel::table! {
+        trash (trash_index) {
+            trash_index -> Integer,
+            date -> Integer,
+            index -> Integer,
+            deleted -> Integer,
+            is_task -> Bool,
             title -> Text,
+            importance -> Integer,
             completion_level -> Integer,
+            due_date -> Nullable<Integer>,
+            start_time -> Nullable<Integer>,
+            end_time -> Nullable<Integer>,
+            depth -> Integer,
+            uid -> Text,
+            id -> Nullable<BigInt>,
+        }
+    }
+
+    diesel::table! {
+        history (id) {
+            id -> BigInt,
+            entry -> BigInt,
+            is_task -> Bool,
+            time -> BigInt,
+            change -> Text,
+            value -> Text,
+        }
+    }
+
+    diesel::table! {
+        moods (date) {
+            date -> Integer,
+            mood -> Nullable<Integer>,
+            energy -> Nullable<Integer>,
+            reflection -> Text,
+        }
+    }
+
+    diesel::table! {
+        journals (date) {
+            date -> Integer,
+            text -> Text,
+        }
+    }
+
+    diesel::table! {
+        filled_days (date) {
+            date -> Integer,
+        }
+    }
+
+    diesel::table! {
+        habits (id) {
+            id -> BigInt,
+            name -> Text,
+        }
+    }
+
+    diesel::table! {
+        habit_days (habit, date) {
+            habit -> BigInt,
+            date -> Integer,
+        }
+    }
+
+    diesel::table! {
+        templates (index) {
+            index -> Integer,
+            recurrence -> Text,
+            is_task -> Bool,
+            title -> Text,
+            importance -> Integer,
+            completion_level -> Integer,
+            due_date -> Nullable<Integer>,
+            start_time -> Nullable<Integer>,
+            end_time -> Nullable<Integer>,
+            uid -> Text,
+        }
+    }
+
+    diesel::table! {
+        realized (date, template) {
+            date -> Integer,
+            template -> Text,
         }
     }
 }
@@ -450,6 +1895,165 @@ pub fn establish_connection(path: &Path) -> Result<SqliteConnection> {
 	`completion_level` INT NOT NULL)",
     )
     .execute(&mut sqlite_conn)?;
+    migrate(&mut sqlite_conn)?;
 
     Ok(sqlite_conn)
 }
+
+/// Schema changes made since the tables above were first released. Each
+/// database records how many of these it has run in its `user_version`, so
+/// new migrations must only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE `tasks` ADD `importance` INT NOT NULL DEFAULT 0",
+    "ALTER TABLE `events` ADD `start_time` INT",
+    "ALTER TABLE `events` ADD `end_time` INT",
+    "ALTER TABLE `tasks` ADD `due_date` INT",
+    "CREATE TABLE `templates` (
+	`index` INT NOT NULL,
+	`recurrence` TEXT NOT NULL,
+	`is_task` BOOL NOT NULL,
+	`title` TEXT NOT NULL,
+	`importance` INT NOT NULL,
+	`completion_level` INT NOT NULL,
+	`due_date` INT,
+	`start_time` INT,
+	`end_time` INT)",
+    "ALTER TABLE `tasks` ADD `depth` INT NOT NULL DEFAULT 0",
+    "CREATE TABLE `trash` (
+	`trash_index` INT NOT NULL,
+	`date` INT NOT NULL,
+	`index` INT NOT NULL,
+	`deleted` INT NOT NULL,
+	`is_task` BOOL NOT NULL,
+	`title` TEXT NOT NULL,
+	`importance` INT NOT NULL,
+	`completion_level` INT NOT NULL,
+	`due_date` INT,
+	`start_time` INT,
+	`end_time` INT,
+	`depth` INT NOT NULL)",
+    "ALTER TABLE `events` ADD `id` INT",
+    "ALTER TABLE `tasks` ADD `id` INT",
+    "CREATE TABLE `history` (
+	`id` INTEGER PRIMARY KEY AUTOINCREMENT,
+	`entry` INT NOT NULL,
+	`is_task` BOOL NOT NULL,
+	`time` INT NOT NULL,
+	`change` TEXT NOT NULL,
+	`value` TEXT NOT NULL)",
+    "CREATE INDEX `history_entry` ON `history` (`entry`, `is_task`)",
+    "CREATE TABLE `habits` (
+	`id` INTEGER PRIMARY KEY AUTOINCREMENT,
+	`name` TEXT NOT NULL UNIQUE)",
+    "CREATE TABLE `habit_days` (
+	`habit` INT NOT NULL,
+	`date` INT NOT NULL,
+	PRIMARY KEY (`habit`, `date`))",
+    "CREATE TABLE `moods` (
+	`date` INT PRIMARY KEY NOT NULL,
+	`mood` INT,
+	`energy` INT,
+	`reflection` TEXT NOT NULL)",
+    "CREATE TABLE `journals` (
+	`date` INT PRIMARY KEY NOT NULL,
+	`text` TEXT NOT NULL)",
+    "ALTER TABLE `events` ADD `uid` TEXT NOT NULL DEFAULT ''",
+    "UPDATE `events` SET `uid` = lower(hex(randomblob(16))) || '@jotty'",
+    "ALTER TABLE `tasks` ADD `uid` TEXT NOT NULL DEFAULT ''",
+    "UPDATE `tasks` SET `uid` = lower(hex(randomblob(16))) || '@jotty'",
+    "ALTER TABLE `trash` ADD `uid` TEXT NOT NULL DEFAULT ''",
+    "CREATE TABLE `filled_days` (
+	`date` INT PRIMARY KEY NOT NULL)",
+    "ALTER TABLE `templates` ADD `uid` TEXT NOT NULL DEFAULT ''",
+    "UPDATE `templates` SET `uid` = lower(hex(randomblob(16))) || '@jotty'",
+    "CREATE TABLE `realized` (
+	`date` INT NOT NULL,
+	`template` TEXT NOT NULL,
+	PRIMARY KEY (`date`, `template`))",
+    "ALTER TABLE `trash` ADD `id` INT",
+    TAGS_TABLE,
+    "CREATE INDEX `tags_entry` ON `tags` (`date`, `is_task`, `index`)",
+];
+
+/// The normalised tags of every event and task, by tag. It is filled in from
+/// the titles already in a journal when it is created.
+const TAGS_TABLE: &str = "CREATE TABLE `tags` (
+	`tag` TEXT NOT NULL,
+	`date` INT NOT NULL,
+	`is_task` BOOL NOT NULL,
+	`index` INT NOT NULL,
+	PRIMARY KEY (`tag`, `date`, `is_task`, `index`))";
+
+/// Rewrites the tags of the events, or the tasks, on day `d` from their
+/// titles, or of every day if `d` is `None`.
+fn index_tags(conn: &mut SqliteConnection, d: Option<i32>, task: bool) -> QueryResult<usize> {
+    let titles: Vec<(i32, i32, String)> = if task {
+        use tables::tasks::dsl::*;
+        let mut query = tasks.select((date, index, title)).into_boxed();
+        if let Some(d) = d {
+            query = query.filter(date.eq(d));
+        }
+        query.load(conn)?
+    } else {
+        use tables::events::dsl::*;
+        let mut query = events.select((date, index, title)).into_boxed();
+        if let Some(d) = d {
+            query = query.filter(date.eq(d));
+        }
+        query.load(conn)?
+    };
+
+    use tables::tags::dsl::*;
+    let mut stale = diesel::delete(tags).filter(is_task.eq(task)).into_boxed();
+    if let Some(d) = d {
+        stale = stale.filter(date.eq(d));
+    }
+    stale.execute(conn)?;
+    let rows: Vec<_> = titles
+        .iter()
+        .flat_map(|(d, i, t)| {
+            super::tags(t)
+                .into_iter()
+                .map(move |t| (tag.eq(t), date.eq(*d), is_task.eq(task), index.eq(*i)))
+        })
+        .collect();
+    // each row binds four variables, which must stay under SQLite's limit
+    let mut inserted = 0;
+    for chunk in rows.chunks(200) {
+        inserted += diesel::insert_into(tags).values(chunk).execute(conn)?;
+    }
+    Ok(inserted)
+}
+
+/// A habit and whether it was done on the day asked about.
+#[derive(QueryableByName)]
+struct SQLHabitOn {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    done: bool,
+}
+
+#[derive(QueryableByName)]
+struct UserVersion {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    user_version: i32,
+}
+
+fn migrate(conn: &mut SqliteConnection) -> Result<()> {
+    let version = diesel::sql_query("PRAGMA user_version")
+        .get_result::<UserVersion>(conn)?
+        .user_version;
+
+    conn.transaction(|conn| {
+        for migration in MIGRATIONS.iter().skip(version as usize) {
+            diesel::sql_query(*migration).execute(conn)?;
+            if *migration == TAGS_TABLE {
+                index_tags(conn, None, false)?;
+                index_tags(conn, None, true)?;
+            }
+        }
+        diesel::sql_query(format!("PRAGMA user_version = {}", MIGRATIONS.len())).execute(conn)?;
+        Ok(())
+    })
+}
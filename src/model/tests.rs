@@ -0,0 +1,1126 @@
+//! A conformance suite that every [`Model`] implementation must pass. Each
+//! backend runs the same checks, including random sequences of operations
+//! compared against a simple reference model.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use proptest::prelude::*;
+use tempfile::TempDir;
+use time::{Date, Month};
+
+use super::*;
+use crate::model::sqlite_model::establish_connection;
+
+/// A model under test, along with anything that has to outlive it.
+struct Backend {
+    model: Box<dyn Model>,
+    _dir: Option<TempDir>,
+}
+
+fn mem_model() -> Backend {
+    Backend {
+        model: Box::new(MemModel::default()),
+        _dir: None,
+    }
+}
+
+fn sqlite_model() -> Backend {
+    let conn = establish_connection(std::path::Path::new(":memory:")).unwrap();
+    Backend {
+        model: Box::new(SqliteModel::new(conn)),
+        _dir: None,
+    }
+}
+
+fn markdown_model() -> Backend {
+    let dir = tempfile::tempdir().unwrap();
+    Backend {
+        model: Box::new(MarkdownModel::new(dir.path().to_path_buf())),
+        _dir: Some(dir),
+    }
+}
+
+fn cached_model() -> Backend {
+    let backend = sqlite_model();
+    Backend {
+        model: Box::new(CachedModel::new(backend.model)),
+        _dir: None,
+    }
+}
+
+fn recurring_model() -> Backend {
+    Backend {
+        model: Box::new(RecurringModel::new(Box::new(MemModel::default()))),
+        _dir: None,
+    }
+}
+
+const DAYS: i64 = 3;
+
+fn day(offset: i64) -> Date {
+    Date::from_calendar_date(2025, Month::October, 14).unwrap() + time::Duration::days(offset)
+}
+
+/// The reference every backend is compared against.
+#[derive(Default)]
+struct Reference(BTreeMap<Date, (Vec<Event>, Vec<Task>)>);
+
+impl Reference {
+    fn apply(&mut self, op: &Op) -> bool {
+        if let Op::MoveEvent(d, i, to, j) | Op::MoveTask(d, i, to, j) = *op {
+            let is_task = matches!(op, Op::MoveTask(..));
+            let len = |x: &mut Self, d| {
+                let (events, tasks) = x.0.entry(day(d)).or_default();
+                if is_task { tasks.len() } else { events.len() }
+            };
+            let ok = i < len(self, d) && j <= len(self, to) - usize::from(d == to);
+            if ok {
+                let (events, tasks) = self.0.entry(day(d)).or_default();
+                let entry = if is_task {
+                    Err(tasks.remove(i))
+                } else {
+                    Ok(events.remove(i))
+                };
+                let (events, tasks) = self.0.entry(day(to)).or_default();
+                match entry {
+                    Ok(event) => events.insert(j, event),
+                    Err(task) => tasks.insert(j, task),
+                }
+            }
+            self.0.retain(|_, (e, t)| !e.is_empty() || !t.is_empty());
+            return ok;
+        }
+
+        let (events, tasks) = self.0.entry(day(op.day())).or_default();
+        let ok = match op {
+            Op::NewEvent(_, i) if *i <= events.len() => {
+                events.insert(*i, Event::default());
+                true
+            }
+            Op::NewTask(_, i) if *i <= tasks.len() => {
+                tasks.insert(*i, Task::default());
+                true
+            }
+            Op::DeleteEvent(_, i) if *i < events.len() => {
+                events.remove(*i);
+                true
+            }
+            Op::DeleteTask(_, i) if *i < tasks.len() => {
+                tasks.remove(*i);
+                true
+            }
+            Op::ReplaceEvent(_, i, e) if *i < events.len() => {
+                events[*i] = e.clone();
+                true
+            }
+            Op::ReplaceTask(_, i, t) if *i < tasks.len() => {
+                tasks[*i] = t.clone();
+                true
+            }
+            _ => false,
+        };
+        self.0.retain(|_, (e, t)| !e.is_empty() || !t.is_empty());
+        ok
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    NewEvent(i64, usize),
+    NewTask(i64, usize),
+    DeleteEvent(i64, usize),
+    DeleteTask(i64, usize),
+    ReplaceEvent(i64, usize, Event),
+    ReplaceTask(i64, usize, Task),
+    MoveEvent(i64, usize, i64, usize),
+    MoveTask(i64, usize, i64, usize),
+}
+
+impl Op {
+    fn day(&self) -> i64 {
+        match self {
+            Op::NewEvent(d, _)
+            | Op::NewTask(d, _)
+            | Op::DeleteEvent(d, _)
+            | Op::DeleteTask(d, _)
+            | Op::ReplaceEvent(d, _, _)
+            | Op::ReplaceTask(d, _, _)
+            | Op::MoveEvent(d, _, _, _)
+            | Op::MoveTask(d, _, _, _) => *d,
+        }
+    }
+
+    fn apply(&self, model: &mut dyn Model) -> Result<()> {
+        match self.clone() {
+            Op::NewEvent(d, i) => model.new_event(day(d), i),
+            Op::NewTask(d, i) => model.new_task(day(d), i),
+            Op::DeleteEvent(d, i) => model.delete_event(day(d), i),
+            Op::DeleteTask(d, i) => model.delete_task(day(d), i),
+            Op::ReplaceEvent(d, i, e) => model.replace_event(day(d), i, e),
+            Op::ReplaceTask(d, i, t) => model.replace_task(day(d), i, t),
+            Op::MoveEvent(d, i, to, j) => model.move_event(day(d), i, day(to), j),
+            Op::MoveTask(d, i, to, j) => model.move_task(day(d), i, day(to), j),
+        }
+    }
+}
+
+fn importance() -> impl Strategy<Value = Importance> {
+    prop_oneof![Just(Importance::Normal), Just(Importance::High)]
+}
+
+fn completion_level() -> impl Strategy<Value = CompletionLevel> {
+    prop_oneof![
+        Just(CompletionLevel::None),
+        Just(CompletionLevel::Partial),
+        Just(CompletionLevel::Full),
+    ]
+}
+
+/// No times, a start time, or a start and end time, to the minute.
+fn times() -> impl Strategy<Value = (Option<Time>, Option<Time>)> {
+    let minute = 0..24 * 60u16;
+    let time = |x: u16| Time::from_hms((x / 60) as u8, (x % 60) as u8, 0).unwrap();
+    prop_oneof![
+        Just((None, None)),
+        minute.clone().prop_map(move |x| (Some(time(x)), None)),
+        (minute.clone(), minute)
+            .prop_map(move |(x, y)| { (Some(time(x.min(y))), Some(time(x.max(y)))) }),
+    ]
+}
+
+/// Any printable text, mixed with text that backends might read as times,
+/// due dates or formatting.
+fn title() -> impl Strategy<Value = String> {
+    let tricky = prop::sample::select(vec![
+        "**x**",
+        "10:00 call",
+        "9:30-10:00",
+        "pay due:2025-01-01",
+        "a <!-- b -->",
+        "\\*",
+    ]);
+    prop_oneof![
+        "\\PC{0,12}",
+        "[0-9a-z #:*<>!\\\\-]{0,16}",
+        tricky.prop_map(str::to_string),
+    ]
+}
+
+fn event() -> impl Strategy<Value = Event> {
+    (title(), importance(), times()).prop_map(|(title, importance, (start, end))| Event {
+        title,
+        importance,
+        start,
+        end,
+        uid: String::new(),
+    })
+}
+
+fn task() -> impl Strategy<Value = Task> {
+    let due = prop::option::of((-2..DAYS + 2).prop_map(day));
+    (title(), completion_level(), importance(), due, 0..3u8).prop_map(
+        |(title, completion_level, importance, due, depth)| Task {
+            title,
+            completion_level,
+            importance,
+            due,
+            depth,
+            uid: String::new(),
+        },
+    )
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let d = 0..DAYS;
+    let i = 0..4usize;
+    prop_oneof![
+        3 => (d.clone(), i.clone()).prop_map(|(d, i)| Op::NewEvent(d, i)),
+        3 => (d.clone(), i.clone()).prop_map(|(d, i)| Op::NewTask(d, i)),
+        1 => (d.clone(), i.clone()).prop_map(|(d, i)| Op::DeleteEvent(d, i)),
+        1 => (d.clone(), i.clone()).prop_map(|(d, i)| Op::DeleteTask(d, i)),
+        2 => (d.clone(), i.clone(), event()).prop_map(|(d, i, e)| Op::ReplaceEvent(d, i, e)),
+        2 => (d.clone(), i.clone(), task()).prop_map(|(d, i, t)| Op::ReplaceTask(d, i, t)),
+        1 => (d.clone(), i.clone(), d.clone(), i.clone())
+            .prop_map(|(d, i, to, j)| Op::MoveEvent(d, i, to, j)),
+        1 => (d.clone(), i.clone(), d, i).prop_map(|(d, i, to, j)| Op::MoveTask(d, i, to, j)),
+    ]
+}
+
+/// An event without the UID its model gave it, to compare with the reference.
+fn event_of(event: Event) -> Event {
+    Event {
+        uid: String::new(),
+        ..event
+    }
+}
+
+/// A task without the UID its model gave it, to compare with the reference.
+fn task_of(task: Task) -> Task {
+    Task {
+        uid: String::new(),
+        ..task
+    }
+}
+
+fn assert_matches(model: &dyn Model, reference: &Reference) {
+    let mut uids = BTreeSet::new();
+    for offset in 0..DAYS {
+        let date = day(offset);
+        let (events, tasks) = reference.0.get(&date).cloned().unwrap_or_default();
+
+        let mut uid_is_new = |uid: String| !uid.is_empty() && uids.insert(uid);
+        assert!(model.events_iter(date).all(|x| uid_is_new(x.uid)));
+        assert!(model.tasks_iter(date).all(|x| uid_is_new(x.uid)));
+        assert_eq!(
+            model.events_iter(date).map(event_of).collect::<Vec<_>>(),
+            events
+        );
+        assert_eq!(
+            model.tasks_iter(date).map(task_of).collect::<Vec<_>>(),
+            tasks
+        );
+        assert_eq!(model.events_len(date), events.len());
+        assert_eq!(model.tasks_len(date), tasks.len());
+        for (i, event) in events.iter().enumerate() {
+            assert_eq!(&event_of(model.get_event(date, i).unwrap()), event);
+        }
+        for (i, task) in tasks.iter().enumerate() {
+            assert_eq!(&task_of(model.get_task(date, i).unwrap()), task);
+        }
+        assert!(model.get_event(date, events.len()).is_err());
+        assert!(model.get_task(date, tasks.len()).is_err());
+    }
+    assert_eq!(
+        model.dates(),
+        reference.0.keys().copied().collect::<Vec<_>>()
+    );
+    let mut due: Vec<(Date, Task)> = reference
+        .0
+        .iter()
+        .flat_map(|(date, (_, tasks))| tasks.iter().map(|x| (*date, x.clone())))
+        .filter(|(_, x)| x.is_open() && x.due.is_some_and(|x| x <= day(1)))
+        .collect();
+    due.sort_by_key(|(_, x)| x.due);
+    let found = model.due_tasks(day(1)).into_iter();
+    assert_eq!(found.map(|(d, x)| (d, task_of(x))).collect::<Vec<_>>(), due);
+    assert!(model.err().is_ok());
+}
+
+fn new_entries_are_empty(backend: Backend) {
+    let mut model = backend.model;
+    model.new_event(day(0), 0).unwrap();
+    model.new_task(day(0), 0).unwrap();
+    let event = model.get_event(day(0), 0).unwrap();
+    let task = model.get_task(day(0), 0).unwrap();
+    assert!(!event.uid.is_empty() && !task.uid.is_empty() && event.uid != task.uid);
+    assert_eq!(event_of(event), Event::default());
+    assert_eq!(task_of(task), Task::default());
+    assert_eq!(model.events_len(day(1)), 0);
+    assert_eq!(model.tasks_len(day(1)), 0);
+}
+
+fn out_of_bounds_operations_fail(backend: Backend) {
+    let mut model = backend.model;
+    assert!(model.new_event(day(0), 1).is_err());
+    assert!(model.new_task(day(0), 1).is_err());
+    assert!(model.delete_event(day(0), 0).is_err());
+    assert!(model.delete_task(day(0), 0).is_err());
+    assert!(model.replace_event(day(0), 0, Event::default()).is_err());
+    assert!(model.replace_task(day(0), 0, Task::default()).is_err());
+    assert!(model.move_event(day(0), 0, day(1), 0).is_err());
+    assert!(model.move_task(day(0), 0, day(0), 0).is_err());
+    assert!(model.get_event(day(0), 0).is_err());
+    assert!(model.get_task(day(0), 0).is_err());
+    assert_eq!(model.events_len(day(0)), 0);
+    assert_eq!(model.tasks_len(day(0)), 0);
+    assert!(model.dates().is_empty());
+    assert!(model.err().is_ok());
+}
+
+fn inserts_shift_later_entries(backend: Backend) {
+    let mut model = backend.model;
+    for (i, title) in ["b", "c", "a"].into_iter().enumerate() {
+        let idx = if i == 2 { 0 } else { i };
+        model.new_task(day(0), idx).unwrap();
+        model
+            .replace_task(
+                day(0),
+                idx,
+                Task {
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    let titles: Vec<String> = model.tasks_iter(day(0)).map(|x| x.title).collect();
+    assert_eq!(titles, ["a", "b", "c"]);
+
+    model.delete_task(day(0), 1).unwrap();
+    let titles: Vec<String> = model.tasks_iter(day(0)).map(|x| x.title).collect();
+    assert_eq!(titles, ["a", "c"]);
+}
+
+fn tags_are_found_in_titles(backend: Backend) {
+    let mut model = backend.model;
+    for (i, title) in ["#Work: plan #q3", "lunch#not-a-tag", "#work/review #work"]
+        .into_iter()
+        .enumerate()
+    {
+        model.new_task(day(i as i64), 0).unwrap();
+        model
+            .replace_task(
+                day(i as i64),
+                0,
+                Task {
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    model.new_event(day(1), 0).unwrap();
+    model
+        .replace_event(
+            day(1),
+            0,
+            Event {
+                title: "offsite #work".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        model.tags(),
+        [
+            ("q3".to_string(), 1),
+            ("work".to_string(), 3),
+            ("work/review".to_string(), 1)
+        ]
+    );
+    let tagged: Vec<(Date, String)> = model
+        .tagged("work")
+        .into_iter()
+        .map(|(date, entry)| (date, entry.title().to_string()))
+        .collect();
+    assert_eq!(
+        tagged,
+        [
+            (day(0), "#Work: plan #q3".to_string()),
+            (day(1), "offsite #work".to_string()),
+            (day(2), "#work/review #work".to_string()),
+        ]
+    );
+    assert!(model.tagged("lunch").is_empty());
+
+    model.new_task(day(0), 0).unwrap();
+    model.move_task(day(2), 0, day(3), 0).unwrap();
+    model.delete_event(day(1), 0).unwrap();
+    model
+        .replace_task(
+            day(0),
+            0,
+            Task {
+                title: "#Ärger".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let tagged: Vec<(Date, String)> = model
+        .tagged("work")
+        .into_iter()
+        .map(|(date, entry)| (date, entry.title().to_string()))
+        .collect();
+    assert_eq!(
+        tagged,
+        [
+            (day(0), "#Work: plan #q3".to_string()),
+            (day(3), "#work/review #work".to_string()),
+        ]
+    );
+    assert_eq!(model.tagged("ärger").len(), 1);
+}
+
+fn due_tasks_are_found_across_days(backend: Backend) {
+    let mut model = backend.model;
+    for (i, (title, completion_level, due)) in [
+        ("rent", CompletionLevel::None, Some(day(1))),
+        ("slides", CompletionLevel::Full, Some(day(0))),
+        ("taxes", CompletionLevel::Partial, Some(day(0))),
+        ("someday", CompletionLevel::None, None),
+        ("later", CompletionLevel::None, Some(day(5))),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        model.new_task(day(i as i64 % 2), 0).unwrap();
+        model
+            .replace_task(
+                day(i as i64 % 2),
+                0,
+                Task {
+                    title: title.to_string(),
+                    completion_level,
+                    due,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+
+    let due: Vec<(Date, String)> = model
+        .due_tasks(day(2))
+        .into_iter()
+        .map(|(date, task)| (date, task.title))
+        .collect();
+    assert_eq!(
+        due,
+        [(day(0), "taxes".to_string()), (day(0), "rent".to_string())]
+    );
+    assert!(model.due_tasks(day(-1)).is_empty());
+}
+
+/// The model's templates without the UIDs it may have given them.
+fn templates_of(model: &dyn Model) -> Vec<Template> {
+    model
+        .templates()
+        .into_iter()
+        .map(|x| Template {
+            entry: x.entry.without_uid(),
+            ..x
+        })
+        .collect()
+}
+
+fn templates_are_stored(backend: Backend) {
+    use recurrence::{End, Frequency};
+
+    let mut model = backend.model;
+    let mut standup = Template {
+        recurrence: Recurrence::new(day(0), Frequency::Weekdays, End::Count(10)),
+        entry: Entry::Event(Event {
+            title: "standup".to_string(),
+            importance: Importance::High,
+            start: Some(Time::from_hms(9, 30, 0).unwrap()),
+            end: None,
+            uid: String::new(),
+        }),
+    };
+    let rent = Template {
+        recurrence: Recurrence::new(day(1), Frequency::MonthlyByDate(1), End::Until(day(90))),
+        entry: Entry::Task(Task {
+            title: "pay #rent".to_string(),
+            due: Some(day(2)),
+            ..Default::default()
+        }),
+    };
+    model.add_template(standup.clone()).unwrap();
+    model.add_template(rent.clone()).unwrap();
+    assert_eq!(templates_of(&*model), [standup.clone(), rent.clone()]);
+
+    standup.recurrence.except.insert(day(1));
+    model.replace_template(0, standup.clone()).unwrap();
+    assert!(model.replace_template(2, rent.clone()).is_err());
+    assert_eq!(templates_of(&*model), [standup, rent.clone()]);
+
+    model.delete_template(0).unwrap();
+    assert!(model.delete_template(1).is_err());
+    assert_eq!(templates_of(&*model), [rent]);
+    model.delete_template(0).unwrap();
+    assert!(model.templates().is_empty());
+    assert!(model.err().is_ok());
+}
+
+fn deleted_entries_go_to_the_trash(backend: Backend) {
+    let mut model = backend.model;
+    let titled = |title: &str, depth| Task {
+        title: title.to_string(),
+        depth,
+        ..Default::default()
+    };
+    for (i, task) in [titled("pack", 0), titled("socks", 1), titled("fly", 0)]
+        .into_iter()
+        .enumerate()
+    {
+        model.new_task(day(0), i).unwrap();
+        model.replace_task(day(0), i, task).unwrap();
+    }
+    model.new_event(day(1), 0).unwrap();
+    let socks = model.get_task(day(0), 1).unwrap();
+
+    model.delete_task(day(0), 1).unwrap();
+    model.delete_event(day(1), 0).unwrap();
+    model.delete_task(day(0), 0).unwrap();
+    model.delete_task(day(0), 0).unwrap();
+    let trash = model.trash();
+    assert_eq!(trash.len(), 4);
+    assert_eq!(
+        trash[0],
+        Trashed {
+            date: day(0),
+            index: 1,
+            deleted: today(),
+            entry: Entry::Task(socks.clone()),
+        }
+    );
+    assert_eq!(
+        trash[1].entry.clone().without_uid(),
+        Entry::Event(Event::default())
+    );
+
+    // the day is now empty, so there is nothing to nest under
+    model.restore(0).unwrap();
+    assert_eq!(
+        model.get_task(day(0), 0).unwrap(),
+        Task { depth: 0, ..socks }
+    );
+    assert_eq!(model.trash().len(), 3);
+    model.restore(1).unwrap();
+    assert_eq!(
+        task_of(model.get_task(day(0), 0).unwrap()),
+        titled("pack", 0)
+    );
+    assert!(model.restore(2).is_err());
+
+    model.purge_before(today()).unwrap();
+    assert_eq!(model.trash().len(), 2);
+    model.purge_before(today().next_day().unwrap()).unwrap();
+    assert!(model.trash().is_empty());
+    assert!(model.purge(0).is_err());
+    assert_eq!(model.events_len(day(1)), 0);
+    assert!(model.err().is_ok());
+}
+
+fn batched_changes_are_kept(backend: Backend) {
+    let mut model = backend.model;
+    model.begin();
+    model.new_task(day(0), 0).unwrap();
+    model.new_task(day(0), 1).unwrap();
+    model
+        .replace_task(
+            day(0),
+            1,
+            Task {
+                title: "second".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    model.move_task(day(0), 0, day(1), 0).unwrap();
+    model.commit().unwrap();
+
+    assert_eq!(model.tasks_len(day(0)), 1);
+    assert_eq!(model.tasks_len(day(1)), 1);
+    assert_eq!(model.get_task(day(0), 0).unwrap().title, "second");
+    assert!(model.err().is_ok());
+}
+
+fn activity_is_counted_per_day(backend: Backend) {
+    let mut model = backend.model;
+    model.new_event(day(0), 0).unwrap();
+    for (i, completion_level) in [
+        CompletionLevel::Full,
+        CompletionLevel::Partial,
+        CompletionLevel::Full,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        model.new_task(day(0), i).unwrap();
+        model
+            .replace_task(
+                day(0),
+                i,
+                Task {
+                    completion_level,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    model.new_event(day(2), 0).unwrap();
+    model.new_task(day(5), 0).unwrap();
+
+    let activity = model.activity(day(0), day(2));
+    assert_eq!(
+        activity.into_iter().collect::<Vec<_>>(),
+        [
+            (
+                day(0),
+                Activity {
+                    entries: 4,
+                    completed: 2
+                }
+            ),
+            (
+                day(2),
+                Activity {
+                    entries: 1,
+                    completed: 0
+                }
+            ),
+        ]
+    );
+    assert!(model.activity(day(3), day(4)).is_empty());
+    assert!(model.err().is_ok());
+}
+
+fn moods_are_kept(backend: Backend) {
+    let mut model = backend.model;
+    assert!(model.mood(day(0)).is_empty());
+    let mood = Mood {
+        mood: Some(4),
+        energy: Some(2),
+        reflection: "slow start, good talks".to_string(),
+    };
+    model.set_mood(day(0), mood.clone()).unwrap();
+    model
+        .set_mood(
+            day(2),
+            Mood {
+                energy: Some(5),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    model.new_task(day(2), 0).unwrap();
+    assert_eq!(model.mood(day(0)), mood);
+    assert_eq!(model.dates(), [day(2)]);
+    assert_eq!(
+        model.moods(day(-1), day(1)).into_iter().collect::<Vec<_>>(),
+        [(day(0), mood)]
+    );
+
+    model.set_mood(day(0), Mood::default()).unwrap();
+    assert!(model.mood(day(0)).is_empty());
+    assert_eq!(model.mood(day(2)).energy, Some(5));
+    assert!(model.err().is_ok());
+}
+
+fn journals_are_kept(backend: Backend) {
+    let mut model = backend.model;
+    assert_eq!(model.journal(day(0)), "");
+    let journal = "# Not a heading\n\nA long walk by the river.\n  Then #reading, all evening.";
+    model.set_journal(day(0), journal).unwrap();
+    model.set_journal(day(2), "rain").unwrap();
+    model.new_task(day(1), 0).unwrap();
+    assert_eq!(model.journal(day(0)), journal);
+    assert_eq!(model.dates(), [day(1)]);
+    assert_eq!(
+        model.journals(),
+        [(day(0), journal.to_string()), (day(2), "rain".to_string())]
+    );
+
+    model.set_journal(day(2), "").unwrap();
+    assert_eq!(model.journals().len(), 1);
+    assert!(model.err().is_ok());
+}
+
+fn realized_days_are_kept(backend: Backend) {
+    let mut model = backend.model;
+    assert!(model.realized(day(0)).is_empty());
+    model.set_realized(day(0), "a@jotty").unwrap();
+    model.set_realized(day(0), "b@jotty").unwrap();
+    model.set_realized(day(0), "a@jotty").unwrap();
+    model.set_realized(day(1), "b@jotty").unwrap();
+    assert_eq!(
+        model.realized(day(0)),
+        BTreeSet::from(["a@jotty".to_string(), "b@jotty".to_string()])
+    );
+    assert_eq!(
+        model.realized(day(1)),
+        BTreeSet::from(["b@jotty".to_string()])
+    );
+    assert!(model.realized(day(2)).is_empty());
+    assert!(model.err().is_ok());
+}
+
+fn filled_days_are_kept(backend: Backend) {
+    let mut model = backend.model;
+    assert!(!model.filled(day(0)));
+    model.set_filled(day(0)).unwrap();
+    model.set_filled(day(2)).unwrap();
+    model.set_filled(day(0)).unwrap();
+    assert!(model.filled(day(0)));
+    assert!(!model.filled(day(1)));
+    assert!(model.filled(day(2)));
+    assert!(model.dates().is_empty());
+    assert!(model.err().is_ok());
+}
+
+fn journal_is_searched(backend: Backend) {
+    let mut model = backend.model;
+    model.new_event(day(0), 0).unwrap();
+    model
+        .replace_event(
+            day(0),
+            0,
+            Event {
+                title: "Walk with Sam".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    model.new_task(day(0), 0).unwrap();
+    model
+        .replace_task(
+            day(0),
+            0,
+            Task {
+                title: "call sam back".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    model.new_task(day(1), 0).unwrap();
+    model
+        .set_journal(
+            day(-1),
+            "Met SAM at the station.\nNothing else.\n  Sam again_%\nÄrger am Bahnhof",
+        )
+        .unwrap();
+
+    assert_eq!(
+        model.search("sam"),
+        [
+            (
+                day(-1),
+                Found::Journal("Met SAM at the station.".to_string())
+            ),
+            (day(-1), Found::Journal("Sam again_%".to_string())),
+            (
+                day(0),
+                Found::Entry(Entry::Event(model.get_event(day(0), 0).unwrap()))
+            ),
+            (
+                day(0),
+                Found::Entry(Entry::Task(model.get_task(day(0), 0).unwrap()))
+            ),
+        ]
+    );
+    assert_eq!(model.search("n_%").len(), 1);
+    assert!(model.search("ga%").is_empty());
+    assert_eq!(model.search("ärger").len(), 1);
+    assert!(model.search("").is_empty());
+    assert!(model.err().is_ok());
+}
+
+fn matches_reference(backend: Backend, ops: Vec<Op>) {
+    let mut model = backend.model;
+    let mut reference = Reference::default();
+    for op in ops {
+        assert_eq!(
+            op.apply(model.as_mut()).is_ok(),
+            reference.apply(&op),
+            "{op:?}"
+        );
+        assert_matches(model.as_ref(), &reference);
+    }
+}
+
+macro_rules! conformance_suite {
+    ($name:ident, $backend:expr, $cases:expr) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn new_entries_are_empty() {
+                super::new_entries_are_empty($backend);
+            }
+
+            #[test]
+            fn out_of_bounds_operations_fail() {
+                super::out_of_bounds_operations_fail($backend);
+            }
+
+            #[test]
+            fn inserts_shift_later_entries() {
+                super::inserts_shift_later_entries($backend);
+            }
+
+            #[test]
+            fn tags_are_found_in_titles() {
+                super::tags_are_found_in_titles($backend);
+            }
+
+            #[test]
+            fn due_tasks_are_found_across_days() {
+                super::due_tasks_are_found_across_days($backend);
+            }
+
+            #[test]
+            fn templates_are_stored() {
+                super::templates_are_stored($backend);
+            }
+
+            #[test]
+            fn deleted_entries_go_to_the_trash() {
+                super::deleted_entries_go_to_the_trash($backend);
+            }
+
+            #[test]
+            fn batched_changes_are_kept() {
+                super::batched_changes_are_kept($backend);
+            }
+
+            #[test]
+            fn activity_is_counted_per_day() {
+                super::activity_is_counted_per_day($backend);
+            }
+
+            #[test]
+            fn moods_are_kept() {
+                super::moods_are_kept($backend);
+            }
+
+            #[test]
+            fn journals_are_kept() {
+                super::journals_are_kept($backend);
+            }
+
+            #[test]
+            fn realized_days_are_kept() {
+                super::realized_days_are_kept($backend);
+            }
+
+            #[test]
+            fn filled_days_are_kept() {
+                super::filled_days_are_kept($backend);
+            }
+
+            #[test]
+            fn journal_is_searched() {
+                super::journal_is_searched($backend);
+            }
+
+            proptest! {
+                #![proptest_config(ProptestConfig::with_cases($cases))]
+
+                #[test]
+                fn matches_reference(ops in prop::collection::vec(op(), 1..40)) {
+                    super::matches_reference($backend, ops);
+                }
+            }
+        }
+    };
+}
+
+conformance_suite!(mem, mem_model(), 256);
+conformance_suite!(sqlite, sqlite_model(), 64);
+conformance_suite!(markdown, markdown_model(), 32);
+conformance_suite!(cached, cached_model(), 64);
+conformance_suite!(recurring, recurring_model(), 64);
+
+#[test]
+fn sqlite_keeps_entry_history() {
+    use diesel::RunQueryDsl;
+
+    let mut conn = establish_connection(std::path::Path::new(":memory:")).unwrap();
+    diesel::sql_query("INSERT INTO `tasks` (`date`, `index`, `title`, `completion_level`, `importance`, `depth`) VALUES (?, 0, 'old task', 0, 0, 0)")
+        .bind::<diesel::sql_types::Integer, _>(day(0).to_julian_day())
+        .execute(&mut conn)
+        .unwrap();
+    let mut model = SqliteModel::new(conn);
+    assert!(model.task_history(day(0), 0).is_empty());
+
+    model.new_task(day(0), 1).unwrap();
+    for (title, completion_level) in [
+        ("draft", CompletionLevel::None),
+        ("draft the plan", CompletionLevel::None),
+        ("draft the plan", CompletionLevel::Full),
+    ] {
+        model
+            .replace_task(
+                day(0),
+                1,
+                Task {
+                    title: title.to_string(),
+                    completion_level,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    model.move_task(day(0), 1, day(1), 0).unwrap();
+    let kinds: Vec<ChangeKind> = model
+        .task_history(day(1), 0)
+        .into_iter()
+        .map(|x| x.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ChangeKind::Created,
+            ChangeKind::Titled("draft".to_string()),
+            ChangeKind::Titled("draft the plan".to_string()),
+            ChangeKind::Completed(CompletionLevel::Full),
+            ChangeKind::Moved(day(1)),
+        ]
+    );
+    model.delete_task(day(1), 0).unwrap();
+    model.restore(0).unwrap();
+    let restored: Vec<ChangeKind> = model
+        .task_history(day(1), 0)
+        .into_iter()
+        .map(|x| x.kind)
+        .collect();
+    assert_eq!(restored, kinds);
+
+    model.move_task(day(0), 0, day(0), 0).unwrap();
+    assert!(model.task_history(day(0), 0).is_empty());
+    model.move_task(day(0), 0, day(2), 0).unwrap();
+    let kinds: Vec<ChangeKind> = model
+        .task_history(day(2), 0)
+        .into_iter()
+        .map(|x| x.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ChangeKind::Tracked("old task".to_string()),
+            ChangeKind::Moved(day(2)),
+        ]
+    );
+    assert!(model.event_history(day(2), 0).is_empty());
+    assert!(model.err().is_ok());
+
+    let mut model = CachedModel::new(Box::new(RecurringModel::new(Box::new(model))));
+    model.delete_task(day(2), 0).unwrap();
+    model.restore(0).unwrap();
+    assert_eq!(model.task_history(day(2), 0).len(), 2);
+    assert!(model.err().is_ok());
+}
+
+#[test]
+fn sqlite_indexes_tags_already_in_a_journal() {
+    use diesel::RunQueryDsl;
+
+    #[derive(diesel::QueryableByName)]
+    struct UserVersion {
+        #[diesel(sql_type = diesel::sql_types::Integer)]
+        user_version: i32,
+    }
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("journal.db");
+    let mut conn = establish_connection(&path).unwrap();
+    let version = diesel::sql_query("PRAGMA user_version")
+        .get_result::<UserVersion>(&mut conn)
+        .unwrap()
+        .user_version;
+    for sql in [
+        "DROP TABLE `tags`".to_string(),
+        format!("PRAGMA user_version = {}", version - 2),
+        "INSERT INTO `events` (`date`, `index`, `title`, `importance`) VALUES (0, 0, 'offsite #Work', 0)".to_string(),
+        // more tags than fit in one statement's variables
+        "WITH RECURSIVE `n` (`i`) AS (SELECT 1 UNION ALL SELECT `i` + 1 FROM `n` WHERE `i` < 10000)
+        INSERT INTO `tasks` (`date`, `index`, `title`, `completion_level`, `importance`, `depth`)
+        SELECT 1, `i`, '#many #task' || `i`, 0, 0, 0 FROM `n`".to_string(),
+    ] {
+        diesel::sql_query(sql).execute(&mut conn).unwrap();
+    }
+    drop(conn);
+
+    let model = SqliteModel::new(establish_connection(&path).unwrap());
+    let tags = model.tags();
+    assert_eq!(tags.len(), 10002);
+    assert!(tags.contains(&("many".to_string(), 10000)));
+    assert!(tags.contains(&("work".to_string(), 1)));
+    assert_eq!(model.tagged("work").len(), 1);
+}
+
+#[test]
+fn sqlite_keeps_habits() {
+    let conn = establish_connection(std::path::Path::new(":memory:")).unwrap();
+    let mut model = SqliteModel::new(conn);
+    model.add_habit("exercise").unwrap();
+    model.add_habit("read").unwrap();
+    assert!(model.add_habit("read").is_err());
+    assert_eq!(model.habits(), ["exercise", "read"]);
+
+    model.set_habit_done("read", day(0), true).unwrap();
+    model.set_habit_done("read", day(0), true).unwrap();
+    model.set_habit_done("read", day(1), true).unwrap();
+    model.set_habit_done("exercise", day(1), true).unwrap();
+    model.set_habit_done("read", day(1), false).unwrap();
+    assert_eq!(model.habit_days("read"), BTreeSet::from([day(0)]));
+    assert_eq!(
+        model.habits_on(day(1)),
+        [("exercise".to_string(), true), ("read".to_string(), false)]
+    );
+    assert!(model.set_habit_done("sleep", day(0), true).is_err());
+
+    model.remove_habit("exercise").unwrap();
+    assert!(model.remove_habit("exercise").is_err());
+    assert_eq!(model.habits(), ["read"]);
+    assert!(model.habit_days("exercise").is_empty());
+    model.add_habit("exercise").unwrap();
+    assert!(model.habit_days("exercise").is_empty());
+    assert!(model.err().is_ok());
+
+    assert!(MemModel::default().add_habit("read").is_err());
+}
+
+#[test]
+fn markdown_keeps_file_errors_in_the_model() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut model = MarkdownModel::new(dir.path().to_path_buf());
+    model.new_task(day(0), 0).unwrap();
+    let task = Task {
+        title: "keep me".to_string(),
+        ..Default::default()
+    };
+    model.replace_task(day(0), 0, task.clone()).unwrap();
+
+    // the trash cannot be read, so the task stays on its day
+    std::fs::write(dir.path().join("trash.md"), "not a list").unwrap();
+    model.delete_task(day(0), 0).unwrap();
+    assert!(model.err().is_err());
+    assert!(model.delete_task(day(0), 1).is_err());
+    let file = std::fs::read_to_string(dir.path().join("2025/10/14.md")).unwrap();
+    assert!(file.contains("keep me"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut model = MarkdownModel::new(dir.path().to_path_buf());
+    std::fs::create_dir_all(dir.path().join("2025/10")).unwrap();
+    std::fs::write(
+        dir.path().join("2025/10/14.md"),
+        "# 2025-10-14\n\nnot a list\n",
+    )
+    .unwrap();
+    model.new_event(day(0), 0).unwrap();
+    model.move_task(day(1), 0, day(0), 0).unwrap();
+    assert!(model.err().is_err());
+}
+
+#[test]
+fn markdown_keeps_other_sections_and_outside_edits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("2025/10/14.md");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &path,
+        "# 2025-10-14\n\n## Notes\n\nfree text\n\n- a list\n### deeper\n\n## Tasks\n\n- ○ rent\n",
+    )
+    .unwrap();
+    let mut model = MarkdownModel::new(dir.path().to_path_buf());
+    assert_eq!(model.dates(), [day(0)]);
+    assert_eq!(model.get_task(day(0), 0).unwrap().title, "rent");
+    model.new_event(day(0), 0).unwrap();
+    assert!(model.err().is_ok());
+    let file = std::fs::read_to_string(&path).unwrap();
+    assert!(file.ends_with("## Notes\n\nfree text\n\n- a list\n### deeper\n"));
+
+    // an edit that keeps the length and the time the file was changed
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    let edited = file.replace("rent", "bill");
+    std::fs::write(&path, &edited).unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(modified).unwrap();
+    drop(file);
+    model.new_event(day(0), 0).unwrap();
+    assert_eq!(model.get_task(day(0), 0).unwrap().title, "bill");
+    assert!(model.err().is_ok());
+}
@@ -0,0 +1,183 @@
+//! Page templates: entries that are added to new days, read from a Markdown
+//! file where each `#` heading names the days it applies to, like
+//!
+//! ```text
+//! # Monday
+//!
+//! ## Events
+//!
+//! - 10:00 team sync
+//!
+//! ## Tasks
+//!
+//! - ○ weekly review
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, anyhow, bail};
+use time::{Date, Weekday};
+
+use crate::interchange::markdown::{parse_event, parse_task, split_item};
+use crate::model::{Event, Model, Task};
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct PageTemplate {
+    pub days: Vec<Weekday>,
+    pub events: Vec<Event>,
+    pub tasks: Vec<Task>,
+}
+
+const WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Monday, "monday"),
+    (Weekday::Tuesday, "tuesday"),
+    (Weekday::Wednesday, "wednesday"),
+    (Weekday::Thursday, "thursday"),
+    (Weekday::Friday, "friday"),
+    (Weekday::Saturday, "saturday"),
+    (Weekday::Sunday, "sunday"),
+];
+
+impl PageTemplate {
+    pub fn matches(&self, date: Date) -> bool {
+        self.days.contains(&date.weekday())
+    }
+
+    /// Appends the template's entries to the end of `date`'s lists.
+    pub fn apply(&self, model: &mut dyn Model, date: Date) -> Result<()> {
+        for event in &self.events {
+            let idx = model.events_len(date);
+            model.new_event(date, idx)?;
+            model.replace_event(date, idx, event.clone())?;
+        }
+        for task in &self.tasks {
+            let idx = model.tasks_len(date);
+            model.new_task(date, idx)?;
+            model.replace_task(date, idx, task.clone())?;
+        }
+        model.err()
+    }
+}
+
+/// Reads the page templates in the file at `path`. A missing file has none.
+pub fn load(path: &Path) -> Result<Vec<PageTemplate>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    parse(&fs::read_to_string(path)?).map_err(|e| anyhow!("cannot read {}: {e}", path.display()))
+}
+
+/// Parses page templates. A heading is a list of days, like `Monday` or
+/// `Tue, Thu`, or one of `weekdays`, `weekends` and `every day`.
+pub fn parse(input: &str) -> Result<Vec<PageTemplate>> {
+    let mut templates: Vec<PageTemplate> = Vec::new();
+    let mut in_tasks = None;
+
+    for (line_no, line) in input.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+
+        if line.trim().is_empty() {
+            continue;
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            in_tasks = match heading.trim().to_lowercase().as_str() {
+                "events" => Some(false),
+                "tasks" => Some(true),
+                _ => return Err(err("expected an `Events` or `Tasks` heading")),
+            };
+        } else if let Some(heading) = line.strip_prefix("# ") {
+            let days = parse_days(heading).map_err(|e| err(&e.to_string()))?;
+            templates.push(PageTemplate {
+                days,
+                ..Default::default()
+            });
+            in_tasks = None;
+        } else if let Some((depth, item)) = split_item(line) {
+            let template = templates
+                .last_mut()
+                .ok_or(err("entry is not under a heading of days"))?;
+            match in_tasks {
+                Some(false) => template.events.push(parse_event(item)),
+                Some(true) => template.tasks.push(Task {
+                    depth,
+                    ..parse_task(item)
+                }),
+                None => return Err(err("entry is not under an `Events` or `Tasks` heading")),
+            }
+        } else {
+            return Err(err("expected a heading or a `- ` list item"));
+        }
+    }
+    Ok(templates)
+}
+
+fn parse_days(heading: &str) -> Result<Vec<Weekday>> {
+    let heading = heading.trim().to_lowercase();
+    let names: Vec<&str> = match heading.as_str() {
+        "every day" | "daily" => WEEKDAYS.iter().map(|(_, name)| *name).collect(),
+        "weekdays" => WEEKDAYS[..5].iter().map(|(_, name)| *name).collect(),
+        "weekends" => WEEKDAYS[5..].iter().map(|(_, name)| *name).collect(),
+        _ => heading.split(',').map(str::trim).collect(),
+    };
+    let mut days = Vec::new();
+    for name in names {
+        let Some((day, _)) = WEEKDAYS
+            .iter()
+            .find(|(_, x)| name.len() >= 3 && x.starts_with(name))
+        else {
+            bail!("expected days of the week, like `Monday` or `Tue, Thu`, got `{name}`");
+        };
+        if !days.contains(day) {
+            days.push(*day);
+        }
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+    use crate::model::{CompletionLevel, Importance, MemModel};
+
+    /// a Tuesday
+    fn date() -> Date {
+        Date::from_calendar_date(2025, Month::October, 14).unwrap()
+    }
+
+    #[test]
+    fn parses_templates() {
+        let templates = parse(
+            "# Monday\n\n## Events\n\n- 10:00 team sync\n\n## Tasks\n\n- ○ **weekly review**\n\n\
+             # tue, thu\n\n## Tasks\n\n- stretch\n\n# Weekends\n",
+        )
+        .unwrap();
+        assert_eq!(templates.len(), 3);
+        assert_eq!(templates[0].days, [Weekday::Monday]);
+        assert_eq!(templates[0].events[0].times().as_deref(), Some("10:00"));
+        assert_eq!(templates[0].tasks[0].title, "weekly review");
+        assert_eq!(templates[0].tasks[0].importance, Importance::High);
+        assert_eq!(templates[1].days, [Weekday::Tuesday, Weekday::Thursday]);
+        assert_eq!(templates[2].days, [Weekday::Saturday, Weekday::Sunday]);
+        assert!(templates[1].matches(date()));
+        assert!(!templates[0].matches(date()));
+
+        assert!(parse("# Someday\n").is_err());
+        assert!(parse("- orphan\n").is_err());
+        assert!(parse("# Monday\n- no section\n").is_err());
+    }
+
+    #[test]
+    fn applies_after_existing_entries() {
+        let template = &parse("# every day\n## Tasks\n- ● stretch\n").unwrap()[0];
+        let mut model = MemModel::default();
+        model.new_task(date(), 0).unwrap();
+        template.apply(&mut model, date()).unwrap();
+
+        let tasks: Vec<Task> = model.tasks_iter(date()).collect();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].title, "stretch");
+        assert_eq!(tasks[1].completion_level, CompletionLevel::Full);
+    }
+}
@@ -0,0 +1,211 @@
+//! Replays scripted key presses against a headless terminal. Scripts use the
+//! subset of the [VHS](https://github.com/charmbracelet/vhs) tape format that
+//! types keys, so `jotty.tape` can be replayed without any external tools.
+
+use anyhow::{Result, anyhow, bail};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use time::Date;
+
+use crate::controller::Controller;
+use crate::model::{MemModel, RecurringModel};
+use crate::view::View;
+
+/// Replays a script against an empty in-memory journal, starting on `date`.
+pub fn replay(
+    script: &str,
+    date: Date,
+    width: u16,
+    height: u16,
+) -> Result<Controller<TestBackend>> {
+    let events = parse(script)?;
+    let terminal = Terminal::new(TestBackend::new(width, height))?;
+    let model = RecurringModel::new(Box::new(MemModel::default()));
+    let view = View::new(Box::new(model), terminal).on_date(date);
+    let mut controller = Controller::new(view);
+    controller.replay(events)?;
+    Ok(controller)
+}
+
+/// Parses a script into the key presses it makes.
+///
+/// `Type`, `Enter`, `Space`, `Backspace`, `Escape`, `Tab` and the arrow keys
+/// are supported, including repeat counts and `Shift+` and `Alt+` modifiers.
+/// Commands that only affect a recording, like `Sleep`, `Set` and `Output`, are
+/// ignored, as is everything between `Hide` and `Show`, which is where tapes
+/// start jotty.
+pub fn parse(script: &str) -> Result<Vec<Event>> {
+    let mut events = Vec::new();
+    let mut hidden = false;
+
+    for (line_no, line) in script.lines().enumerate() {
+        let err = |msg: &str| anyhow!("line {}: {msg}", line_no + 1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        // a typing speed such as `Type@500ms` does not change what is typed
+        let command = command.split('@').next().unwrap_or_default();
+        let args = args.trim();
+        match command {
+            "Hide" => hidden = true,
+            "Show" => hidden = false,
+            "Output" | "Set" | "Sleep" | "Require" | "Source" | "Screenshot" | "Wait" => {}
+            _ if hidden => {}
+            "Type" => {
+                let text = args
+                    .strip_prefix('"')
+                    .and_then(|x| x.strip_suffix('"'))
+                    .or(args.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')))
+                    .or(args.strip_prefix('`').and_then(|x| x.strip_suffix('`')))
+                    .ok_or(err("expected quoted text to type"))?;
+                events.extend(
+                    text.chars()
+                        .map(|c| key(KeyCode::Char(c), KeyModifiers::NONE)),
+                );
+            }
+            _ => {
+                let (modifiers, name) = if let Some(name) = command.strip_prefix("Shift+") {
+                    (KeyModifiers::SHIFT, name)
+                } else if let Some(name) = command.strip_prefix("Alt+") {
+                    (KeyModifiers::ALT, name)
+                } else {
+                    (KeyModifiers::NONE, command)
+                };
+                let code = match name {
+                    "Enter" => KeyCode::Enter,
+                    "Space" => KeyCode::Char(' '),
+                    "Backspace" => KeyCode::Backspace,
+                    "Escape" => KeyCode::Esc,
+                    "Tab" if modifiers == KeyModifiers::SHIFT => KeyCode::BackTab,
+                    "Tab" => KeyCode::Tab,
+                    "Up" => KeyCode::Up,
+                    "Down" => KeyCode::Down,
+                    "Left" => KeyCode::Left,
+                    "Right" => KeyCode::Right,
+                    _ => return Err(err(&format!("unsupported command `{command}`"))),
+                };
+                let count = if args.is_empty() {
+                    1
+                } else {
+                    args.parse()
+                        .map_err(|_| err(&format!("invalid repeat count `{args}`")))?
+                };
+                events.extend((0..count).map(|_| key(code, modifiers)));
+            }
+        }
+    }
+
+    if events.is_empty() {
+        bail!("the script does not press any keys");
+    }
+    Ok(events)
+}
+
+/// The text on a headless terminal, one line per row.
+pub fn screen(backend: &TestBackend) -> String {
+    let buffer = backend.buffer();
+    let mut screen = String::new();
+    for y in 0..buffer.area.height {
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect();
+        screen.push_str(row.trim_end());
+        screen.push('\n');
+    }
+    screen
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use time::Month;
+
+    use super::*;
+    use crate::model::{CompletionLevel, Event, Importance, Task};
+
+    fn date() -> Date {
+        Date::from_calendar_date(2025, Month::October, 14).unwrap()
+    }
+
+    #[test]
+    fn parses_keys() {
+        let events = parse(
+            "Hide\nType \"cargo run\"\nEnter\nShow\n# a comment\nType@50ms \"t\"\nSleep 1s\nSpace 2\nShift+Right\nAlt+Up\nShift+Tab\n",
+        )
+        .unwrap();
+        assert_eq!(
+            events,
+            [
+                key(KeyCode::Char('t'), KeyModifiers::NONE),
+                key(KeyCode::Char(' '), KeyModifiers::NONE),
+                key(KeyCode::Char(' '), KeyModifiers::NONE),
+                key(KeyCode::Right, KeyModifiers::SHIFT),
+                key(KeyCode::Up, KeyModifiers::ALT),
+                key(KeyCode::BackTab, KeyModifiers::SHIFT),
+            ]
+        );
+        assert!(parse("Ctrl+C").is_err());
+        assert!(parse("Type unquoted").is_err());
+    }
+
+    #[test]
+    fn replays_demo_tape() {
+        let controller = replay(include_str!("../jotty.tape"), date(), 80, 12).unwrap();
+        assert_snapshot!(screen(controller.view().terminal().backend()));
+
+        let model = controller.view().model();
+        assert_eq!(
+            model
+                .events_iter(date())
+                .map(|x| Event {
+                    uid: String::new(),
+                    ..x
+                })
+                .collect::<Vec<_>>(),
+            [Event {
+                title: "day 3 of conference".to_string(),
+                importance: Importance::High,
+                ..Default::default()
+            }]
+        );
+        assert_eq!(
+            model
+                .tasks_iter(date())
+                .map(|x| Task {
+                    uid: String::new(),
+                    ..x
+                })
+                .collect::<Vec<_>>(),
+            [
+                Task {
+                    title: "cook breakfast".to_string(),
+                    completion_level: CompletionLevel::Full,
+                    ..Default::default()
+                },
+                Task {
+                    title: "change the world".to_string(),
+                    ..Default::default()
+                },
+                Task {
+                    title: "enjoy today".to_string(),
+                    completion_level: CompletionLevel::Partial,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_after_quitting() {
+        let controller = replay("Type \"q\"\nType \"t\"", date(), 40, 8).unwrap();
+        assert_eq!(controller.view().model().tasks_len(date()), 0);
+    }
+}
@@ -1,7 +1,8 @@
+use std::collections::{BTreeSet, HashSet};
 use std::io::Result;
 
 use anyhow::Error;
-use ratatui::DefaultTerminal;
+use ratatui::Terminal;
 use ratatui::layout::Position;
 use ratatui::prelude::*;
 use ratatui::text::Span;
@@ -17,25 +18,173 @@ use ratatui::{
 };
 use time::{Date, OffsetDateTime};
 
-use crate::model::Model;
-use crate::model::{CompletionLevel, Event, Importance, Task};
+use crate::clipboard;
+use crate::editor::{self, Editor};
+use crate::interchange::markdown::{self, Day};
+use crate::model::recurrence::split_recurrence;
+use crate::model::{CachedModel, Model};
+use crate::model::{
+    CompletionLevel, Entry, Event, Importance, Mood, Recurrence, Task, Template, is_tag_char,
+    subtasks,
+};
+use crate::pages::PageTemplate;
+
+mod agenda;
+mod details;
+mod due;
+mod habits;
+mod heatmap;
+mod journal;
+mod moods;
+mod recurring;
+mod search;
+mod stats;
+mod tags;
+#[cfg(test)]
+mod tests;
+mod trash;
 
-pub struct View {
-    terminal: DefaultTerminal,
-    model: Box<dyn Model>,
+use agenda::Agenda;
+use details::EntryDetails;
+use due::DueTasks;
+use habits::HabitGrid;
+use heatmap::Heatmap;
+use journal::JournalPane;
+use moods::MoodTrend;
+use recurring::RecurringList;
+use search::Search;
+use stats::Stats;
+use tags::TagBrowser;
+use trash::TrashList;
+
+pub struct View<B: Backend> {
+    terminal: Terminal<B>,
+    model: CachedModel,
     date: Date,
+    today: Date,
     editing: Option<usize>,
+    /// The title being edited, with an event's times or a task's due date in
+    /// it, which is only written to the entry when editing stops.
+    draft: String,
     bg_message: Option<String>,
-    help_menu: Option<ListState>,
+    screen: Option<Screen>,
+    /// Whether the selected entry's history is shown over the day's page.
+    details: bool,
+    /// Whether keys typed go to the day's reflection.
+    reflecting: bool,
     events_state: ListState,
     task_state: ListState,
+    pages: Vec<PageTemplate>,
+    /// The indices of the current day's tasks whose subtasks are hidden.
+    collapsed: HashSet<usize>,
+    /// Entries picked up to be put on another day, in the order they were on
+    /// their own day.
+    moving: Vec<Moving>,
+    marks: Marks,
+    /// Entries yanked to be pasted, with tasks' depths relative to the first.
+    register: Vec<Entry>,
+    clipboard: bool,
+    editor: Option<Editor>,
+    /// Why the last action failed, shown in place of the instructions until
+    /// the next frame.
+    notice: Option<String>,
+}
+
+/// An entry picked up to be put on another day, with where it was picked up.
+struct Moving {
+    date: Date,
+    index: usize,
+    entry: Entry,
+}
+
+/// Rows of the current day marked with `s`. While any are marked, cycling,
+/// importance, deleting, moving and yanking act on them instead of the
+/// selected entry.
+#[derive(Default)]
+struct Marks {
+    events: BTreeSet<usize>,
+    tasks: BTreeSet<usize>,
+}
+
+impl Marks {
+    fn len(&self) -> usize {
+        self.events.len() + self.tasks.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn clear(&mut self) {
+        self.events.clear();
+        self.tasks.clear();
+    }
+}
+
+/// A screen shown in place of the day's events and tasks.
+enum Screen {
+    Help(ListState),
+    Tags(TagBrowser),
+    Agenda(Agenda),
+    Due(DueTasks),
+    Recurring(RecurringList),
+    Trash(TrashList),
+    Stats(Stats),
+    Heatmap(Heatmap),
+    Habits(HabitGrid),
+    Moods(MoodTrend),
+    Journal(JournalPane),
+    Search(Search),
 }
 
-impl View {
-    pub fn new(model: Box<dyn Model>, terminal: DefaultTerminal) -> Self {
-        let date = OffsetDateTime::now_local()
-            .unwrap_or(OffsetDateTime::now_utc())
-            .date();
+const HELP: [(&str, &str); 41] = [
+    ("q", "quit jotty"),
+    ("h", "toggle this help menu"),
+    ("e", "append a new event"),
+    ("t", "append a new task"),
+    ("n", "insert a new entry above the selected entry"),
+    ("' '", "cycle the selected entry"),
+    ("!", "toggle the importance of the selected task"),
+    ("d", "move an entry to the trash"),
+    ("TAB", "nest the selected task under the one above"),
+    ("SHIFT + TAB", "move the selected task out a level"),
+    ("z", "fold or unfold the selected task's subtasks"),
+    ("i", "show the selected entry's history"),
+    ("ALT + ARROW", "move the selected entry up or down"),
+    (">", "send the selected entry to tomorrow"),
+    ("m", "pick up an entry to put on another day"),
+    ("s", "mark an entry for m, d, >, y, ' ' or !"),
+    ("y", "yank the selected entry"),
+    ("v", "paste after the selected entry"),
+    ("V", "paste before the selected entry"),
+    ("ENTER", "toggle editing mode for the selected entry"),
+    ("ARROW", "move the cursor"),
+    ("SHIFT + ARROW", "move between days"),
+    ("c", "jump to today's page"),
+    ("#", "browse entries by tag"),
+    ("a", "toggle the day's agenda"),
+    ("o", "list overdue tasks and tasks due soon"),
+    ("r", "list recurring entries"),
+    ("u", "list deleted entries to restore them"),
+    ("S", "show stats for the days up to this one"),
+    ("Y", "show a year of activity up to this day"),
+    ("x", "show the month's habits"),
+    ("1-9", "check off a habit for the day"),
+    ("M", "rate the day's mood"),
+    ("E", "rate the day's energy"),
+    ("R", "write a line of reflection on the day"),
+    ("T", "show the month's moods"),
+    ("j", "write in the day's journal"),
+    ("O", "open the day's journal in $EDITOR"),
+    ("w", "edit the selected entry's title in $EDITOR"),
+    ("/", "search entries and journals"),
+    ("p", "add the day's page template"),
+];
+
+impl<B: Backend> View<B> {
+    pub fn new(model: Box<dyn Model>, terminal: Terminal<B>) -> Self {
+        let model = CachedModel::new(model);
+        let date = today();
 
         let mut events_state = ListState::default();
         let mut task_state = ListState::default();
@@ -49,20 +198,65 @@ impl View {
             terminal,
             model,
             date,
+            today: date,
             bg_message: None,
-            help_menu: None,
+            screen: None,
+            details: false,
+            reflecting: false,
             editing: None,
+            draft: String::new(),
             events_state,
             task_state,
+            pages: Vec::new(),
+            collapsed: HashSet::new(),
+            moving: Vec::new(),
+            marks: Marks::default(),
+            register: Vec::new(),
+            clipboard: false,
+            editor: None,
+            notice: None,
         }
     }
 
+    /// Sets the page templates that fill empty days from today on.
+    pub fn page_templates(mut self, pages: Vec<PageTemplate>) -> Self {
+        self.pages = pages;
+        self.move_to(self.date);
+        self
+    }
+
     pub fn background_text(mut self, str: String) -> Self {
         self.bg_message = Some(str);
         self
     }
 
+    /// Also copies yanked entries to the system clipboard, as Markdown.
+    pub fn system_clipboard(mut self, enabled: bool) -> Self {
+        self.clipboard = enabled;
+        self
+    }
+
+    /// Lets text be written in an external editor, like the user's `$EDITOR`.
+    pub fn external_editor(mut self, editor: Editor) -> Self {
+        self.editor = Some(editor);
+        self
+    }
+
+    pub fn on_date(mut self, date: Date) -> Self {
+        self.move_to(date);
+        self
+    }
+
+    pub fn terminal(&self) -> &Terminal<B> {
+        &self.terminal
+    }
+
+    pub fn model(&self) -> &dyn Model {
+        &self.model
+    }
+
     pub fn render(&mut self) -> Result<()> {
+        self.model.refresh();
         if let Err(e) = self.model.err() {
             self.render_err(&e)
         } else {
@@ -71,25 +265,110 @@ impl View {
     }
 
     fn render_default(&mut self) -> Result<()> {
+        let mut tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+        let editing_event = self.editing.and(self.events_state.selected());
+        if self.editing.is_some()
+            && let Some(task) = self.task_state.selected().and_then(|x| tasks.get_mut(x))
+        {
+            task.title = self.draft.clone();
+            task.due = None;
+        }
+        let hidden = hidden_tasks(&tasks, &self.collapsed);
+        let mood = self.model.mood(self.date);
+        let habits: Vec<(String, bool)> = self.model.habits_on(self.date);
+        let details = self
+            .details
+            .then(|| {
+                EntryDetails::new(
+                    &self.model,
+                    self.date,
+                    self.events_state.selected(),
+                    self.task_state.selected(),
+                )
+            })
+            .flatten();
+        let journal = matches!(self.screen, Some(Screen::Journal(_)))
+            .then(|| self.model.journal(self.date))
+            .unwrap_or_default();
+        let notice = self.notice.take();
         self.terminal.draw(|frame| {
             let [_top, middle, _bottom] =
                 Layout::vertical([Constraint::Max(1), Constraint::Min(1), Constraint::Max(1)])
                     .flex(Flex::Center)
                     .areas(frame.area());
 
-            let title = Line::from(vec![
+            let mut title = Line::from(vec![
                 "Jotty".green().bold(),
                 " entry on ".bold(),
                 self.date.to_string().blue().bold(),
             ]);
-            let instructions = Line::from("<q> to quit; <h> for help".gray());
+            title.spans.extend(format_mood(&mood));
+            let instructions = match self.moving.as_slice() {
+                _ if self.reflecting => {
+                    Line::from(vec!["reflection: ".gray(), mood.reflection.clone().into()])
+                }
+                _ if notice.is_some() => Line::from(notice.clone().unwrap_or_default().red()),
+                [] if !self.marks.is_empty() => Line::from(vec![
+                    self.marks.len().to_string().magenta(),
+                    " marked; <ESC> to clear".gray(),
+                ]),
+                [] => Line::from("<q> to quit; <h> for help".gray()),
+                [moving] => Line::from(vec![
+                    "moving ".gray(),
+                    moving.entry.title().to_string().magenta(),
+                    "; <m> to put it on this day, <ESC> to cancel".gray(),
+                ]),
+                moving => Line::from(vec![
+                    "moving ".gray(),
+                    format!("{} entries", moving.len()).magenta(),
+                    "; <m> to put them on this day, <ESC> to cancel".gray(),
+                ]),
+            };
+            let instructions_width = instructions.width() as u16;
             let container_block = Block::new()
                 .title(title.centered())
                 .title_bottom(instructions.centered());
 
             frame.render_widget(container_block, frame.area());
-            if let Some(ls) = &mut self.help_menu {
-                View::render_help_frame(frame, middle, ls);
+            if self.reflecting {
+                let area = frame.area();
+                frame.set_cursor_position(Position::new(
+                    area.x + area.width.saturating_sub(instructions_width) / 2 + instructions_width,
+                    area.bottom().saturating_sub(1),
+                ));
+            }
+            let middle = if self.screen.is_none() && !habits.is_empty() {
+                let [habits_area, middle] =
+                    Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(middle);
+                frame.render_widget(format_habits(&habits).centered(), habits_area);
+                middle
+            } else {
+                middle
+            };
+            if let Some(Screen::Help(ls)) = &mut self.screen {
+                Self::render_help_frame(frame, middle, ls);
+            } else if let Some(Screen::Tags(browser)) = &mut self.screen {
+                browser.render(frame, middle, self.today);
+            } else if let Some(Screen::Agenda(agenda)) = &mut self.screen {
+                agenda.render(frame, middle, &self.model, self.date);
+            } else if let Some(Screen::Due(due)) = &mut self.screen {
+                due.render(frame, middle);
+            } else if let Some(Screen::Recurring(list)) = &mut self.screen {
+                list.render(frame, middle, self.today);
+            } else if let Some(Screen::Trash(list)) = &mut self.screen {
+                list.render(frame, middle, self.today);
+            } else if let Some(Screen::Stats(stats)) = &self.screen {
+                stats.render(frame, middle);
+            } else if let Some(Screen::Heatmap(heatmap)) = &self.screen {
+                heatmap.render(frame, middle);
+            } else if let Some(Screen::Habits(grid)) = &self.screen {
+                grid.render(frame, middle);
+            } else if let Some(Screen::Moods(trend)) = &self.screen {
+                trend.render(frame, middle);
+            } else if let Some(Screen::Journal(pane)) = &mut self.screen {
+                pane.render(frame, middle, &journal);
+            } else if let Some(Screen::Search(search)) = &mut self.screen {
+                search.render(frame, middle, self.today);
             } else if self.model.events_len(self.date) != 0 || self.model.tasks_len(self.date) != 0
             {
                 let [events_rect, tasks_rect] =
@@ -103,7 +382,22 @@ impl View {
                 let events_widget = self
                     .model
                     .events_iter(self.date)
-                    .map(|x| ListItem::new(format_events(x)))
+                    .enumerate()
+                    .map(|(idx, x)| {
+                        // the title being edited has the event's times in it
+                        let x = if editing_event == Some(idx) {
+                            Event {
+                                title: self.draft.clone(),
+                                start: None,
+                                end: None,
+                                ..x
+                            }
+                        } else {
+                            x
+                        };
+                        ListItem::new(format_events(x))
+                            .style(marked_style(self.marks.events.contains(&idx)))
+                    })
                     .collect::<List>()
                     .block(events_block)
                     .highlight_style(Style::new().fg(Color::Red));
@@ -112,10 +406,10 @@ impl View {
                 let task_block = Block::bordered()
                     .title(task_title.centered())
                     .border_set(border::ROUNDED);
-                let task_widget = self
-                    .model
-                    .tasks_iter(self.date)
-                    .map(|x| ListItem::new(format_tasks(x)))
+                let task_widget = format_outline(&tasks, &hidden, &self.collapsed, self.today)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, x)| x.style(marked_style(self.marks.tasks.contains(&idx))))
                     .collect::<List>()
                     .block(task_block)
                     .highlight_style(Style::new().fg(Color::Yellow));
@@ -141,13 +435,18 @@ impl View {
                             events_rect.y + 1 + selected as u16 - height_offset as u16,
                         )
                     } else {
+                        let indent = 2 * tasks[selected].depth as u16;
+                        let rows = hidden[height_offset..selected].iter().filter(|x| !**x);
                         Position::new(
-                            tasks_rect.x + 4 + str_offset as u16,
-                            tasks_rect.y + 1 + selected as u16 - height_offset as u16,
+                            tasks_rect.x + 4 + indent + str_offset as u16,
+                            tasks_rect.y + 1 + rows.count() as u16,
                         )
                     };
                     frame.set_cursor_position(position);
                 }
+                if let Some(details) = &details {
+                    details.render(frame, middle);
+                }
             } else {
                 let [bg_text_area] =
                     Layout::vertical([Constraint::Length(if self.bg_message.is_none() {
@@ -174,50 +473,20 @@ impl View {
     }
 
     fn render_help_frame(frame: &mut Frame, area: Rect, ls: &mut ListState) {
-        let [help_area] = Layout::vertical([Constraint::Length(12)])
+        let [help_area] = Layout::vertical([Constraint::Length(HELP.len() as u16 + 1)])
             .flex(Flex::Center)
             .areas(area);
         let [key_area, value_area] =
             Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
                 .areas(help_area);
-        let key_list = List::from_iter(
-            [
-                "q",
-                "h",
-                "e",
-                "t",
-                "n",
-                "\' \'",
-                "d",
-                "ENTER",
-                "ARROW",
-                "SHIFT + ARROW",
-                "c",
-            ]
-            .map(|x| {
-                Line::from(format!("{x} : "))
-                    .bold()
-                    .alignment(Alignment::Right)
-            }),
-        )
+        let key_list = List::from_iter(HELP.map(|(x, _)| {
+            Line::from(format!("{x} : "))
+                .bold()
+                .alignment(Alignment::Right)
+        }))
         .highlight_style(Style::new().fg(Color::Green));
-        let value_list = List::from_iter(
-            [
-                "quit jotty",
-                "toggle this help menu",
-                "append a new event",
-                "append a new task",
-                "insert a new entry above the selected entry",
-                "cycle the selected entry",
-                "delete an entry",
-                "toggle editing mode for the selected entry",
-                "move the cursor",
-                "move between days",
-                "jump to today's page",
-            ]
-            .map(Line::from),
-        )
-        .highlight_style(Style::new().fg(Color::Blue));
+        let value_list = List::from_iter(HELP.map(|(_, x)| Line::from(x)))
+            .highlight_style(Style::new().fg(Color::Blue));
 
         frame.render_stateful_widget(key_list, key_area, ls);
         frame.render_stateful_widget(value_list, value_area, ls);
@@ -242,33 +511,85 @@ impl View {
 
     pub fn move_up(&mut self) {
         if self.model.err().is_ok() {
-            self.editing = None;
-            if let Some(ls) = &mut self.help_menu {
+            self.stop_editing();
+            if let Some(Screen::Help(ls)) = &mut self.screen {
                 ls.select_previous();
+            } else if let Some(Screen::Tags(browser)) = &mut self.screen {
+                browser.move_up(&self.model);
+            } else if let Some(Screen::Agenda(agenda)) = &mut self.screen {
+                agenda.move_up();
+            } else if let Some(Screen::Due(due)) = &mut self.screen {
+                due.move_up();
+            } else if let Some(Screen::Recurring(list)) = &mut self.screen {
+                list.move_up();
+            } else if let Some(Screen::Trash(list)) = &mut self.screen {
+                list.move_up();
+            } else if let Some(Screen::Heatmap(heatmap)) = &mut self.screen {
+                heatmap.move_up();
+            } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+                grid.move_up();
+            } else if let Some(Screen::Journal(pane)) = &mut self.screen {
+                pane.move_up();
+            } else if let Some(Screen::Search(search)) = &mut self.screen {
+                search.move_up();
             } else if self.events_state.selected().is_some() {
                 self.events_state.select_previous();
-            } else if self.task_state.selected().is_some() {
-                self.task_state.select_previous();
+            } else if let Some(idx) = self.task_state.selected() {
+                let hidden = self.hidden_tasks();
+                if let Some(prev) = (0..idx.min(hidden.len())).rev().find(|x| !hidden[*x]) {
+                    self.task_state.select(Some(prev));
+                }
             }
         }
     }
 
     pub fn move_down(&mut self) {
         if self.model.err().is_ok() {
-            self.editing = None;
-            if let Some(ls) = &mut self.help_menu {
+            self.stop_editing();
+            if let Some(Screen::Help(ls)) = &mut self.screen {
                 ls.select_next();
+            } else if let Some(Screen::Tags(browser)) = &mut self.screen {
+                browser.move_down(&self.model);
+            } else if let Some(Screen::Agenda(agenda)) = &mut self.screen {
+                agenda.move_down();
+            } else if let Some(Screen::Due(due)) = &mut self.screen {
+                due.move_down();
+            } else if let Some(Screen::Recurring(list)) = &mut self.screen {
+                list.move_down();
+            } else if let Some(Screen::Trash(list)) = &mut self.screen {
+                list.move_down();
+            } else if let Some(Screen::Heatmap(heatmap)) = &mut self.screen {
+                heatmap.move_down();
+            } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+                grid.move_down();
+            } else if let Some(Screen::Journal(pane)) = &mut self.screen {
+                pane.move_down();
+            } else if let Some(Screen::Search(search)) = &mut self.screen {
+                search.move_down();
             } else if self.events_state.selected().is_some() {
                 self.events_state.select_next();
-            } else if self.task_state.selected().is_some() {
-                self.task_state.select_next();
+            } else if let Some(idx) = self.task_state.selected() {
+                let hidden = self.hidden_tasks();
+                if let Some(next) = (idx + 1..hidden.len()).find(|x| !hidden[*x]) {
+                    self.task_state.select(Some(next));
+                }
             }
         }
     }
 
     pub fn move_left(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
-            self.editing = None;
+        if let Some(Screen::Tags(browser)) = &mut self.screen {
+            browser.move_left();
+        } else if let Some(Screen::Stats(stats)) = &mut self.screen {
+            stats.shorten(&self.model);
+        } else if let Some(Screen::Heatmap(heatmap)) = &mut self.screen {
+            heatmap.move_left();
+        } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+            grid.move_left();
+        } else if let Some(Screen::Moods(trend)) = &mut self.screen {
+            trend.move_left(&self.model);
+        } else if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
             if self.task_state.selected().is_some() && self.model.events_len(self.date) > 0 {
                 self.events_state.select(self.task_state.selected());
                 self.task_state.select(None);
@@ -277,19 +598,53 @@ impl View {
     }
 
     pub fn move_right(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
-            self.editing = None;
-            if self.events_state.selected().is_some() && self.model.tasks_len(self.date) > 0 {
-                self.task_state.select(self.events_state.selected());
+        if let Some(Screen::Tags(browser)) = &mut self.screen {
+            browser.move_right();
+        } else if let Some(Screen::Stats(stats)) = &mut self.screen {
+            stats.lengthen(&self.model);
+        } else if let Some(Screen::Heatmap(heatmap)) = &mut self.screen {
+            heatmap.move_right();
+        } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+            grid.move_right();
+        } else if let Some(Screen::Moods(trend)) = &mut self.screen {
+            trend.move_right(&self.model);
+        } else if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            if let Some(idx) = self.events_state.selected()
+                && self.model.tasks_len(self.date) > 0
+            {
+                let hidden = self.hidden_tasks();
+                let idx = (0..=idx.min(hidden.len() - 1))
+                    .rev()
+                    .find(|x| !hidden[*x])
+                    .unwrap_or(0);
+                self.task_state.select(Some(idx));
                 self.events_state.select(None);
             }
         }
     }
 
     pub fn cycle(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
-            self.editing = None;
-            if let Some(idx) = self.task_state.selected() {
+        if let Some(Screen::Habits(grid)) = &mut self.screen {
+            if self.model.err().is_ok() {
+                grid.toggle(&mut self.model);
+            }
+        } else if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            if !self.marks.is_empty() {
+                self.model.begin();
+                for &idx in &self.marks.events {
+                    if let Ok(event) = self.model.get_event(self.date, idx) {
+                        let _ = self.model.replace_event(self.date, idx, event.cycle());
+                    }
+                }
+                for &idx in &self.marks.tasks {
+                    if let Ok(task) = self.model.get_task(self.date, idx) {
+                        let _ = self.model.replace_task(self.date, idx, task.cycle());
+                    }
+                }
+                let _ = self.model.commit();
+            } else if let Some(idx) = self.task_state.selected() {
                 let cycled_task = self
                     .model
                     .get_task(self.date, idx)
@@ -311,14 +666,85 @@ impl View {
         }
     }
 
+    /// Toggles the importance of the selected task. Marked entries, events
+    /// included, are all made important, or all made normal if they already are.
+    pub fn cycle_importance(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            if !self.marks.is_empty() {
+                self.set_marked_importance();
+            } else if let Some(idx) = self.task_state.selected() {
+                let cycled_task = self
+                    .model
+                    .get_task(self.date, idx)
+                    .expect("selected cannot be out of bounds")
+                    .cycle_importance();
+                self.model
+                    .replace_task(self.date, idx, cycled_task)
+                    .expect("selected cannot be out of bounds");
+            }
+        }
+    }
+
+    fn set_marked_importance(&mut self) {
+        let events: Vec<(usize, Event)> = self
+            .marks
+            .events
+            .iter()
+            .filter_map(|&x| Some((x, self.model.get_event(self.date, x).ok()?)))
+            .collect();
+        let tasks: Vec<(usize, Task)> = self
+            .marks
+            .tasks
+            .iter()
+            .filter_map(|&x| Some((x, self.model.get_task(self.date, x).ok()?)))
+            .collect();
+        let importance = if events.iter().all(|(_, x)| x.importance == Importance::High)
+            && tasks.iter().all(|(_, x)| x.importance == Importance::High)
+        {
+            Importance::Normal
+        } else {
+            Importance::High
+        };
+
+        self.model.begin();
+        for (idx, event) in events {
+            let importance = importance.clone();
+            let _ = self.model.replace_event(
+                self.date,
+                idx,
+                Event {
+                    importance,
+                    ..event
+                },
+            );
+        }
+        for (idx, task) in tasks {
+            let importance = importance.clone();
+            let _ = self
+                .model
+                .replace_task(self.date, idx, Task { importance, ..task });
+        }
+        let _ = self.model.commit();
+    }
+
+    /// Whether the day can be changed with the current screen open, because
+    /// the screen shows the day.
+    fn follows_date(&self) -> bool {
+        matches!(
+            self.screen,
+            None | Some(Screen::Agenda(_) | Screen::Journal(_))
+        )
+    }
+
     pub fn move_to_next(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
+        if self.model.err().is_ok() && self.follows_date() {
             self.move_to(self.date.next_day().expect("we will never reach max date"));
         }
     }
 
     pub fn move_to_prev(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
+        if self.model.err().is_ok() && self.follows_date() {
             self.move_to(
                 self.date
                     .previous_day()
@@ -328,18 +754,34 @@ impl View {
     }
 
     pub fn move_to_today(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
-            self.move_to(
-                OffsetDateTime::now_local()
-                    .unwrap_or(OffsetDateTime::now_utc())
-                    .date(),
-            );
+        if self.model.err().is_ok() && self.follows_date() {
+            self.today = today();
+            self.move_to(self.today);
         }
     }
 
     fn move_to(&mut self, date: Date) {
-        self.editing = None;
+        self.stop_editing();
+        if let Some(Screen::Journal(pane)) = &mut self.screen {
+            pane.stop_writing(&mut self.model, self.date);
+            *pane = JournalPane::default();
+        }
+        if date != self.date {
+            self.collapsed.clear();
+            self.marks.clear();
+        }
         self.date = date;
+        if date >= self.today
+            && self.pages.iter().any(|x| x.matches(date))
+            && !self.model.filled(date)
+        {
+            // a day is only considered once, so that one emptied on purpose
+            // is not filled again
+            if self.model.events_len(date) == 0 && self.model.tasks_len(date) == 0 {
+                self.apply_pages(date);
+            }
+            let _ = self.model.set_filled(date);
+        }
         if self.task_state.selected().is_some() && self.model.tasks_len(date) == 0 {
             if self.model.events_len(date) > 0 {
                 self.events_state.select(self.task_state.selected());
@@ -350,7 +792,7 @@ impl View {
                 self.task_state.select(self.events_state.selected());
             }
             self.events_state.select(None);
-        } else if self.task_state.selected().is_none() && self.task_state.selected().is_none() {
+        } else if self.events_state.selected().is_none() && self.task_state.selected().is_none() {
             if self.model.events_len(date) > 0 {
                 self.events_state.select(Some(0));
             } else if self.model.tasks_len(date) > 0 {
@@ -359,37 +801,189 @@ impl View {
         }
     }
 
+    /// Adds the entries of every page template matching the current day,
+    /// even if the day already has entries.
+    pub fn apply_page_templates(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.apply_pages(self.date);
+            let _ = self.model.set_filled(self.date);
+            self.move_to(self.date);
+        }
+    }
+
+    fn apply_pages(&mut self, date: Date) {
+        for page in self.pages.iter().filter(|x| x.matches(date)) {
+            if page.apply(&mut self.model, date).is_err() {
+                break;
+            }
+        }
+    }
+
     pub fn toggle_editing_mode(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
-            if self.editing.is_none()
-                && let Some(editing_str) = self.get_editing_string()
-            {
-                self.editing = Some(editing_str.len());
+        if self.model.err().is_ok() && self.screen.is_none() {
+            if self.editing.is_none() {
+                // editing an event can reorder the day's events
+                self.marks.clear();
+                self.start_editing();
             } else {
-                self.editing = None;
+                self.stop_editing();
             }
         }
     }
 
     pub fn exit_mode(&mut self) {
         if self.model.err().is_ok() {
-            if self.help_menu.is_some() {
-                self.help_menu = None;
+            if self.reflecting {
+                self.reflecting = false;
+            } else if let Some(Screen::Habits(grid)) = &mut self.screen
+                && grid.is_naming()
+            {
+                grid.cancel_naming();
+            } else if let Some(Screen::Journal(pane)) = &mut self.screen
+                && pane.is_writing()
+            {
+                pane.stop_writing(&mut self.model, self.date);
+            } else if self.screen.is_some() {
+                self.screen = None;
+            } else if self.details {
+                self.details = false;
+            } else if self.editing.is_some() {
+                self.stop_editing();
+            } else if !self.moving.is_empty() {
+                self.moving.clear();
             } else {
-                self.editing = None;
+                self.marks.clear();
+            }
+        }
+    }
+
+    /// Starts editing the selected entry's title, with an event's times at its
+    /// start and a task's due date at its end, so they can be edited as text.
+    fn start_editing(&mut self) {
+        let title = if let Some(idx) = self.events_state.selected() {
+            self.model
+                .get_event(self.date, idx)
+                .expect("selected cannot be out of bounds")
+                .put_times_in_title()
+                .title
+        } else if let Some(idx) = self.task_state.selected() {
+            self.model
+                .get_task(self.date, idx)
+                .expect("selected cannot be out of bounds")
+                .put_due_in_title()
+                .title
+        } else {
+            return;
+        };
+        self.editing = Some(title.len());
+        self.draft = title;
+    }
+
+    /// Leaves editing mode, writing the edited title if it changed the entry.
+    /// An edited event's leading time is moved out of its title, and the
+    /// day's events are put back in chronological order. An edited task's
+    /// trailing due date is moved out of its title. An entry whose title ends
+    /// in a recurrence, like `every:weekday`, also becomes a template that
+    /// repeats it from this day on.
+    fn stop_editing(&mut self) {
+        if self.editing.take().is_none() {
+            return;
+        }
+        let draft = std::mem::take(&mut self.draft);
+        if let Some(idx) = self.task_state.selected() {
+            let task = self
+                .model
+                .get_task(self.date, idx)
+                .expect("selected cannot be out of bounds");
+            let mut edited = Task {
+                title: draft,
+                ..task.clone().put_due_in_title()
+            };
+            let recurrence = self.take_recurrence(&mut edited.title);
+            let edited = edited.take_due_from_title();
+            if let Some(recurrence) = recurrence {
+                let entry = Entry::Task(Task {
+                    title: edited.title.clone(),
+                    importance: edited.importance.clone(),
+                    ..Default::default()
+                });
+                self.add_template(Template { recurrence, entry });
+            }
+            if edited != task {
+                self.model
+                    .replace_task(self.date, idx, edited)
+                    .expect("selected cannot be out of bounds");
+            }
+        } else if let Some(idx) = self.events_state.selected() {
+            let event = self
+                .model
+                .get_event(self.date, idx)
+                .expect("selected cannot be out of bounds");
+            let mut edited = Event {
+                title: draft,
+                ..event.clone().put_times_in_title()
+            };
+            let recurrence = self.take_recurrence(&mut edited.title);
+            let edited = edited.take_times_from_title();
+            if let Some(recurrence) = recurrence {
+                let entry = Entry::Event(edited.clone()).without_uid();
+                self.add_template(Template { recurrence, entry });
             }
+            if edited != event {
+                self.model
+                    .replace_event(self.date, idx, edited)
+                    .expect("selected cannot be out of bounds");
+                self.sort_events(idx);
+            }
+        }
+    }
+
+    /// Splits a recurrence off the end of a title. The recurrence starts on
+    /// this day, but leaves it out, since the entry being edited is this day's.
+    fn take_recurrence(&self, title: &mut String) -> Option<Recurrence> {
+        let (rest, recurrence) = split_recurrence(title, self.date);
+        let mut recurrence = recurrence?;
+        recurrence.except.insert(self.date);
+        *title = rest.to_string();
+        Some(recurrence)
+    }
+
+    fn add_template(&mut self, template: Template) {
+        // a failure to save sets the model's error, which is shown on the next render
+        let _ = self.model.add_template(template);
+    }
+
+    /// Orders the day's events with all-day events first, as they were, then
+    /// timed events by start and end. Keeps the event at `idx` selected.
+    fn sort_events(&mut self, idx: usize) {
+        let events: Vec<Event> = self.model.events_iter(self.date).collect();
+        let mut order: Vec<usize> = (0..events.len()).collect();
+        order.sort_by_key(|&i| (events[i].start.is_some(), events[i].start, events[i].end));
+        if order.iter().enumerate().all(|(i, &j)| i == j) {
+            return;
+        }
+        for (i, &j) in order.iter().enumerate() {
+            self.model
+                .replace_event(self.date, i, events[j].clone())
+                .expect("i is within the day's events");
         }
+        self.events_state
+            .select(order.iter().position(|&j| j == idx));
     }
 
     pub fn move_cursor_left(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
+        if let Some(Screen::Journal(pane)) = &mut self.screen {
+            pane.move_cursor_left();
+        } else if self.model.err().is_ok() && self.screen.is_none() {
             self.editing = self.editing.map(|x| if x > 0 { x - 1 } else { x });
         }
     }
 
     pub fn move_cursor_right(&mut self) {
-        if self.model.err().is_ok()
-            && self.help_menu.is_none()
+        if let Some(Screen::Journal(pane)) = &mut self.screen {
+            pane.move_cursor_right();
+        } else if self.model.err().is_ok()
+            && self.screen.is_none()
             && let Some(len) = self.get_editing_string().map(|x| x.len())
         {
             self.editing = self.editing.map(|x| if x < len { x + 1 } else { x });
@@ -397,8 +991,18 @@ impl View {
     }
 
     pub fn insert_char(&mut self, c: char) {
-        if self.model.err().is_ok()
-            && self.help_menu.is_none()
+        if self.reflecting {
+            let mut mood = self.model.mood(self.date);
+            mood.reflection.push(c);
+            let _ = self.model.set_mood(self.date, mood);
+        } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+            grid.insert_char(c);
+        } else if let Some(Screen::Journal(pane)) = &mut self.screen {
+            pane.insert_char(c);
+        } else if let Some(Screen::Search(search)) = &mut self.screen {
+            search.insert_char(c);
+        } else if self.model.err().is_ok()
+            && self.screen.is_none()
             && let Some(idx) = self.editing
         {
             let mut new_str = self
@@ -412,8 +1016,18 @@ impl View {
     }
 
     pub fn delete_char(&mut self) {
-        if self.model.err().is_ok()
-            && self.help_menu.is_none()
+        if self.reflecting {
+            let mut mood = self.model.mood(self.date);
+            mood.reflection.pop();
+            let _ = self.model.set_mood(self.date, mood);
+        } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+            grid.delete_char();
+        } else if let Some(Screen::Journal(pane)) = &mut self.screen {
+            pane.delete_char();
+        } else if let Some(Screen::Search(search)) = &mut self.screen {
+            search.delete_char();
+        } else if self.model.err().is_ok()
+            && self.screen.is_none()
             && let Some(editing) = self.editing
             && let Some(str) = self.get_editing_string()
             && editing > 0
@@ -426,49 +1040,84 @@ impl View {
     }
 
     pub fn append_new_event(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.marks.clear();
             let idx = self.model.events_len(self.date);
             self.model
                 .new_event(self.date, idx)
                 .expect("idx was set based on length");
             self.events_state.selected_mut().replace(idx);
             self.task_state.selected_mut().take();
-            self.editing = Some(0);
+            self.start_editing();
         }
     }
 
     pub fn append_new_task(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.marks.clear();
             let idx = self.model.tasks_len(self.date);
             self.model
                 .new_task(self.date, idx)
                 .expect("idx was set based on length");
             self.task_state.selected_mut().replace(idx);
             self.events_state.selected_mut().take();
-            self.editing = Some(0);
+            self.start_editing();
         }
     }
 
     pub fn insert_new_item(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
+        if let Some(Screen::Habits(grid)) = &mut self.screen {
+            grid.start_naming();
+        } else if self.model.err().is_ok() && self.screen.is_none() {
+            self.marks.clear();
             if let Some(idx) = self.events_state.selected() {
                 self.model
                     .new_event(self.date, idx)
                     .expect("idx was set based on selected");
-                self.editing = Some(0);
+                self.start_editing();
             } else if let Some(idx) = self.task_state.selected() {
+                let depth = self
+                    .model
+                    .get_task(self.date, idx)
+                    .expect("selected cannot be out of bounds")
+                    .depth;
                 self.model
                     .new_task(self.date, idx)
                     .expect("idx was set based on selected");
-                self.editing = Some(0);
+                self.model
+                    .replace_task(
+                        self.date,
+                        idx,
+                        Task {
+                            depth,
+                            ..Default::default()
+                        },
+                    )
+                    .expect("idx was set based on selected");
+                self.shift_collapsed(idx, true);
+                self.start_editing();
             }
         }
     }
 
     pub fn delete(&mut self) {
-        if self.model.err().is_ok() && self.help_menu.is_none() {
-            self.editing = None;
-            if let Some(idx) = self.events_state.selected() {
+        if let Some(Screen::Recurring(list)) = &mut self.screen {
+            if self.model.err().is_ok() {
+                list.delete(&mut self.model);
+            }
+        } else if let Some(Screen::Trash(list)) = &mut self.screen {
+            if self.model.err().is_ok() {
+                list.purge(&mut self.model);
+            }
+        } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+            if self.model.err().is_ok() {
+                grid.remove(&mut self.model);
+            }
+        } else if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            if !self.marks.is_empty() {
+                self.delete_marked();
+            } else if let Some(idx) = self.events_state.selected() {
                 self.model
                     .delete_event(self.date, idx)
                     .expect("the item is selected");
@@ -479,9 +1128,11 @@ impl View {
                     }
                 }
             } else if let Some(idx) = self.task_state.selected() {
+                self.shift_subtasks(idx, false);
                 self.model
                     .delete_task(self.date, idx)
                     .expect("the item is selected");
+                self.shift_collapsed(idx, false);
                 if self.model.tasks_len(self.date) == 0 {
                     self.task_state.select(None);
                     if self.model.events_len(self.date) > 0 {
@@ -492,80 +1143,1034 @@ impl View {
         }
     }
 
-    pub fn is_editing(&self) -> bool {
-        if self.model.err().is_err() {
-            return false;
+    /// Deletes the marked entries. Subtasks of a deleted task that are not
+    /// marked move out a level, as with a single delete.
+    fn delete_marked(&mut self) {
+        let marks = std::mem::take(&mut self.marks);
+        self.model.begin();
+        for &idx in marks.events.iter().rev() {
+            let _ = self.model.delete_event(self.date, idx);
         }
-        self.editing.is_some()
+        for &idx in marks.tasks.iter().rev() {
+            if idx < self.model.tasks_len(self.date) {
+                self.shift_subtasks(idx, false);
+                let _ = self.model.delete_task(self.date, idx);
+                self.shift_collapsed(idx, false);
+            }
+        }
+        let _ = self.model.commit();
+        self.clamp_selection();
     }
 
-    fn get_editing_string(&mut self) -> Option<String> {
-        if let Some(row_idx) = self.events_state.selected() {
-            return Some(
+    /// Keeps the selection on an entry after entries are taken off the day.
+    fn clamp_selection(&mut self) {
+        let events = self.model.events_len(self.date);
+        if let Some(idx) = self.events_state.selected() {
+            self.events_state
+                .select(Some(idx.min(events.saturating_sub(1))));
+        }
+        let tasks = self.model.tasks_len(self.date);
+        if let Some(idx) = self.task_state.selected() {
+            self.task_state
+                .select(Some(idx.min(tasks.saturating_sub(1))));
+        }
+        self.move_to(self.date);
+    }
+
+    /// Swaps the selected entry with the one above it. A task swaps, with its
+    /// subtasks, with the task above it at the same depth, if there is one.
+    pub fn shift_up(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            self.marks.clear();
+            if let Some(idx) = self.events_state.selected()
+                && idx > 0
+                && idx < self.model.events_len(self.date)
+            {
                 self.model
-                    .get_event(self.date, row_idx)
-                    .expect("selected cannot be out of range")
-                    .title,
-            );
-        } else if let Some(row_idx) = self.task_state.selected() {
-            return Some(
+                    .move_event(self.date, idx, self.date, idx - 1)
+                    .expect("selected cannot be out of bounds");
+                self.events_state.select(Some(idx - 1));
+            } else if let Some(idx) = self.task_state.selected() {
+                let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+                let end = subtasks(&tasks, idx).end;
+                if let Some(prev) = tasks[..idx]
+                    .iter()
+                    .rposition(|x| x.depth <= tasks[idx].depth)
+                    && tasks[prev].depth == tasks[idx].depth
+                {
+                    self.swap_tasks(prev, idx - prev, end - idx);
+                    self.task_state.select(Some(prev));
+                }
+            }
+        }
+    }
+
+    /// Swaps the selected entry with the one below it. A task swaps, with its
+    /// subtasks, with the task below it at the same depth, if there is one.
+    pub fn shift_down(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            self.marks.clear();
+            if let Some(idx) = self.events_state.selected()
+                && idx + 1 < self.model.events_len(self.date)
+            {
                 self.model
-                    .get_task(self.date, row_idx)
-                    .expect("selected cannot be out of range")
-                    .title,
-            );
+                    .move_event(self.date, idx, self.date, idx + 1)
+                    .expect("selected cannot be out of bounds");
+                self.events_state.select(Some(idx + 1));
+            } else if let Some(idx) = self.task_state.selected() {
+                let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+                let next = subtasks(&tasks, idx).end;
+                if next < tasks.len() && tasks[next].depth == tasks[idx].depth {
+                    let end = subtasks(&tasks, next).end;
+                    self.swap_tasks(idx, next - idx, end - next);
+                    self.task_state.select(Some(idx + end - next));
+                }
+            }
         }
-        None
     }
 
-    fn update_editing_string(&mut self, string: String) {
-        if let Some(idx) = self.task_state.selected() {
-            let mut new_task = self
+    /// Moves the `second` tasks after the `first` tasks from `start` to
+    /// before them.
+    fn swap_tasks(&mut self, start: usize, first: usize, second: usize) {
+        self.model.begin();
+        for i in 0..second {
+            let moved = self
                 .model
-                .get_task(self.date, idx)
-                .expect("selected cannot be out of bounds");
-            new_task.title = string;
+                .move_task(self.date, start + first + i, self.date, start + i);
+            if moved.is_err() {
+                break;
+            }
+        }
+        let _ = self.model.commit();
+        self.collapsed = self
+            .collapsed
+            .iter()
+            .map(|x| match x.checked_sub(start) {
+                Some(offset) if offset < first => x + second,
+                Some(offset) if offset < first + second => x - first,
+                _ => *x,
+            })
+            .collect();
+    }
+
+    /// Moves the selected or marked entries, with any subtasks, to the end of
+    /// tomorrow's entries.
+    pub fn send_to_tomorrow(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            let tomorrow = self.date.next_day().expect("we will never reach max date");
+            self.model.begin();
+            if !self.marks.is_empty() {
+                let moving = self.take_marked();
+                self.put_down(moving, tomorrow);
+                self.clamp_selection();
+            } else if let Some(idx) = self.events_state.selected() {
+                self.move_event_to(self.date, idx, tomorrow);
+                let len = self.model.events_len(self.date);
+                self.events_state
+                    .select(Some(idx.min(len.saturating_sub(1))));
+            } else if let Some(idx) = self.task_state.selected() {
+                self.move_task_to(self.date, idx, tomorrow);
+                let len = self.model.tasks_len(self.date);
+                self.task_state.select(Some(idx.min(len.saturating_sub(1))));
+            }
+            let _ = self.model.commit();
+            self.move_to(self.date);
+        }
+    }
+
+    /// Picks up the selected or marked entries, or puts down the entries that
+    /// were picked up at the end of the current day's entries.
+    pub fn toggle_moving(&mut self) {
+        if self.model.err().is_err() || self.screen.is_some() {
+            return;
+        }
+        self.stop_editing();
+        if !self.moving.is_empty() {
+            let moving = std::mem::take(&mut self.moving);
+            self.model.begin();
+            let (event, task) = self.put_down(moving, self.date);
+            let _ = self.model.commit();
+            if let Some(idx) = event {
+                self.task_state.select(None);
+                self.events_state.select(Some(idx));
+            } else if let Some(idx) = task {
+                self.events_state.select(None);
+                self.task_state.select(Some(idx));
+            }
+        } else if !self.marks.is_empty() {
+            self.moving = self.take_marked();
+        } else if let Some(index) = self.events_state.selected() {
+            self.moving = self
+                .model
+                .get_event(self.date, index)
+                .ok()
+                .map(|x| Moving {
+                    date: self.date,
+                    index,
+                    entry: Entry::Event(x),
+                })
+                .into_iter()
+                .collect();
+        } else if let Some(index) = self.task_state.selected() {
+            self.moving = self
+                .model
+                .get_task(self.date, index)
+                .ok()
+                .map(|x| Moving {
+                    date: self.date,
+                    index,
+                    entry: Entry::Task(x),
+                })
+                .into_iter()
+                .collect();
+        }
+    }
+
+    /// Clears the marks, returning the marked events and the marked tasks
+    /// that are not subtasks of other marked tasks, ready to be moved.
+    fn take_marked(&mut self) -> Vec<Moving> {
+        let marks = std::mem::take(&mut self.marks);
+        let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+        let events = marks.events.into_iter().filter_map(|index| {
+            let event = self.model.get_event(self.date, index).ok()?;
+            Some(Moving {
+                date: self.date,
+                index,
+                entry: Entry::Event(event),
+            })
+        });
+        let events: Vec<Moving> = events.collect();
+        let tasks = marked_roots(&tasks, &marks.tasks)
+            .into_iter()
+            .map(|index| Moving {
+                date: self.date,
+                index,
+                entry: Entry::Task(tasks[index].clone()),
+            });
+        events.into_iter().chain(tasks).collect()
+    }
+
+    /// Appends picked up entries to `to`'s entries, returning the new indices
+    /// of the first event and the first task moved. An entry is only moved if
+    /// it is still where it was picked up.
+    fn put_down(&mut self, moving: Vec<Moving>, to: Date) -> (Option<usize>, Option<usize>) {
+        // entries were picked up in order, so earlier ones moving off the
+        // same day shift the later ones up
+        let (mut events_moved, mut tasks_moved) = (0, 0);
+        let (mut first_event, mut first_task) = (None, None);
+        for Moving { date, index, entry } in moving {
+            match entry {
+                Entry::Event(event) => {
+                    let index = index - events_moved;
+                    if self.model.get_event(date, index).as_ref().ok() == Some(&event)
+                        && let Some(idx) = self.move_event_to(date, index, to)
+                    {
+                        events_moved += 1;
+                        first_event.get_or_insert(idx);
+                    }
+                }
+                Entry::Task(task) => {
+                    let index = index - tasks_moved;
+                    let len = self.model.tasks_len(date);
+                    if self.model.get_task(date, index).as_ref().ok() == Some(&task)
+                        && let Some(idx) = self.move_task_to(date, index, to)
+                    {
+                        tasks_moved += len - self.model.tasks_len(date);
+                        first_task.get_or_insert(idx);
+                    }
+                }
+            }
+        }
+        (first_event, first_task)
+    }
+
+    /// Appends an event to another day's events, returning its new index.
+    fn move_event_to(&mut self, from: Date, idx: usize, to: Date) -> Option<usize> {
+        if from == to {
+            return None;
+        }
+        let len = self.model.events_len(to);
+        self.model.move_event(from, idx, to, len).ok()?;
+        Some(len)
+    }
+
+    /// Appends a task and its subtasks to another day's tasks, as a top level
+    /// task, returning its new index.
+    fn move_task_to(&mut self, from: Date, idx: usize, to: Date) -> Option<usize> {
+        if from == to {
+            return None;
+        }
+        let tasks: Vec<Task> = self.model.tasks_iter(from).collect();
+        let moved = &tasks[idx..subtasks(&tasks, idx).end];
+        let len = self.model.tasks_len(to);
+        for (i, task) in moved.iter().enumerate() {
+            self.model.move_task(from, idx, to, len + i).ok()?;
+            let depth = task.depth - moved[0].depth;
             self.model
-                .replace_task(self.date, idx, new_task)
-                .expect("selected cannot be out of bounds");
-        } else if let Some(idx) = self.events_state.selected() {
-            let mut new_event = self
+                .replace_task(
+                    to,
+                    len + i,
+                    Task {
+                        depth,
+                        ..task.clone()
+                    },
+                )
+                .ok()?;
+        }
+        if from == self.date {
+            for _ in moved {
+                self.shift_collapsed(idx, false);
+            }
+        }
+        Some(len)
+    }
+
+    /// Copies the selected or marked entries, with any subtasks, into the
+    /// register.
+    pub fn yank(&mut self) {
+        if self.model.err().is_err() || self.screen.is_some() {
+            return;
+        }
+        self.stop_editing();
+        if !self.marks.is_empty() {
+            let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+            let events = self.marks.events.iter();
+            let events = events.filter_map(|x| self.model.get_event(self.date, *x).ok());
+            self.register = events.map(Entry::Event).collect();
+            for idx in marked_roots(&tasks, &self.marks.tasks) {
+                let base = tasks[idx].depth;
+                self.register
+                    .extend(tasks[idx..subtasks(&tasks, idx).end].iter().map(|x| {
+                        Entry::Task(Task {
+                            depth: x.depth - base,
+                            ..x.clone()
+                        })
+                    }));
+            }
+            self.marks.clear();
+        } else if let Some(idx) = self.events_state.selected()
+            && let Ok(event) = self.model.get_event(self.date, idx)
+        {
+            self.register = vec![Entry::Event(event)];
+        } else if let Some(idx) = self.task_state.selected()
+            && idx < self.model.tasks_len(self.date)
+        {
+            let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+            let base = tasks[idx].depth;
+            self.register = tasks[idx..subtasks(&tasks, idx).end]
+                .iter()
+                .map(|x| {
+                    Entry::Task(Task {
+                        depth: x.depth - base,
+                        ..x.clone()
+                    })
+                })
+                .collect();
+        } else {
+            return;
+        }
+
+        if self.clipboard {
+            let (events, tasks) = split_entries(self.register.clone());
+            let mut text = Vec::new();
+            let day = Day {
+                date: None,
+                events,
+                tasks,
+                ..Default::default()
+            };
+            if markdown::write_day(&day, &mut text).is_ok() {
+                // terminals without OSC 52 ignore it, so a failure is not worth reporting
+                let _ = clipboard::copy(&String::from_utf8_lossy(&text));
+            }
+        }
+    }
+
+    /// Pastes the register's events and tasks after, or before, the selected
+    /// entry. Entries that are not the kind selected go at the end of their list.
+    pub fn paste(&mut self, after: bool) {
+        if self.model.err().is_err() || self.screen.is_some() || self.register.is_empty() {
+            return;
+        }
+        self.stop_editing();
+        self.marks.clear();
+        let (events, tasks) = split_entries(self.register.clone());
+
+        let at = match self.events_state.selected() {
+            Some(idx) => idx + usize::from(after),
+            None => self.model.events_len(self.date),
+        };
+        for (i, event) in events.iter().enumerate() {
+            let pasted = self
                 .model
-                .get_event(self.date, idx)
+                .new_event(self.date, at + i)
+                .and_then(|()| self.model.replace_event(self.date, at + i, event.clone()));
+            // the day may have been changed by another program since it was read
+            if pasted.is_err() || self.model.err().is_err() {
+                return;
+            }
+        }
+
+        let day: Vec<Task> = self.model.tasks_iter(self.date).collect();
+        let (at, base) = match self.task_state.selected() {
+            Some(idx) if idx < day.len() && after => (subtasks(&day, idx).end, day[idx].depth),
+            Some(idx) if idx < day.len() => (idx, day[idx].depth),
+            _ => (day.len(), 0),
+        };
+        for (i, task) in tasks.iter().enumerate() {
+            let depth = task.depth + base;
+            let pasted = self.model.new_task(self.date, at + i).and_then(|()| {
+                self.model.replace_task(
+                    self.date,
+                    at + i,
+                    Task {
+                        depth,
+                        ..task.clone()
+                    },
+                )
+            });
+            if pasted.is_err() || self.model.err().is_err() {
+                return;
+            }
+            self.shift_collapsed(at + i, true);
+        }
+
+        if let Some(idx) = self.events_state.selected()
+            && !events.is_empty()
+        {
+            self.events_state.select(Some(idx + usize::from(after)));
+        } else if self.task_state.selected().is_some() && !tasks.is_empty() {
+            self.task_state.select(Some(at));
+        }
+        self.move_to(self.date);
+    }
+
+    /// Nests the selected task, with its subtasks, one level deeper, under
+    /// the task above it. On the activity heatmap, switches what it counts.
+    pub fn indent(&mut self) {
+        if let Some(Screen::Heatmap(heatmap)) = &mut self.screen {
+            heatmap.toggle_completed();
+        } else if self.model.err().is_ok()
+            && self.screen.is_none()
+            && let Some(idx) = self.task_state.selected()
+            && idx > 0
+        {
+            let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+            if tasks[idx].depth <= tasks[idx - 1].depth {
+                self.shift_subtasks(idx, true);
+                self.model
+                    .replace_task(
+                        self.date,
+                        idx,
+                        Task {
+                            depth: tasks[idx].depth + 1,
+                            ..tasks[idx].clone()
+                        },
+                    )
+                    .expect("selected cannot be out of bounds");
+            }
+        }
+    }
+
+    /// Moves the selected task, with its subtasks, one level shallower.
+    pub fn outdent(&mut self) {
+        if self.model.err().is_ok()
+            && self.screen.is_none()
+            && let Some(idx) = self.task_state.selected()
+        {
+            let task = self
+                .model
+                .get_task(self.date, idx)
                 .expect("selected cannot be out of bounds");
-            new_event.title = string;
+            if task.depth > 0 {
+                self.shift_subtasks(idx, false);
+                self.model
+                    .replace_task(
+                        self.date,
+                        idx,
+                        Task {
+                            depth: task.depth - 1,
+                            ..task
+                        },
+                    )
+                    .expect("selected cannot be out of bounds");
+            }
+        }
+    }
+
+    /// Marks the selected entry, or unmarks it if it is marked.
+    pub fn toggle_mark(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            let marks = if let Some(idx) = self.events_state.selected() {
+                Some((&mut self.marks.events, idx))
+            } else {
+                self.task_state
+                    .selected()
+                    .map(|x| (&mut self.marks.tasks, x))
+            };
+            if let Some((marks, idx)) = marks
+                && !marks.remove(&idx)
+            {
+                marks.insert(idx);
+            }
+        }
+    }
+
+    /// Hides or shows the subtasks of the selected task.
+    pub fn toggle_collapsed(&mut self) {
+        if self.model.err().is_ok()
+            && self.screen.is_none()
+            && let Some(idx) = self.task_state.selected()
+        {
+            self.stop_editing();
+            let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+            if !self.collapsed.remove(&idx) && !subtasks(&tasks, idx).is_empty() {
+                self.collapsed.insert(idx);
+            }
+        }
+    }
+
+    /// Moves every subtask of `tasks[idx]` one level deeper or shallower.
+    fn shift_subtasks(&mut self, idx: usize, deeper: bool) {
+        let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+        for i in subtasks(&tasks, idx) {
+            let task = tasks[i].clone();
+            let depth = if deeper {
+                task.depth + 1
+            } else {
+                task.depth - 1
+            };
             self.model
-                .replace_event(self.date, idx, new_event)
-                .expect("selected cannot be out of bounds");
+                .replace_task(self.date, i, Task { depth, ..task })
+                .expect("subtasks are in bounds");
+        }
+    }
+
+    /// Keeps collapsed tasks collapsed after a task is inserted or removed at `idx`.
+    fn shift_collapsed(&mut self, idx: usize, inserted: bool) {
+        self.collapsed = self
+            .collapsed
+            .iter()
+            .filter(|x| inserted || **x != idx)
+            .map(|x| match (*x >= idx, inserted) {
+                (true, true) => x + 1,
+                (true, false) => x - 1,
+                (false, _) => *x,
+            })
+            .collect();
+    }
+
+    fn hidden_tasks(&self) -> Vec<bool> {
+        let tasks: Vec<Task> = self.model.tasks_iter(self.date).collect();
+        hidden_tasks(&tasks, &self.collapsed)
+    }
+
+    pub fn is_editing(&self) -> bool {
+        if self.model.err().is_err() {
+            return false;
         }
+        self.editing.is_some()
+            || self.reflecting
+            || matches!(&self.screen, Some(Screen::Habits(grid)) if grid.is_naming())
+            || matches!(&self.screen, Some(Screen::Journal(pane)) if pane.is_writing())
+            || matches!(&self.screen, Some(Screen::Search(search)) if search.is_typing())
+    }
+
+    fn get_editing_string(&self) -> Option<String> {
+        self.editing.map(|_| self.draft.clone())
+    }
+
+    fn update_editing_string(&mut self, string: String) {
+        self.draft = string;
     }
 
     pub fn toggle_help(&mut self) {
-        if self.help_menu.is_some() {
-            self.help_menu = None;
+        if let Some(Screen::Help(_)) = self.screen {
+            self.screen = None;
+        } else {
+            self.stop_editing();
+            self.screen = Some(Screen::Help(ListState::default().with_selected(Some(0))));
+        }
+    }
+
+    pub fn toggle_tags(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Tags(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Tags(TagBrowser::new(&self.model)));
+            }
+        }
+    }
+
+    pub fn toggle_agenda(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Agenda(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Agenda(Agenda::new()));
+            }
+        }
+    }
+
+    pub fn toggle_due(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Due(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Due(DueTasks::new(&self.model, self.today)));
+            }
+        }
+    }
+
+    pub fn toggle_recurring(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Recurring(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Recurring(RecurringList::new(&self.model)));
+            }
+        }
+    }
+
+    pub fn toggle_stats(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Stats(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Stats(Stats::new(&self.model, self.date)));
+            }
+        }
+    }
+
+    pub fn toggle_heatmap(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Heatmap(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Heatmap(Heatmap::new(&self.model, self.date)));
+            }
+        }
+    }
+
+    pub fn toggle_habits(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Habits(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Habits(HabitGrid::new(
+                    &self.model,
+                    self.date,
+                    self.today,
+                )));
+            }
+        }
+    }
+
+    /// Marks the day's `index`th habit as done, or as not done if it was.
+    pub fn toggle_habit(&mut self, index: usize) {
+        if self.model.err().is_ok()
+            && self.screen.is_none()
+            && let Some((name, done)) = self.model.habits_on(self.date).into_iter().nth(index)
+        {
+            self.stop_editing();
+            let _ = self.model.set_habit_done(&name, self.date, !done);
+        }
+    }
+
+    /// Rates the day's mood one higher, going from the highest back to none.
+    pub fn cycle_mood(&mut self) {
+        self.change_mood(|x| x.mood = Mood::cycle_rating(x.mood));
+    }
+
+    /// Rates the day's energy one higher, going from the highest back to none.
+    pub fn cycle_energy(&mut self) {
+        self.change_mood(|x| x.energy = Mood::cycle_rating(x.energy));
+    }
+
+    fn change_mood(&mut self, f: impl FnOnce(&mut Mood)) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            let mut mood = self.model.mood(self.date);
+            f(&mut mood);
+            let _ = self.model.set_mood(self.date, mood);
+        }
+    }
+
+    /// Starts typing a line of reflection on the day, after any already
+    /// written.
+    pub fn reflect(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() {
+            self.stop_editing();
+            self.reflecting = true;
+        }
+    }
+
+    pub fn toggle_moods(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Moods(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Moods(MoodTrend::new(&self.model, self.date)));
+            }
+        }
+    }
+
+    pub fn toggle_journal(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Journal(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Journal(JournalPane::default()));
+            }
+        }
+    }
+
+    /// Opens the search, or goes back to changing the query if it is open.
+    pub fn toggle_search(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Search(search)) = &mut self.screen {
+                search.start_typing();
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Search(Search::new()));
+            }
+        }
+    }
+
+    /// Rewrites the day's journal in the external editor.
+    pub fn open_in_editor(&mut self) {
+        if self.model.err().is_ok()
+            && let Some(Screen::Journal(_)) = self.screen
+        {
+            let text = self.model.journal(self.date);
+            if let Some(edited) = self.edit_externally(&text, ".md") {
+                let _ = self.model.set_journal(self.date, edited.trim_end());
+            }
+        }
+    }
+
+    /// Rewrites the selected entry's title in the external editor. The title
+    /// is given as it is in editing mode, and read back as if it had been
+    /// typed there, with its lines joined by spaces.
+    pub fn edit_in_editor(&mut self) {
+        if self.model.err().is_ok() && self.screen.is_none() && self.editor.is_some() {
+            self.toggle_editing_mode();
+            if let Some(title) = self.get_editing_string()
+                && let Some(edited) = self.edit_externally(&title, ".txt")
+            {
+                let lines: Vec<&str> = edited
+                    .lines()
+                    .map(str::trim)
+                    .filter(|x| !x.is_empty())
+                    .collect();
+                self.update_editing_string(lines.join(" "));
+            }
+            self.stop_editing();
+        }
+    }
+
+    /// Has the external editor change `text`, or says why it could not.
+    fn edit_externally(&mut self, text: &str, suffix: &str) -> Option<String> {
+        let edited = editor::edit(self.editor?, text, suffix);
+        // the editor drew over the whole screen
+        let _ = self.terminal.clear();
+        edited
+            .inspect_err(|e| self.notice = Some(format!("cannot open the editor: {e}")))
+            .ok()
+    }
+
+    /// Shows or hides the selected entry's history over the day's page. It
+    /// follows the selection until hidden.
+    pub fn toggle_details(&mut self) {
+        if self.model.err().is_ok() {
+            self.details = !self.details;
+            if self.details {
+                self.stop_editing();
+                self.screen = None;
+            }
+        }
+    }
+
+    pub fn toggle_trash(&mut self) {
+        if self.model.err().is_ok() {
+            if let Some(Screen::Trash(_)) = self.screen {
+                self.screen = None;
+            } else {
+                self.stop_editing();
+                self.screen = Some(Screen::Trash(TrashList::new(&self.model)));
+            }
+        }
+    }
+
+    /// Acts on the selection: opens the selected tag or jumps to the selected
+    /// entry's day when browsing tags, due tasks or search results, jumps to
+    /// the selected day on the activity heatmap, restores the selected entry
+    /// in the trash, starts a new line in the journal, and otherwise toggles
+    /// editing mode.
+    pub fn select(&mut self) {
+        if self.reflecting {
+            self.reflecting = false;
+        } else if let Some(Screen::Tags(browser)) = &mut self.screen {
+            if self.model.err().is_ok()
+                && let Some(date) = browser.select()
+            {
+                self.screen = None;
+                self.move_to(date);
+            }
+        } else if let Some(Screen::Due(due)) = &self.screen {
+            if self.model.err().is_ok()
+                && let Some(date) = due.select()
+            {
+                self.screen = None;
+                self.move_to(date);
+            }
+        } else if let Some(Screen::Habits(grid)) = &mut self.screen {
+            if self.model.err().is_ok() {
+                grid.finish_naming(&mut self.model);
+            }
+        } else if let Some(Screen::Journal(pane)) = &mut self.screen {
+            if self.model.err().is_ok() {
+                if pane.is_writing() {
+                    pane.insert_char('\n');
+                } else {
+                    pane.start_writing(&self.model.journal(self.date));
+                }
+            }
+        } else if let Some(Screen::Search(search)) = &mut self.screen {
+            if self.model.err().is_ok()
+                && let Some((date, in_journal)) = search.select(&self.model)
+            {
+                self.screen = in_journal.then(|| Screen::Journal(JournalPane::default()));
+                self.move_to(date);
+            }
+        } else if let Some(Screen::Heatmap(heatmap)) = &self.screen {
+            if self.model.err().is_ok() {
+                let date = heatmap.selected();
+                self.screen = None;
+                self.move_to(date);
+            }
+        } else if let Some(Screen::Trash(list)) = &mut self.screen {
+            if self.model.err().is_ok() && list.restore(&mut self.model) == Some(self.date) {
+                // the restored entry shifts the day's later entries down
+                self.collapsed.clear();
+                self.marks.clear();
+                self.move_to(self.date);
+            }
         } else {
-            self.help_menu = Some(ListState::default().with_selected(Some(0)));
+            self.toggle_editing_mode();
         }
     }
 }
 
-fn format_tasks(task: Task) -> String {
-    match task.completion_level {
-        CompletionLevel::None => {
-            format!(" ○ {}", task.title)
+/// Formats a task, with its due date in red if it is open and due before `today`.
+fn format_tasks(task: Task, today: Date) -> Line<'static> {
+    let mut spans = vec![Span::from(format!(" {} ", task.completion_level.symbol()))];
+    let style = match task.importance {
+        Importance::Normal => Style::new(),
+        Importance::High => Style::new().bold(),
+    };
+    spans.extend(highlight_tags(task.title.clone(), style));
+    if let Some(due) = task.due {
+        let span = Span::from(format!(" due {due}"));
+        spans.push(if task.is_open() && due < today {
+            span.red()
+        } else {
+            span.dark_gray()
+        });
+    }
+    Line::from(spans)
+}
+
+fn marked_style(marked: bool) -> Style {
+    if marked {
+        Style::new().bg(Color::DarkGray)
+    } else {
+        Style::new()
+    }
+}
+
+/// The marked tasks that are not subtasks of another marked task. Each one
+/// stands for itself and its subtasks when tasks are moved or yanked.
+fn marked_roots(tasks: &[Task], marked: &BTreeSet<usize>) -> Vec<usize> {
+    let mut roots = Vec::new();
+    let mut end = 0;
+    for &idx in marked.iter().filter(|x| **x < tasks.len()) {
+        if idx >= end {
+            roots.push(idx);
+            end = subtasks(tasks, idx).end;
         }
-        CompletionLevel::Partial => {
-            format!(" ◐ {}", task.title)
+    }
+    roots
+}
+
+/// Splits copied entries into events and tasks, without their UIDs.
+fn split_entries(entries: Vec<Entry>) -> (Vec<Event>, Vec<Task>) {
+    let mut events = Vec::new();
+    let mut tasks = Vec::new();
+    for entry in entries {
+        match entry.without_uid() {
+            Entry::Event(event) => events.push(event),
+            Entry::Task(task) => tasks.push(task),
         }
-        CompletionLevel::Full => {
-            format!(" ● {}", task.title)
+    }
+    (events, tasks)
+}
+
+/// Whether each task is hidden inside a collapsed task.
+fn hidden_tasks(tasks: &[Task], collapsed: &HashSet<usize>) -> Vec<bool> {
+    let mut hidden = vec![false; tasks.len()];
+    for idx in collapsed.iter().filter(|x| **x < tasks.len()) {
+        for i in subtasks(tasks, *idx) {
+            hidden[i] = true;
         }
     }
+    hidden
+}
+
+/// Formats the day's tasks as an outline. Subtasks are indented under their
+/// parents, which show a fold marker and how many of their subtasks are
+/// done. Hidden subtasks take up no rows.
+fn format_outline(
+    tasks: &[Task],
+    hidden: &[bool],
+    collapsed: &HashSet<usize>,
+    today: Date,
+) -> Vec<ListItem<'static>> {
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(idx, task)| {
+            if hidden[idx] {
+                return ListItem::new(Text::default());
+            }
+            let mut line = format_tasks(task.clone(), today);
+            let children = &tasks[subtasks(tasks, idx)];
+            let marker = match (children.is_empty(), collapsed.contains(&idx)) {
+                (true, _) => ' ',
+                (false, false) => '▾',
+                (false, true) => '▸',
+            };
+            let indent = "  ".repeat(task.depth.into());
+            line.spans[0] = Span::from(format!(
+                "{indent}{marker}{} ",
+                task.completion_level.symbol()
+            ));
+            if !children.is_empty() {
+                let done = children
+                    .iter()
+                    .filter(|x| x.completion_level == CompletionLevel::Full)
+                    .count();
+                line.spans
+                    .push(format!(" {done}/{}", children.len()).dark_gray());
+            }
+            ListItem::new(line)
+        })
+        .collect()
 }
 
-fn format_events(event: Event) -> Span<'static> {
-    match event.importance {
-        Importance::Normal => Span::from(event.title),
-        Importance::High => event.title.bold(),
+/// The day's ratings and reflection, to follow the date in the title.
+fn format_mood(mood: &Mood) -> Vec<Span<'static>> {
+    /// The most of a reflection shown, in characters.
+    const REFLECTION_WIDTH: usize = 30;
+
+    let mut spans = Vec::new();
+    for (name, rating) in [(" mood ", mood.mood), (" energy ", mood.energy)] {
+        if let Some(rating) = rating {
+            spans.push(Span::from(" ·").dark_gray());
+            spans.push(Span::from(name));
+            spans.push(Span::from(format!("{rating}/{}", Mood::MAX_RATING)).magenta());
+        }
+    }
+    if !mood.reflection.is_empty() {
+        let mut reflection: String = mood.reflection.chars().take(REFLECTION_WIDTH).collect();
+        if mood.reflection.chars().count() > REFLECTION_WIDTH {
+            reflection.pop();
+            reflection.push('…');
+        }
+        spans.push(Span::from(" · ").dark_gray());
+        spans.push(Span::from(reflection).italic());
+    }
+    spans
+}
+
+/// A checkbox for each habit, numbered by the key that checks it off.
+fn format_habits(habits: &[(String, bool)]) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (i, (name, done)) in habits.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::from("  "));
+        }
+        spans.push(Span::from(format!("{} ", i + 1)).dark_gray());
+        spans.push(if *done {
+            Span::from("[x] ").green()
+        } else {
+            Span::from("[ ] ")
+        });
+        spans.push(Span::from(name.clone()));
+    }
+    Line::from(spans)
+}
+
+fn today() -> Date {
+    OffsetDateTime::now_local()
+        .unwrap_or(OffsetDateTime::now_utc())
+        .date()
+}
+
+fn format_events(event: Event) -> Line<'static> {
+    let style = match event.importance {
+        Importance::Normal => Style::new(),
+        Importance::High => Style::new().bold(),
+    };
+    let mut spans = Vec::new();
+    if let Some(times) = event.times() {
+        spans.push(Span::from(format!("{times} ")).green());
+    }
+    spans.extend(highlight_tags(event.title, style));
+    Line::from(spans)
+}
+
+/// Splits a title into spans so that its `#tags` stand out from the rest.
+fn highlight_tags(title: String, style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = title.as_str();
+    while let Some(start) = rest
+        .char_indices()
+        .find(|(i, c)| {
+            *c == '#'
+                && rest[..*i]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+                && rest[i + 1..].starts_with(is_tag_char)
+        })
+        .map(|(i, _)| i)
+    {
+        let len = rest[start + 1..]
+            .find(|c: char| !is_tag_char(c))
+            .map_or(rest.len(), |x| start + 1 + x);
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), style));
+        }
+        spans.push(Span::styled(rest[start..len].to_string(), style.cyan()));
+        rest = &rest[len..];
+    }
+    if !rest.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(rest.to_string(), style));
     }
+    spans
 }
@@ -0,0 +1,92 @@
+use ratatui::prelude::*;
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, List, ListItem, ListState};
+use time::Date;
+
+use super::format_events;
+use crate::model::{Event, Model};
+
+/// The hours shown even when the day has no events outside of them.
+const WORKING_HOURS: (u8, u8) = (8, 18);
+
+/// Lays a day's events out along its hours, with all-day events on top.
+pub struct Agenda {
+    state: ListState,
+}
+
+impl Agenda {
+    pub fn new() -> Self {
+        Self {
+            state: ListState::default(),
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.state.scroll_up_by(1);
+    }
+
+    pub fn move_down(&mut self) {
+        self.state.scroll_down_by(1);
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, model: &dyn Model, date: Date) {
+        let events: Vec<Event> = model.events_iter(date).collect();
+        let mut rows = Vec::new();
+
+        for event in events.iter().filter(|x| x.start.is_none()) {
+            rows.push(row("all day".to_string(), Span::from("┃ ").green(), event));
+        }
+
+        let timed: Vec<&Event> = events.iter().filter(|x| x.start.is_some()).collect();
+        let first = timed
+            .iter()
+            .filter_map(|x| x.start)
+            .map(|x| x.hour())
+            .fold(WORKING_HOURS.0, u8::min);
+        let last = timed
+            .iter()
+            .filter_map(|x| x.end.or(x.start))
+            .map(|x| x.hour())
+            .fold(WORKING_HOURS.1, u8::max);
+        for hour in first..=last {
+            let busy = timed.iter().any(|x| {
+                let start = x.start.map_or(0, |x| x.hour());
+                let end = x.end.map_or(start, |x| {
+                    x.hour().saturating_sub(u8::from(x.minute() == 0))
+                });
+                (start..=end.max(start)).contains(&hour)
+            });
+            let gutter = if busy {
+                Span::from("┃ ").green()
+            } else {
+                Span::from("│ ").dark_gray()
+            };
+            let label = format!("{hour:02}:00");
+            let mut starting = timed
+                .iter()
+                .filter(|x| x.start.is_some_and(|x| x.hour() == hour));
+            match starting.next() {
+                Some(event) => rows.push(row(label, gutter.clone(), event)),
+                None => rows.push(ListItem::new(Line::from(vec![
+                    format!("{label:>7} ").into(),
+                    gutter.clone(),
+                ]))),
+            }
+            for event in starting {
+                rows.push(row(String::new(), gutter.clone(), event));
+            }
+        }
+
+        let block = Block::bordered()
+            .title(Line::from(" Agenda ".green().bold()).centered())
+            .border_set(border::ROUNDED);
+        frame.render_stateful_widget(List::new(rows).block(block), area, &mut self.state);
+    }
+}
+
+fn row(label: String, gutter: Span<'static>, event: &Event) -> ListItem<'static> {
+    let mut line = format_events(event.clone());
+    line.spans
+        .splice(0..0, [format!("{label:>7} ").into(), gutter]);
+    ListItem::new(line)
+}
@@ -0,0 +1,113 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use ratatui::{layout::Flex, symbols::border};
+use time::{Date, OffsetDateTime, UtcOffset};
+
+use crate::model::{Change, ChangeKind, CompletionLevel, Entry, Model};
+
+/// When the selected entry was made, moved and completed, and what it was
+/// called before, shown over the day's page.
+pub struct EntryDetails {
+    lines: Vec<Line<'static>>,
+}
+
+impl EntryDetails {
+    /// The details of the selected event or task, if either is selected.
+    pub fn new(
+        model: &dyn Model,
+        date: Date,
+        event: Option<usize>,
+        task: Option<usize>,
+    ) -> Option<Self> {
+        let (entry, history) = if let Some(idx) = event {
+            (
+                Entry::Event(model.get_event(date, idx).ok()?),
+                model.event_history(date, idx),
+            )
+        } else {
+            let idx = task?;
+            (
+                Entry::Task(model.get_task(date, idx).ok()?),
+                model.task_history(date, idx),
+            )
+        };
+        Some(Self {
+            lines: describe(&entry, &history),
+        })
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let [popup] = Layout::vertical([Constraint::Length(self.lines.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(popup);
+
+        let block = Block::bordered()
+            .title(Line::from(" Details ".cyan().bold()).centered())
+            .title_bottom(Line::from(" <i> to close ".gray()).centered())
+            .border_set(border::ROUNDED);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(self.lines.clone())
+                .block(block)
+                .wrap(Wrap { trim: true }),
+            popup,
+        );
+    }
+}
+
+/// One line per change worth showing, oldest first, with the entry's earlier
+/// titles gathered at the end.
+fn describe(entry: &Entry, history: &[Change]) -> Vec<Line<'static>> {
+    if history.is_empty() {
+        return vec![Line::from("no history is kept for this entry".dark_gray())];
+    }
+
+    let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let mut lines = Vec::new();
+    let mut titles: Vec<&str> = Vec::new();
+    for change in history {
+        let when = format_time(change.time, offset).dark_gray();
+        match &change.kind {
+            ChangeKind::Created => lines.push(Line::from(vec!["created ".into(), when])),
+            ChangeKind::Tracked(title) => {
+                lines.push(Line::from(vec!["created before ".into(), when]));
+                titles.push(title);
+            }
+            ChangeKind::Titled(title) => titles.push(title),
+            ChangeKind::Completed(CompletionLevel::Full) => {
+                lines.push(Line::from(vec!["completed ".into(), when]));
+            }
+            ChangeKind::Completed(_) => {}
+            ChangeKind::Moved(to) => lines.push(Line::from(vec![
+                "moved to ".into(),
+                to.to_string().blue(),
+                " on ".into(),
+                when,
+            ])),
+        }
+    }
+
+    let mut previous: Vec<&str> = Vec::new();
+    for title in titles {
+        if !title.is_empty() && title != entry.title() && !previous.contains(&title) {
+            previous.push(title);
+        }
+    }
+    if !previous.is_empty() {
+        lines.push(Line::from("previously".bold()));
+        lines.extend(
+            previous
+                .into_iter()
+                .map(|title| Line::from(format!("  {title}"))),
+        );
+    }
+    lines
+}
+
+fn format_time(time: OffsetDateTime, offset: UtcOffset) -> String {
+    let time = time.to_offset(offset);
+    format!("{} {:02}:{:02}", time.date(), time.hour(), time.minute())
+}
@@ -0,0 +1,74 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{layout::Flex, symbols::border};
+use time::{Date, Duration};
+
+use super::format_tasks;
+use crate::model::{Model, Task};
+
+/// How far ahead a task counts as due soon.
+const SOON: Duration = Duration::days(7);
+
+/// Lists every open task that is overdue or due soon, across all days.
+pub struct DueTasks {
+    tasks: Vec<(Date, Task)>,
+    state: ListState,
+    today: Date,
+}
+
+impl DueTasks {
+    pub fn new(model: &dyn Model, today: Date) -> Self {
+        let tasks = model.due_tasks(today + SOON);
+        Self {
+            state: ListState::default().with_selected((!tasks.is_empty()).then_some(0)),
+            tasks,
+            today,
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn move_down(&mut self) {
+        self.state.select_next();
+    }
+
+    /// The date the selected task was written on.
+    pub fn select(&self) -> Option<Date> {
+        let idx = self.state.selected()?;
+        self.tasks
+            .get(idx.min(self.tasks.len().saturating_sub(1)))
+            .map(|(date, _)| *date)
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        if self.tasks.is_empty() {
+            let [text_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(area);
+            frame.render_widget(
+                Paragraph::new("nothing is overdue or due in the next week").centered(),
+                text_area,
+            );
+            return;
+        }
+
+        let block = Block::bordered()
+            .title(Line::from(" Overdue / Due soon ".red().bold()).centered())
+            .border_set(border::ROUNDED);
+        let widget = self
+            .tasks
+            .iter()
+            .map(|(date, task)| {
+                let mut line = format_tasks(task.clone(), self.today);
+                line.spans.insert(0, date.to_string().blue());
+                ListItem::new(line)
+            })
+            .collect::<List>()
+            .block(block)
+            .highlight_style(Style::new().fg(Color::Red));
+
+        frame.render_stateful_widget(widget, area, &mut self.state);
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeSet;
+
+use ratatui::layout::{Flex, Position};
+use ratatui::prelude::*;
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Paragraph};
+use time::{Date, Duration};
+
+use crate::model::Model;
+
+/// The widest a habit's name is shown in the grid.
+const NAME_WIDTH: usize = 10;
+
+/// A month of habits, a row to a habit and a column to a day, with how many
+/// days in a row each has been kept up to today.
+pub struct HabitGrid {
+    /// the days each habit was done on, in the order the habits were added
+    habits: Vec<(String, BTreeSet<Date>)>,
+    selected: usize,
+    /// the selected day, which is always in the month shown
+    day: Date,
+    today: Date,
+    /// the name of a habit being added
+    naming: Option<String>,
+}
+
+impl HabitGrid {
+    pub fn new(model: &dyn Model, date: Date, today: Date) -> Self {
+        let mut grid = Self {
+            habits: Vec::new(),
+            selected: 0,
+            day: date,
+            today,
+            naming: None,
+        };
+        grid.reload(model);
+        grid
+    }
+
+    fn reload(&mut self, model: &dyn Model) {
+        self.habits = model
+            .habits()
+            .into_iter()
+            .map(|name| {
+                let days = model.habit_days(&name);
+                (name, days)
+            })
+            .collect();
+        self.selected = self.selected.min(self.habits.len().saturating_sub(1));
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        self.selected = (self.selected + 1).min(self.habits.len().saturating_sub(1));
+    }
+
+    pub fn move_left(&mut self) {
+        if self.day.day() > 1 {
+            self.day -= Duration::days(1);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.day.day() < self.day.month().length(self.day.year()) {
+            self.day += Duration::days(1);
+        }
+    }
+
+    /// Marks the selected habit as done on the selected day, or as not done if
+    /// it was.
+    pub fn toggle(&mut self, model: &mut dyn Model) {
+        if let Some((name, days)) = self.habits.get(self.selected) {
+            let done = days.contains(&self.day);
+            if model.set_habit_done(name, self.day, !done).is_ok() {
+                self.reload(model);
+            }
+        }
+    }
+
+    /// Stops keeping the selected habit.
+    pub fn remove(&mut self, model: &mut dyn Model) {
+        if let Some((name, _)) = self.habits.get(self.selected)
+            && model.remove_habit(name).is_ok()
+        {
+            self.reload(model);
+        }
+    }
+
+    pub fn start_naming(&mut self) {
+        self.naming = Some(String::new());
+    }
+
+    pub fn is_naming(&self) -> bool {
+        self.naming.is_some()
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if let Some(name) = &mut self.naming {
+            name.push(c);
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        if let Some(name) = &mut self.naming {
+            name.pop();
+        }
+    }
+
+    pub fn cancel_naming(&mut self) {
+        self.naming = None;
+    }
+
+    /// Starts keeping the habit being named, and selects it.
+    pub fn finish_naming(&mut self, model: &mut dyn Model) {
+        let Some(name) = self.naming.take() else {
+            return;
+        };
+        let name = name.trim();
+        if !name.is_empty() && model.add_habit(name).is_ok() {
+            self.reload(model);
+            self.selected = self.habits.len() - 1;
+        }
+    }
+
+    /// How many days in a row up to today a habit was done on. Today itself
+    /// does not break the run if it is yet to be done.
+    fn streak(&self, days: &BTreeSet<Date>) -> usize {
+        let mut day = self.today;
+        if !days.contains(&day) {
+            day -= Duration::days(1);
+        }
+        let mut streak = 0;
+        while days.contains(&day) {
+            streak += 1;
+            day -= Duration::days(1);
+        }
+        streak
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let first = self
+            .day
+            .replace_day(1)
+            .expect("every month has a first day");
+        let month: Vec<Date> = (0..self.day.month().length(self.day.year()))
+            .map(|x| first + Duration::days(x.into()))
+            .collect();
+        let width = self
+            .habits
+            .iter()
+            .map(|(name, _)| name.chars().count().min(NAME_WIDTH))
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = Vec::new();
+        if self.habits.is_empty() && self.naming.is_none() {
+            lines.push(Line::from("no habits yet; <n> to add one").centered());
+        } else {
+            let mut header = vec![Span::from(" ".repeat(width + 1))];
+            header.extend(month.iter().map(|x| {
+                let initial = format!("{:?}", x.weekday())[..1].to_string();
+                if *x == self.day {
+                    Span::from(format!("{initial} ")).magenta()
+                } else {
+                    Span::from(format!("{initial} ")).gray()
+                }
+            }));
+            header.push(Span::from("run").gray());
+            lines.push(Line::from(header));
+        }
+        for (i, (name, days)) in self.habits.iter().enumerate() {
+            let name: String = name.chars().take(NAME_WIDTH).collect();
+            let mut spans = vec![if i == self.selected {
+                Span::from(format!("{name:width$} ")).magenta()
+            } else {
+                Span::from(format!("{name:width$} "))
+            }];
+            spans.extend(month.iter().map(|x| {
+                let selected = i == self.selected && *x == self.day;
+                match (days.contains(x), selected) {
+                    (true, false) => Span::from("● ").green(),
+                    (true, true) => Span::from("◉ ").magenta(),
+                    (false, false) => Span::from("· ").dark_gray(),
+                    (false, true) => Span::from("○ ").magenta(),
+                }
+            }));
+            spans.push(Span::from(format!("{:>3}", self.streak(days))).yellow());
+            lines.push(Line::from(spans));
+        }
+        if let Some(name) = &self.naming {
+            lines.push(Line::from(vec!["new habit: ".gray(), name.clone().into()]));
+        }
+
+        let block = Block::bordered()
+            .title(
+                Line::from(
+                    format!(" {} {} ", self.day.month(), self.day.year())
+                        .blue()
+                        .bold(),
+                )
+                .centered(),
+            )
+            .title_bottom(
+                Line::from(" <SPACE> to check, <n> to add, <d> to remove ".gray()).centered(),
+            )
+            .border_set(border::ROUNDED);
+        let [grid_area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let inner = block.inner(grid_area);
+        frame.render_widget(Paragraph::new(lines).block(block), grid_area);
+
+        if let Some(name) = &self.naming {
+            frame.set_cursor_position(Position::new(
+                inner.x + ("new habit: ".len() + name.chars().count()) as u16,
+                inner.y + inner.height.saturating_sub(1),
+            ));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{layout::Flex, symbols::border};
+use time::{Date, Duration};
+
+use crate::model::{Activity, Model};
+
+/// The colors of days with no activity, then of each quarter of the busiest
+/// day's activity.
+const SHADES: [Color; 5] = [
+    Color::DarkGray,
+    Color::Rgb(14, 68, 41),
+    Color::Rgb(0, 109, 50),
+    Color::Rgb(38, 166, 65),
+    Color::Rgb(57, 211, 83),
+];
+
+/// Labels for the rows, which are the days of the week from Monday.
+const WEEKDAYS: [&str; 7] = ["Mon ", "    ", "Wed ", "    ", "Fri ", "    ", "    "];
+
+/// Shades each day of the year up to a date by how much was written or done
+/// on it, a column to a week.
+pub struct Heatmap {
+    /// the Monday of the first column
+    start: Date,
+    end: Date,
+    selected: Date,
+    activity: BTreeMap<Date, Activity>,
+    /// whether days are shaded by completed tasks rather than entries
+    completed: bool,
+}
+
+impl Heatmap {
+    pub fn new(model: &dyn Model, end: Date) -> Self {
+        let start = end - Duration::weeks(52);
+        let start = start - Duration::days(start.weekday().number_days_from_monday().into());
+        Self {
+            start,
+            end,
+            selected: end,
+            activity: model.activity(start, end),
+            completed: false,
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.select(self.selected - Duration::days(1));
+    }
+
+    pub fn move_down(&mut self) {
+        self.select(self.selected + Duration::days(1));
+    }
+
+    pub fn move_left(&mut self) {
+        self.select(self.selected - Duration::weeks(1));
+    }
+
+    pub fn move_right(&mut self) {
+        self.select(self.selected + Duration::weeks(1));
+    }
+
+    fn select(&mut self, date: Date) {
+        self.selected = date.clamp(self.start, self.end);
+    }
+
+    /// The selected day.
+    pub fn selected(&self) -> Date {
+        self.selected
+    }
+
+    /// Switches between shading days by entries and by completed tasks.
+    pub fn toggle_completed(&mut self) {
+        self.completed = !self.completed;
+    }
+
+    fn count(&self, date: Date) -> usize {
+        self.activity.get(&date).map_or(0, |x| {
+            if self.completed {
+                x.completed
+            } else {
+                x.entries
+            }
+        })
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let weeks = (self.end - self.start).whole_weeks() as usize + 1;
+        let most = self
+            .activity
+            .keys()
+            .map(|x| self.count(*x))
+            .max()
+            .unwrap_or(0);
+        let shade = |count: usize| {
+            if count == 0 {
+                SHADES[0]
+            } else {
+                SHADES[(4 * count).div_ceil(most)]
+            }
+        };
+
+        let mut lines = vec![self.months(weeks)];
+        for (weekday, label) in WEEKDAYS.iter().enumerate() {
+            let mut spans = vec![Span::from(*label).gray()];
+            for week in 0..weeks {
+                let date = self.start + Duration::days((7 * week + weekday) as i64);
+                spans.push(if date > self.end {
+                    Span::from(" ")
+                } else if date == self.selected {
+                    Span::from("▣").fg(shade(self.count(date))).bold()
+                } else {
+                    Span::from("■").fg(shade(self.count(date)))
+                });
+            }
+            lines.push(Line::from(spans));
+        }
+
+        let count = self.count(self.selected);
+        let noun = match (self.completed, count) {
+            (true, 1) => "completed task",
+            (true, _) => "completed tasks",
+            (false, 1) => "entry",
+            (false, _) => "entries",
+        };
+        let mut legend = vec![
+            Span::from(format!("{}: {count} {noun}", self.selected)),
+            Span::from("   less ").gray(),
+        ];
+        legend.extend(SHADES.map(|x| Span::from("■").fg(x)));
+        legend.push(Span::from(" more").gray());
+        lines.push(Line::default());
+        lines.push(Line::from(legend));
+
+        let hint = if self.completed {
+            " <ENTER> to open the day, <TAB> to count entries "
+        } else {
+            " <ENTER> to open the day, <TAB> to count completed tasks "
+        };
+        let block = Block::bordered()
+            .title(Line::from(" Activity ".green().bold()).centered())
+            .title_bottom(Line::from(hint.gray()).centered())
+            .border_set(border::ROUNDED);
+
+        let [heatmap_area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [heatmap_area] = Layout::horizontal([Constraint::Length(
+            (WEEKDAYS[0].len() + weeks).max(hint.len()) as u16 + 2,
+        )])
+        .flex(Flex::Center)
+        .areas(heatmap_area);
+        frame.render_widget(Paragraph::new(lines).block(block), heatmap_area);
+    }
+
+    /// Names each month above the week its first day is in, where there is
+    /// room.
+    fn months(&self, weeks: usize) -> Line<'static> {
+        let mut line = " ".repeat(WEEKDAYS[0].len());
+        for week in 0..weeks {
+            let monday = self.start + Duration::weeks(week as i64);
+            let first = (0..7)
+                .map(|x| monday + Duration::days(x))
+                .find(|x| x.day() == 1 && *x <= self.end);
+            let column = WEEKDAYS[0].len() + week;
+            if let Some(first) = first
+                && line.chars().count() <= column
+            {
+                line.push_str(&" ".repeat(column - line.chars().count()));
+                line.push_str(&format!("{:?}", first.month())[..3]);
+            }
+        }
+        Line::from(line).gray()
+    }
+}
@@ -0,0 +1,234 @@
+use std::ops::Range;
+
+use ratatui::layout::{Flex, Position};
+use ratatui::prelude::*;
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Paragraph};
+use time::Date;
+
+use crate::model::Model;
+
+/// A day's journal, wrapped to the width of the pane, which can be written
+/// in place. What is written is kept in the pane and saved when writing
+/// stops, as an entry's title is when editing stops.
+#[derive(Default)]
+pub struct JournalPane {
+    /// where the cursor is in the journal, in bytes, while writing
+    cursor: Option<usize>,
+    /// the journal as it has been written so far
+    draft: String,
+    /// the first row shown
+    scroll: usize,
+    /// the width the journal was last wrapped to
+    width: usize,
+}
+
+impl JournalPane {
+    /// Starts writing at the end of the journal.
+    pub fn start_writing(&mut self, text: &str) {
+        self.draft = text.to_string();
+        self.cursor = Some(text.len());
+    }
+
+    /// Stops writing, saving the journal if it was changed.
+    pub fn stop_writing(&mut self, model: &mut dyn Model, date: Date) {
+        if self.cursor.take().is_some() {
+            let draft = std::mem::take(&mut self.draft);
+            if draft != model.journal(date) {
+                let _ = model.set_journal(date, &draft);
+            }
+        }
+    }
+
+    pub fn is_writing(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if let Some(cursor) = self.cursor {
+            self.draft.insert(cursor, c);
+            self.cursor = Some(cursor + c.len_utf8());
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        if let Some(cursor) = self.cursor
+            && let Some((idx, _)) = self.draft[..cursor].char_indices().next_back()
+        {
+            self.draft.remove(idx);
+            self.cursor = Some(idx);
+        }
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        if let Some(cursor) = self.cursor
+            && let Some((idx, _)) = self.draft[..cursor].char_indices().next_back()
+        {
+            self.cursor = Some(idx);
+        }
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        if let Some(cursor) = self.cursor
+            && let Some(c) = self.draft[cursor..].chars().next()
+        {
+            self.cursor = Some(cursor + c.len_utf8());
+        }
+    }
+
+    /// Moves the cursor up a row while writing, or scrolls up otherwise.
+    pub fn move_up(&mut self) {
+        match self.cursor {
+            Some(cursor) => self.move_rows(cursor, false),
+            None => self.scroll = self.scroll.saturating_sub(1),
+        }
+    }
+
+    /// Moves the cursor down a row while writing, or scrolls down otherwise.
+    pub fn move_down(&mut self) {
+        match self.cursor {
+            Some(cursor) => self.move_rows(cursor, true),
+            None => self.scroll += 1,
+        }
+    }
+
+    /// Moves the cursor to the same column of the row above or below, or to
+    /// the end of that row if it is shorter.
+    fn move_rows(&mut self, cursor: usize, down: bool) {
+        let text = &self.draft;
+        let rows = wrap(text, self.width);
+        let (row, column) = locate(text, &rows, cursor);
+        let row = match down {
+            true if row + 1 < rows.len() => row + 1,
+            false if row > 0 => row - 1,
+            _ => return,
+        };
+        let range = rows[row].clone();
+        // the end of a row that runs on into the next is the next row's start
+        let runs_on = rows.get(row + 1).is_some_and(|x| x.start == range.end);
+        let chars: Vec<usize> = text[range.clone()]
+            .char_indices()
+            .map(|(i, _)| range.start + i)
+            .collect();
+        let end = match runs_on {
+            true => chars.last().copied().unwrap_or(range.start),
+            false => range.end,
+        };
+        self.cursor = Some(chars.get(column).copied().unwrap_or(end));
+    }
+
+    /// Draws the journal as it is being written, or else `text`.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, text: &str) {
+        let draft = self.cursor.map(|_| self.draft.clone());
+        let text = draft.as_deref().unwrap_or(text);
+        let hint = if self.is_writing() {
+            " <ESC> to stop writing "
+        } else {
+            " <ENTER> to write, <O> to open in $EDITOR "
+        };
+        let block = Block::bordered()
+            .title(Line::from(" Journal ".cyan().bold()).centered())
+            .title_bottom(Line::from(hint.gray()).centered())
+            .border_set(border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        self.width = inner.width.into();
+
+        if text.is_empty() && !self.is_writing() {
+            let [text_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(inner);
+            frame.render_widget(
+                Paragraph::new("nothing written on this day yet").centered(),
+                text_area,
+            );
+            return;
+        }
+
+        let height = usize::from(inner.height);
+        let rows = wrap(text, self.width);
+        let cursor = self.cursor.map(|x| locate(text, &rows, x));
+        if let Some((row, _)) = cursor {
+            self.scroll = self.scroll.min(row).max((row + 1).saturating_sub(height));
+        }
+        self.scroll = self.scroll.min(rows.len().saturating_sub(height));
+
+        let lines: Vec<Line> = rows[self.scroll..]
+            .iter()
+            .take(height)
+            .map(|x| Line::from(&text[x.clone()]))
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+        if let Some((row, column)) = cursor {
+            frame.set_cursor_position(Position::new(
+                inner.x + column as u16,
+                inner.y + (row - self.scroll) as u16,
+            ));
+        }
+    }
+}
+
+/// Splits text into rows of at most `width` characters, as byte ranges of the
+/// text. Rows break after a space where they can, and each line of the text
+/// starts a new row.
+fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let mut row_start = line_start;
+        let mut chars = 0;
+        let mut last_break = None;
+        for (i, c) in line.char_indices() {
+            let i = line_start + i;
+            if chars == width {
+                let end = last_break.filter(|x| *x > row_start).unwrap_or(i);
+                rows.push(row_start..end);
+                chars = text[end..i].chars().count();
+                row_start = end;
+                last_break = None;
+            }
+            chars += 1;
+            if c == ' ' {
+                last_break = Some(i + 1);
+            }
+        }
+        rows.push(row_start..line_start + line.len());
+        line_start += line.len() + 1;
+    }
+    rows
+}
+
+/// The row and column that a byte of the text is shown at. A cursor at a
+/// break between rows is at the start of the later one.
+fn locate(text: &str, rows: &[Range<usize>], cursor: usize) -> (usize, usize) {
+    let row = rows
+        .iter()
+        .rposition(|x| x.start <= cursor)
+        .expect("the first row starts the text");
+    let end = cursor.clamp(rows[row].start, rows[row].end);
+    (row, text[rows[row].start..end].chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(text: &str, width: usize) -> Vec<&str> {
+        wrap(text, width).into_iter().map(|x| &text[x]).collect()
+    }
+
+    #[test]
+    fn wraps_at_spaces() {
+        assert_eq!(rows("", 5), [""]);
+        assert_eq!(rows("ab cd ef", 5), ["ab ", "cd ef"]);
+        assert_eq!(rows("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(rows("ab\n\ncdé fg", 4), ["ab", "", "cdé ", "fg"]);
+
+        let text = "ab cd ef";
+        let wrapped = wrap(text, 5);
+        assert_eq!(locate(text, &wrapped, 2), (0, 2));
+        assert_eq!(locate(text, &wrapped, 3), (1, 0));
+        assert_eq!(locate(text, &wrapped, 8), (1, 5));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use ratatui::layout::Flex;
+use ratatui::prelude::*;
+use ratatui::symbols::{Marker, border};
+use ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType, LegendPosition, Paragraph, Wrap};
+use time::Date;
+
+use crate::model::{Model, Mood};
+
+/// A month of moods and energy ratings as lines, with the month's
+/// reflections under them.
+pub struct MoodTrend {
+    /// the first day of the month shown
+    first: Date,
+    moods: BTreeMap<Date, Mood>,
+}
+
+impl MoodTrend {
+    pub fn new(model: &dyn Model, date: Date) -> Self {
+        let first = date.replace_day(1).expect("every month has a first day");
+        Self {
+            first,
+            moods: model.moods(first, last_of_month(first)),
+        }
+    }
+
+    /// Shows the month before.
+    pub fn move_left(&mut self, model: &dyn Model) {
+        let date = self
+            .first
+            .previous_day()
+            .expect("we will never reach minimum date");
+        *self = Self::new(model, date);
+    }
+
+    /// Shows the month after.
+    pub fn move_right(&mut self, model: &dyn Model) {
+        let date = last_of_month(self.first)
+            .next_day()
+            .expect("we will never reach maximum date");
+        *self = Self::new(model, date);
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered()
+            .title(
+                Line::from(
+                    format!(" {} {} ", self.first.month(), self.first.year())
+                        .blue()
+                        .bold(),
+                )
+                .centered(),
+            )
+            .title_bottom(Line::from(" <LEFT> and <RIGHT> to change month ".gray()).centered())
+            .border_set(border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if self.moods.is_empty() {
+            let [text_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(inner);
+            frame.render_widget(
+                Paragraph::new("no moods this month; <M>, <E> and <R> on a day to add one")
+                    .centered(),
+                text_area,
+            );
+            return;
+        }
+
+        let [chart_area, reflections_area] =
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(inner);
+        self.render_chart(frame, chart_area);
+
+        let reflections: Vec<Line> = self
+            .moods
+            .iter()
+            .filter(|(_, x)| !x.reflection.is_empty())
+            .map(|(date, x)| {
+                Line::from(vec![
+                    format!("{:>2} ", date.day()).dark_gray(),
+                    x.reflection.clone().into(),
+                ])
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(reflections).wrap(Wrap { trim: true }),
+            reflections_area,
+        );
+    }
+
+    fn render_chart(&self, frame: &mut Frame, area: Rect) {
+        let points = |rating: fn(&Mood) -> Option<u8>| -> Vec<(f64, f64)> {
+            self.moods
+                .iter()
+                .filter_map(|(date, x)| Some((date.day() as f64, rating(x)? as f64)))
+                .collect()
+        };
+        let moods = points(|x| x.mood);
+        let energy = points(|x| x.energy);
+        let days = last_of_month(self.first).day();
+
+        let datasets = vec![
+            Dataset::default()
+                .name("mood")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .magenta()
+                .data(&moods),
+            Dataset::default()
+                .name("energy")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .cyan()
+                .data(&energy),
+        ];
+        let chart = Chart::new(datasets)
+            .legend_position(Some(LegendPosition::TopLeft))
+            .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 1)))
+            .x_axis(
+                Axis::default()
+                    .bounds([1.0, days as f64])
+                    .labels(["1".to_string(), days.to_string()])
+                    .gray(),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([1.0, Mood::MAX_RATING as f64])
+                    .labels(["1".to_string(), Mood::MAX_RATING.to_string()])
+                    .gray(),
+            );
+        frame.render_widget(chart, area);
+    }
+}
+
+fn last_of_month(first: Date) -> Date {
+    first
+        .replace_day(first.month().length(first.year()))
+        .expect("a month's length is one of its days")
+}
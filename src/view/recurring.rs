@@ -0,0 +1,86 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{layout::Flex, symbols::border};
+use time::Date;
+
+use super::{format_events, format_tasks};
+use crate::model::{Entry, Model, Template};
+
+/// Lists every recurring template, with when it repeats.
+pub struct RecurringList {
+    templates: Vec<Template>,
+    state: ListState,
+}
+
+impl RecurringList {
+    pub fn new(model: &dyn Model) -> Self {
+        let templates = model.templates();
+        Self {
+            state: ListState::default().with_selected((!templates.is_empty()).then_some(0)),
+            templates,
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn move_down(&mut self) {
+        self.state.select_next();
+    }
+
+    /// Stops the selected template from repeating. Entries it has already
+    /// added to the journal are kept.
+    pub fn delete(&mut self, model: &mut dyn Model) {
+        if let Some(idx) = self.state.selected()
+            && idx < self.templates.len()
+            && model.delete_template(idx).is_ok()
+        {
+            self.templates.remove(idx);
+            if self.templates.is_empty() {
+                self.state.select(None);
+            } else if idx == self.templates.len() {
+                self.state.select(Some(idx - 1));
+            }
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, today: Date) {
+        if self.templates.is_empty() {
+            let [text_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(area);
+            frame.render_widget(
+                Paragraph::new("nothing repeats yet; end a title with every:day to start")
+                    .centered(),
+                text_area,
+            );
+            return;
+        }
+
+        let block = Block::bordered()
+            .title(Line::from(" Recurring ".magenta().bold()).centered())
+            .border_set(border::ROUNDED);
+        let widget = self
+            .templates
+            .iter()
+            .map(|template| {
+                let mut line = match template.entry.clone() {
+                    Entry::Event(event) => {
+                        let mut line = format_events(event);
+                        line.spans.insert(0, "   ".into());
+                        line
+                    }
+                    Entry::Task(task) => format_tasks(task, today),
+                };
+                line.spans
+                    .push(format!(" ({})", template.recurrence.describe()).blue());
+                ListItem::new(line)
+            })
+            .collect::<List>()
+            .block(block)
+            .highlight_style(Style::new().fg(Color::Magenta));
+
+        frame.render_stateful_widget(widget, area, &mut self.state);
+    }
+}
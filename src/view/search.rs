@@ -0,0 +1,127 @@
+use ratatui::layout::{Flex, Position};
+use ratatui::prelude::*;
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use time::Date;
+
+use super::{format_events, format_tasks};
+use crate::model::{Entry, Found, Model};
+
+/// Every entry and journal line that contains a query, found once the query
+/// is typed.
+pub struct Search {
+    query: String,
+    /// whether keys typed go to the query
+    typing: bool,
+    results: Vec<(Date, Found)>,
+    state: ListState,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            typing: true,
+            results: Vec::new(),
+            state: ListState::default(),
+        }
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    /// Goes back to changing the query.
+    pub fn start_typing(&mut self) {
+        self.typing = true;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.query.push(c);
+    }
+
+    pub fn delete_char(&mut self) {
+        self.query.pop();
+    }
+
+    pub fn move_up(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn move_down(&mut self) {
+        self.state.select_next();
+    }
+
+    /// Searches for the query, or returns the date of the selected result and
+    /// whether it was found in the day's journal.
+    pub fn select(&mut self, model: &dyn Model) -> Option<(Date, bool)> {
+        if self.typing {
+            self.results = model.search(self.query.trim());
+            self.state =
+                ListState::default().with_selected((!self.results.is_empty()).then_some(0));
+            self.typing = false;
+            return None;
+        }
+        let idx = self.state.selected()?;
+        self.results
+            .get(idx.min(self.results.len().saturating_sub(1)))
+            .map(|(date, found)| (*date, matches!(found, Found::Journal(_))))
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, today: Date) {
+        let hint = if self.typing {
+            " <ENTER> to search "
+        } else {
+            " <ENTER> to open the day, </> to change the search "
+        };
+        let block = Block::bordered()
+            .title(Line::from(" Search ".magenta().bold()).centered())
+            .title_bottom(Line::from(hint.gray()).centered())
+            .border_set(border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [query_area, results_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(inner);
+        frame.render_widget(
+            Paragraph::new(Line::from(vec!["/".gray(), self.query.clone().into()])),
+            query_area,
+        );
+        if self.typing {
+            frame.set_cursor_position(Position::new(
+                query_area.x + 1 + self.query.chars().count() as u16,
+                query_area.y,
+            ));
+        }
+
+        if self.results.is_empty() {
+            if !self.typing {
+                let [text_area] = Layout::vertical([Constraint::Length(1)])
+                    .flex(Flex::Center)
+                    .areas(results_area);
+                frame.render_widget(Paragraph::new("nothing found").centered(), text_area);
+            }
+            return;
+        }
+
+        let results = self
+            .results
+            .iter()
+            .map(|(date, found)| {
+                let mut line = match found.clone() {
+                    Found::Entry(Entry::Event(event)) => {
+                        let mut line = format_events(event);
+                        line.spans.insert(0, "   ".into());
+                        line
+                    }
+                    Found::Entry(Entry::Task(task)) => format_tasks(task, today),
+                    Found::Journal(text) => Line::from(vec![" ¶ ".cyan(), text.italic()]),
+                };
+                line.spans.insert(0, date.to_string().blue());
+                ListItem::new(line)
+            })
+            .collect::<List>()
+            .highlight_style(Style::new().fg(Color::Magenta));
+        frame.render_stateful_widget(results, results_area, &mut self.state);
+    }
+}
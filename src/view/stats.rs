@@ -0,0 +1,305 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use ratatui::prelude::*;
+use ratatui::symbols::{Marker, border};
+use ratatui::widgets::{
+    Axis, Block, Chart, Dataset, GraphType, LegendPosition, Paragraph, Sparkline,
+};
+use time::{Date, Duration};
+
+use crate::model::{CompletionLevel, Model, tags};
+
+/// How many tags are listed, most used first.
+const TOP_TAGS: usize = 3;
+
+/// How far back from the current day the stats look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    fn days(self) -> i64 {
+        match self {
+            Period::Week => 7,
+            Period::Month => 30,
+            Period::Quarter => 91,
+            Period::Year => 365,
+        }
+    }
+
+    fn shorter(self) -> Self {
+        match self {
+            Period::Week | Period::Month => Period::Week,
+            Period::Quarter => Period::Month,
+            Period::Year => Period::Quarter,
+        }
+    }
+
+    fn longer(self) -> Self {
+        match self {
+            Period::Week => Period::Month,
+            Period::Month => Period::Quarter,
+            Period::Quarter | Period::Year => Period::Year,
+        }
+    }
+}
+
+/// Counts of the tasks on one day, or in one week.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counts {
+    created: u64,
+    completed: u64,
+}
+
+/// How tasks were created and completed over the days up to a date. A task
+/// counts as created on the day it is on, and as completed if it is fully
+/// complete.
+pub struct Stats {
+    end: Date,
+    period: Period,
+    days: Vec<Counts>,
+    /// starting on the first day of the range, then on each Monday after
+    weeks: Vec<Counts>,
+    full: usize,
+    partial: usize,
+    open: usize,
+    tags: Vec<(String, usize)>,
+    /// days in a row, up to the last day, on which a task was completed. The
+    /// last day itself may be yet to have one.
+    streak: usize,
+    longest_streak: usize,
+}
+
+impl Stats {
+    /// The stats for the month up to `end`.
+    pub fn new(model: &dyn Model, end: Date) -> Self {
+        Self::over(model, end, Period::Month)
+    }
+
+    fn over(model: &dyn Model, end: Date, period: Period) -> Self {
+        let start = end - Duration::days(period.days() - 1);
+        let mut stats = Self {
+            end,
+            period,
+            days: vec![Counts::default(); period.days() as usize],
+            weeks: Vec::new(),
+            full: 0,
+            partial: 0,
+            open: 0,
+            tags: Vec::new(),
+            streak: 0,
+            longest_streak: 0,
+        };
+
+        let mut tag_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for date in model.dates() {
+            if date < start || date > end {
+                continue;
+            }
+            let day = &mut stats.days[(date - start).whole_days() as usize];
+            for task in model.tasks_iter(date) {
+                day.created += 1;
+                match task.completion_level {
+                    CompletionLevel::Full => {
+                        day.completed += 1;
+                        stats.full += 1;
+                    }
+                    CompletionLevel::Partial => stats.partial += 1,
+                    CompletionLevel::None => stats.open += 1,
+                }
+                for tag in tags(&task.title) {
+                    *tag_counts.entry(tag).or_default() += 1;
+                }
+            }
+            for event in model.events_iter(date) {
+                for tag in tags(&event.title) {
+                    *tag_counts.entry(tag).or_default() += 1;
+                }
+            }
+        }
+
+        let mut tags: Vec<(String, usize)> = tag_counts.into_iter().collect();
+        tags.sort_by_key(|(_, count)| Reverse(*count));
+        tags.truncate(TOP_TAGS);
+        stats.tags = tags;
+
+        for (offset, day) in stats.days.iter().enumerate() {
+            let date = start + Duration::days(offset as i64);
+            if offset == 0 || date.weekday() == time::Weekday::Monday {
+                stats.weeks.push(Counts::default());
+            }
+            let week = stats.weeks.last_mut().expect("a week was just pushed");
+            week.created += day.created;
+            week.completed += day.completed;
+        }
+
+        let mut run = 0;
+        for day in &stats.days {
+            run = if day.completed > 0 { run + 1 } else { 0 };
+            stats.longest_streak = stats.longest_streak.max(run);
+        }
+        let mut days = stats.days.iter().rev().peekable();
+        days.next_if(|x| x.completed == 0);
+        stats.streak = days.take_while(|x| x.completed > 0).count();
+        stats
+    }
+
+    /// Looks back over a shorter range.
+    pub fn shorten(&mut self, model: &dyn Model) {
+        *self = Self::over(model, self.end, self.period.shorter());
+    }
+
+    /// Looks back over a longer range.
+    pub fn lengthen(&mut self, model: &dyn Model) {
+        *self = Self::over(model, self.end, self.period.longer());
+    }
+
+    fn start(&self) -> Date {
+        self.end - Duration::days(self.period.days() - 1)
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered()
+            .title(Line::from(" Stats ".green().bold()).centered())
+            .title_bottom(
+                Line::from(format!(
+                    " {} to {}; <LEFT> and <RIGHT> to change ",
+                    self.start(),
+                    self.end
+                ))
+                .gray()
+                .centered(),
+            )
+            .border_set(border::ROUNDED);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [summary_area, chart_area, weeks_area] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Min(5),
+            Constraint::Length(4),
+        ])
+        .areas(inner);
+
+        let [totals_area, tags_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(summary_area);
+        frame.render_widget(Paragraph::new(self.totals()), totals_area);
+        let mut tag_lines = vec![Line::from("most used tags".bold())];
+        tag_lines.extend(self.tags.iter().map(|(tag, count)| {
+            Line::from(vec![
+                format!("#{tag}").cyan(),
+                format!(" {count}").dark_gray(),
+            ])
+        }));
+        if self.tags.is_empty() {
+            tag_lines.push(Line::from("none".dark_gray()));
+        }
+        frame.render_widget(Paragraph::new(tag_lines), tags_area);
+
+        self.render_chart(frame, chart_area);
+
+        let [created_area, completed_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .spacing(1)
+                .areas(weeks_area);
+        for (area, title, color, data) in [
+            (
+                created_area,
+                "created per week",
+                Color::Yellow,
+                self.weeks.iter().map(|x| x.created).collect::<Vec<_>>(),
+            ),
+            (
+                completed_area,
+                "completed per week",
+                Color::Green,
+                self.weeks.iter().map(|x| x.completed).collect(),
+            ),
+        ] {
+            let sparkline = Sparkline::default()
+                .block(Block::new().title(title))
+                .data(&data)
+                .style(Style::new().fg(color));
+            frame.render_widget(sparkline, area);
+        }
+    }
+
+    fn totals(&self) -> Vec<Line<'static>> {
+        let total = self.full + self.partial + self.open;
+        let rate = (100 * self.full).checked_div(total).unwrap_or(0);
+        vec![
+            Line::from(vec![
+                format!("{}", self.full).green(),
+                format!(" of {total} tasks completed ({rate}%)").into(),
+            ]),
+            Line::from(format!(
+                "{} full, {} partial, {} open",
+                self.full, self.partial, self.open
+            )),
+            Line::from(vec![
+                "streak ".into(),
+                format!("{} days", self.streak).magenta(),
+                format!(", longest {} days", self.longest_streak).into(),
+            ]),
+        ]
+    }
+
+    fn render_chart(&self, frame: &mut Frame, area: Rect) {
+        let created: Vec<(f64, f64)> = self
+            .days
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (i as f64, x.created as f64))
+            .collect();
+        let completed: Vec<(f64, f64)> = self
+            .days
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (i as f64, x.completed as f64))
+            .collect();
+        let most = self
+            .days
+            .iter()
+            .map(|x| x.created)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("created")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .yellow()
+                .data(&created),
+            Dataset::default()
+                .name("completed")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .green()
+                .data(&completed),
+        ];
+        let chart = Chart::new(datasets)
+            .legend_position(Some(LegendPosition::TopLeft))
+            .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 1)))
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, (self.days.len() - 1) as f64])
+                    .labels([self.start().to_string(), self.end.to_string()])
+                    .gray(),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, most as f64])
+                    .labels(["0".to_string(), most.to_string()])
+                    .gray(),
+            );
+        frame.render_widget(chart, area);
+    }
+}
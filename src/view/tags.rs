@@ -0,0 +1,138 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{layout::Flex, symbols::border};
+use time::Date;
+
+use super::{format_events, format_tasks};
+use crate::model::{Entry, Model};
+
+/// Lists every tag in the journal, next to the entries tagged with the
+/// selected tag.
+pub struct TagBrowser {
+    tags: Vec<(String, usize)>,
+    tags_state: ListState,
+    entries: Vec<(Date, Entry)>,
+    entries_state: ListState,
+}
+
+impl TagBrowser {
+    pub fn new(model: &dyn Model) -> Self {
+        let tags = model.tags();
+        let mut browser = Self {
+            tags_state: ListState::default().with_selected((!tags.is_empty()).then_some(0)),
+            tags,
+            entries: Vec::new(),
+            entries_state: ListState::default(),
+        };
+        browser.load_entries(model);
+        browser
+    }
+
+    fn load_entries(&mut self, model: &dyn Model) {
+        self.entries = self
+            .tags_state
+            .selected()
+            .and_then(|x| self.tags.get(x))
+            .map(|(tag, _)| model.tagged(tag))
+            .unwrap_or_default();
+        *self.entries_state.offset_mut() = 0;
+    }
+
+    pub fn move_up(&mut self, model: &dyn Model) {
+        if self.entries_state.selected().is_some() {
+            self.entries_state.select_previous();
+        } else {
+            self.tags_state.select_previous();
+            self.load_entries(model);
+        }
+    }
+
+    pub fn move_down(&mut self, model: &dyn Model) {
+        if self.entries_state.selected().is_some() {
+            self.entries_state.select_next();
+        } else if self
+            .tags_state
+            .selected()
+            .is_some_and(|x| x + 1 < self.tags.len())
+        {
+            self.tags_state.select_next();
+            self.load_entries(model);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.entries_state.select(None);
+    }
+
+    pub fn move_right(&mut self) {
+        if !self.entries.is_empty() && self.entries_state.selected().is_none() {
+            self.entries_state.select(Some(0));
+        }
+    }
+
+    /// Moves into the selected tag's entries, or returns the date of the
+    /// selected entry.
+    pub fn select(&mut self) -> Option<Date> {
+        if let Some(idx) = self.entries_state.selected() {
+            return self
+                .entries
+                .get(idx.min(self.entries.len().saturating_sub(1)))
+                .map(|(date, _)| *date);
+        }
+        self.move_right();
+        None
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, today: Date) {
+        if self.tags.is_empty() {
+            let [text_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(area);
+            frame.render_widget(
+                Paragraph::new("no #tags yet; add one to the title of any entry").centered(),
+                text_area,
+            );
+            return;
+        }
+
+        let [tags_rect, entries_rect] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(area);
+
+        let tags_block = Block::bordered()
+            .title(Line::from(" Tags ".cyan().bold()).centered())
+            .border_set(border::ROUNDED);
+        let tags_widget = self
+            .tags
+            .iter()
+            .map(|(tag, count)| ListItem::new(format!("#{tag} ({count})")))
+            .collect::<List>()
+            .block(tags_block)
+            .highlight_style(Style::new().fg(Color::Cyan));
+
+        let entries_block = Block::bordered()
+            .title(Line::from(" Entries ".magenta().bold()).centered())
+            .border_set(border::ROUNDED);
+        let entries_widget = self
+            .entries
+            .iter()
+            .map(|(date, entry)| {
+                let mut line = match entry.clone() {
+                    Entry::Event(event) => {
+                        let mut line = format_events(event);
+                        line.spans.insert(0, "   ".into());
+                        line
+                    }
+                    Entry::Task(task) => format_tasks(task, today),
+                };
+                line.spans.insert(0, date.to_string().blue());
+                ListItem::new(line)
+            })
+            .collect::<List>()
+            .block(entries_block)
+            .highlight_style(Style::new().fg(Color::Magenta));
+
+        frame.render_stateful_widget(tags_widget, tags_rect, &mut self.tags_state);
+        frame.render_stateful_widget(entries_widget, entries_rect, &mut self.entries_state);
+    }
+}
@@ -0,0 +1,972 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use diesel::RunQueryDsl;
+use insta::assert_snapshot;
+use ratatui::backend::TestBackend;
+use time::{Duration, Month};
+
+use super::*;
+use crate::model::sqlite_model::establish_connection;
+use crate::model::{CompletionLevel, MemModel, RecurringModel, SqliteModel};
+
+fn date() -> Date {
+    Date::from_calendar_date(2025, Month::October, 14).unwrap()
+}
+
+fn view(model: Box<dyn Model>, width: u16, height: u16) -> View<TestBackend> {
+    let terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut view = View::new(model, terminal);
+    view.today = date();
+    view.move_to(date());
+    view
+}
+
+fn journal() -> Box<dyn Model> {
+    let mut model = MemModel::default();
+    for (i, (title, importance)) in [
+        ("day 3 of conference", Importance::Normal),
+        ("presentation on teleportation", Importance::High),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        model.new_event(date(), i).unwrap();
+        model
+            .replace_event(
+                date(),
+                i,
+                Event {
+                    title: title.to_string(),
+                    importance,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    for (i, (title, completion_level)) in [
+        ("cook breakfast", CompletionLevel::Full),
+        ("change the world", CompletionLevel::Partial),
+        ("enjoy today", CompletionLevel::None),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        model.new_task(date(), i).unwrap();
+        model
+            .replace_task(
+                date(),
+                i,
+                Task {
+                    title: title.to_string(),
+                    completion_level,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    }
+    Box::new(model)
+}
+
+fn render(view: &mut View<TestBackend>) -> String {
+    view.render().unwrap();
+    view.terminal.backend().to_string()
+}
+
+#[test]
+fn empty_day() {
+    let mut view = view(Box::new(MemModel::default()), 60, 10);
+    assert_snapshot!(render(&mut view));
+}
+
+#[test]
+fn empty_day_with_background_text() {
+    let mut view = view(Box::new(MemModel::default()), 60, 10)
+        .background_text("entries will not be saved when you quit".to_string());
+    assert_snapshot!(render(&mut view));
+}
+
+#[test]
+fn day_with_entries() {
+    let mut view = view(journal(), 80, 10);
+    assert_snapshot!(render(&mut view));
+}
+
+#[test]
+fn help_menu() {
+    let mut view = view(journal(), 80, 43);
+    view.toggle_help();
+    view.move_down();
+    assert_snapshot!(render(&mut view));
+}
+
+#[test]
+fn editing_cursor_on_events() {
+    let mut view = view(journal(), 80, 10);
+    view.move_down();
+    view.toggle_editing_mode();
+    view.move_cursor_left();
+    render(&mut view);
+    assert_eq!(
+        view.terminal.get_cursor_position().unwrap(),
+        Position::new(1 + "presentation on teleportatio".len() as u16, 3)
+    );
+}
+
+#[test]
+fn editing_cursor_on_tasks() {
+    let mut view = view(journal(), 80, 10);
+    view.append_new_task();
+    for c in "rest".chars() {
+        view.insert_char(c);
+    }
+    assert_snapshot!(render(&mut view));
+    assert_eq!(
+        view.terminal.get_cursor_position().unwrap(),
+        Position::new(40 + 4 + 4, 5)
+    );
+}
+
+#[test]
+fn error_screen() {
+    let mut conn = establish_connection(Path::new(":memory:")).unwrap();
+    diesel::sql_query("DROP TABLE `events`")
+        .execute(&mut conn)
+        .unwrap();
+
+    let mut view = view(Box::new(SqliteModel::new(conn)), 60, 12);
+    assert!(!view.is_editing());
+    assert_snapshot!(render(&mut view));
+}
+
+#[test]
+fn narrow_terminal() {
+    let mut view = view(journal(), 30, 8);
+    assert_snapshot!(render(&mut view));
+}
+
+#[test]
+fn tag_browser() {
+    let mut model = journal();
+    model.new_task(date() + time::Duration::days(1), 0).unwrap();
+    model
+        .replace_task(
+            date() + time::Duration::days(1),
+            0,
+            Task {
+                title: "write up #conference notes".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    model
+        .replace_event(
+            date(),
+            0,
+            Event {
+                title: "day 3 of #conference".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let mut view = view(model, 80, 10);
+    view.toggle_tags();
+    assert_snapshot!(render(&mut view));
+
+    view.select();
+    view.move_down();
+    view.select();
+    assert!(view.screen.is_none());
+    assert_eq!(view.date, date() + time::Duration::days(1));
+}
+
+fn type_event(view: &mut View<TestBackend>, title: &str) {
+    view.append_new_event();
+    for c in title.chars() {
+        view.insert_char(c);
+    }
+    view.toggle_editing_mode();
+}
+
+#[test]
+fn inline_times_sort_events() {
+    let mut view = view(journal(), 80, 10);
+    type_event(&mut view, "9:15-10:00 standup");
+    type_event(&mut view, "8:00 coffee");
+
+    let events: Vec<Event> = view.model.events_iter(date()).collect();
+    let titles: Vec<&str> = events.iter().map(|x| x.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "day 3 of conference",
+            "presentation on teleportation",
+            "coffee",
+            "standup"
+        ]
+    );
+    assert_eq!(events[3].times().as_deref(), Some("09:15-10:00"));
+    assert_eq!(view.events_state.selected(), Some(2));
+    assert_snapshot!(render(&mut view));
+
+    view.toggle_editing_mode();
+    assert_eq!(view.get_editing_string().as_deref(), Some("08:00 coffee"));
+    assert_eq!(view.editing, Some("08:00 coffee".len()));
+}
+
+#[test]
+fn agenda() {
+    let mut view = view(journal(), 80, 20);
+    type_event(&mut view, "9:15-11:00 standup #work");
+    type_event(&mut view, "9:30 coffee");
+    type_event(&mut view, "19:00 dinner");
+    view.toggle_agenda();
+    view.move_down();
+    assert_snapshot!(render(&mut view));
+}
+
+fn type_task(view: &mut View<TestBackend>, title: &str) {
+    view.append_new_task();
+    for c in title.chars() {
+        view.insert_char(c);
+    }
+    view.toggle_editing_mode();
+}
+
+#[test]
+fn inline_due_dates() {
+    let mut view = view(Box::new(MemModel::default()), 80, 10);
+    type_task(&mut view, "pay rent due:2025-10-20");
+    let task = view.model.get_task(date(), 0).unwrap();
+    assert_eq!(task.title, "pay rent");
+    assert_eq!(task.due, Some(date() + time::Duration::days(6)));
+
+    view.toggle_editing_mode();
+    assert_eq!(
+        view.get_editing_string().as_deref(),
+        Some("pay rent due:2025-10-20")
+    );
+}
+
+#[test]
+fn editing_writes_only_changed_entries() {
+    let conn = establish_connection(Path::new(":memory:")).unwrap();
+    let mut view = view(Box::new(SqliteModel::new(conn)), 80, 10);
+    type_task(&mut view, "pay rent due:2025-10-20");
+    let history = view.model.task_history(date(), 0);
+
+    view.toggle_editing_mode();
+    view.exit_mode();
+    view.select();
+    view.select();
+    assert_eq!(view.model.task_history(date(), 0), history);
+
+    view.toggle_editing_mode();
+    view.insert_char('!');
+    assert_eq!(view.model.get_task(date(), 0).unwrap().title, "pay rent");
+    assert!(render(&mut view).contains("pay rent due:2025-10-20!"));
+    view.delete_char();
+    view.toggle_editing_mode();
+    assert_eq!(view.model.task_history(date(), 0), history);
+    let task = view.model.get_task(date(), 0).unwrap();
+    assert_eq!(task.title, "pay rent");
+    assert_eq!(task.due, Some(date() + time::Duration::days(6)));
+}
+
+#[test]
+fn due_tasks() {
+    let mut view = view(journal(), 80, 10);
+    view.move_to_prev();
+    type_task(&mut view, "file taxes due:2025-10-13");
+    type_task(&mut view, "book flights due:2025-10-16");
+    type_task(&mut view, "renew passport due:2026-01-01");
+    view.move_to_next();
+    type_task(&mut view, "call mom due:2025-10-14");
+    assert_snapshot!(render(&mut view));
+
+    view.toggle_due();
+    view.move_down();
+    assert_snapshot!("due_tasks_screen", render(&mut view));
+
+    view.select();
+    assert!(view.screen.is_none());
+    assert_eq!(view.date, date());
+}
+
+#[test]
+fn recurring_entries() {
+    let model = RecurringModel::new(Box::new(MemModel::default()));
+    let mut view = view(Box::new(model), 80, 10);
+    type_event(&mut view, "9:00 standup every:weekday");
+    type_task(&mut view, "water plants every:tue,fri count:3");
+
+    view.move_to_next();
+    view.move_to_next();
+    assert_snapshot!(render(&mut view));
+
+    view.toggle_recurring();
+    assert_snapshot!("recurring_entries_screen", render(&mut view));
+
+    view.delete();
+    view.toggle_recurring();
+    view.move_to_next();
+    assert_eq!(view.model.events_len(view.date), 0);
+    assert_eq!(view.model.tasks_len(view.date), 1);
+}
+
+#[test]
+fn overdue_tasks_are_red() {
+    let task = |completion_level, days| Task {
+        completion_level,
+        due: Some(date() + time::Duration::days(days)),
+        ..Default::default()
+    };
+    let due_style = |task| format_tasks(task, date()).spans.last().unwrap().style.fg;
+    assert_eq!(due_style(task(CompletionLevel::None, -1)), Some(Color::Red));
+    assert_eq!(
+        due_style(task(CompletionLevel::None, 0)),
+        Some(Color::DarkGray)
+    );
+    assert_eq!(
+        due_style(task(CompletionLevel::Full, -1)),
+        Some(Color::DarkGray)
+    );
+}
+
+#[test]
+fn highlights_tags() {
+    let spans = highlight_tags("#a b#c #d-e, f #".to_string(), Style::new());
+    let text: Vec<&str> = spans.iter().map(|x| x.content.as_ref()).collect();
+    assert_eq!(text, ["#a", " b#c ", "#d-e", ", f #"]);
+    assert_eq!(spans[2].style.fg, Some(Color::Cyan));
+    assert_eq!(spans[3].style.fg, None);
+}
+
+#[test]
+fn page_templates_fill_empty_days() {
+    let mut view = view(Box::new(MemModel::default()), 80, 10);
+    view.pages = crate::pages::parse(
+        "# Wednesday\n## Events\n- 10:00 team sync\n## Tasks\n- weekly review\n",
+    )
+    .unwrap();
+
+    view.move_to_prev();
+    view.move_to_next();
+    view.move_to_next();
+    assert_eq!(view.model.events_len(view.date), 1);
+    assert_eq!(view.model.tasks_len(view.date), 1);
+    assert_eq!(view.events_state.selected(), Some(0));
+
+    view.delete();
+    view.move_to_next();
+    view.move_to_prev();
+    assert_eq!(view.model.events_len(view.date), 0);
+
+    view.apply_page_templates();
+    assert_eq!(view.model.events_len(view.date), 1);
+    assert_eq!(view.model.tasks_len(view.date), 2);
+
+    view.move_to(date() - time::Duration::days(6));
+    assert_eq!(view.model.events_len(view.date), 0);
+
+    // a day that was filled once stays as it was left, even after a restart
+    let wednesday = date() + time::Duration::days(1);
+    view.move_to(wednesday);
+    view.delete();
+    view.delete();
+    view.delete();
+    let pages = view.pages.clone();
+    let model = std::mem::replace(
+        &mut view.model,
+        CachedModel::new(Box::new(MemModel::default())),
+    );
+    let mut restarted = self::view(Box::new(model), 80, 10);
+    restarted.pages = pages;
+    restarted.move_to(wednesday);
+    assert_eq!(restarted.model.events_len(wednesday), 0);
+    assert_eq!(restarted.model.tasks_len(wednesday), 0);
+}
+
+#[test]
+fn subtasks() {
+    let mut view = view(Box::new(MemModel::default()), 80, 10);
+    for title in ["plan trip", "book train", "pack", "socks", "water plants"] {
+        type_task(&mut view, title);
+    }
+    let depths = |view: &View<TestBackend>| -> Vec<u8> {
+        view.model.tasks_iter(date()).map(|x| x.depth).collect()
+    };
+
+    view.task_state.select(Some(0));
+    view.indent();
+    assert_eq!(depths(&view), [0, 0, 0, 0, 0]);
+    for (idx, times) in [(1, 1), (2, 1), (3, 2)] {
+        view.task_state.select(Some(idx));
+        for _ in 0..times {
+            view.indent();
+        }
+    }
+    assert_eq!(depths(&view), [0, 1, 1, 2, 0]);
+
+    view.task_state.select(Some(1));
+    view.cycle();
+    view.cycle();
+    assert_snapshot!(render(&mut view));
+
+    view.task_state.select(Some(2));
+    view.toggle_collapsed();
+    view.move_down();
+    assert_eq!(view.task_state.selected(), Some(4));
+    view.move_up();
+    assert_eq!(view.task_state.selected(), Some(2));
+    view.task_state.select(Some(0));
+    view.toggle_collapsed();
+    assert_snapshot!("subtasks_collapsed", render(&mut view));
+
+    view.move_down();
+    view.insert_new_item();
+    view.insert_char('x');
+    render(&mut view);
+    assert_eq!(
+        view.terminal.get_cursor_position().unwrap(),
+        Position::new(40 + 4 + 1, 3)
+    );
+    view.delete();
+    view.task_state.select(Some(0));
+
+    view.toggle_collapsed();
+    view.task_state.select(Some(2));
+    view.outdent();
+    assert_eq!(depths(&view), [0, 1, 0, 1, 0]);
+    view.delete();
+    assert_eq!(depths(&view), [0, 1, 0, 0]);
+}
+
+#[test]
+fn moving_entries() {
+    let mut view = view(journal(), 80, 10);
+    type_task(&mut view, "pack");
+    view.indent();
+    let titles = |view: &View<TestBackend>, date| -> Vec<String> {
+        view.model.tasks_iter(date).map(|x| x.title).collect()
+    };
+
+    view.task_state.select(Some(1));
+    view.shift_up();
+    assert_eq!(
+        titles(&view, date()),
+        ["change the world", "cook breakfast", "enjoy today", "pack"]
+    );
+    view.task_state.select(Some(2));
+    view.shift_down();
+    assert_eq!(view.task_state.selected(), Some(2));
+    view.shift_up();
+    assert_eq!(
+        titles(&view, date()),
+        ["change the world", "enjoy today", "pack", "cook breakfast"]
+    );
+
+    view.task_state.select(Some(1));
+    view.send_to_tomorrow();
+    assert_eq!(
+        titles(&view, date()),
+        ["change the world", "cook breakfast"]
+    );
+    assert_eq!(view.task_state.selected(), Some(1));
+    let tomorrow = date() + time::Duration::days(1);
+    let moved: Vec<Task> = view.model.tasks_iter(tomorrow).collect();
+    assert_eq!(moved[1].title, "pack");
+    assert_eq!(moved[1].depth, 1);
+
+    view.move_left();
+    view.shift_down();
+    view.toggle_moving();
+    view.move_to_prev();
+    view.move_to_prev();
+    assert_snapshot!(render(&mut view));
+    view.toggle_moving();
+    assert!(view.moving.is_empty());
+    assert_eq!(view.model.events_len(date()), 1);
+    assert_eq!(
+        view.model.get_event(view.date, 0).unwrap().title,
+        "presentation on teleportation"
+    );
+    assert_eq!(view.events_state.selected(), Some(0));
+}
+
+#[test]
+fn paste_stops_when_the_day_changed_underneath() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("journal.db");
+    let mut model = SqliteModel::new(establish_connection(&path).unwrap());
+    for (i, event) in journal().events_iter(date()).enumerate() {
+        model.new_event(date(), i).unwrap();
+        model.replace_event(date(), i, event).unwrap();
+    }
+    let mut view = view(Box::new(model), 80, 10);
+    view.move_left();
+    view.move_down();
+    view.yank();
+
+    diesel::sql_query("DELETE FROM `events`")
+        .execute(&mut establish_connection(&path).unwrap())
+        .unwrap();
+    view.paste(true);
+    assert_eq!(view.model.events_len(date()), 0);
+}
+
+#[test]
+fn yank_and_paste() {
+    let mut view = view(journal(), 80, 10);
+    type_task(&mut view, "pack");
+    view.indent();
+    view.move_up();
+    view.yank();
+    assert_eq!(view.register.len(), 2);
+
+    view.move_to_next();
+    view.paste(true);
+    let tomorrow: Vec<Task> = view.model.tasks_iter(view.date).collect();
+    assert_eq!(tomorrow.len(), 2);
+    assert_eq!(tomorrow[0].title, "enjoy today");
+    assert_eq!(tomorrow[1].depth, 1);
+
+    view.move_to_prev();
+    view.task_state.select(Some(0));
+    view.paste(true);
+    let titles: Vec<String> = view.model.tasks_iter(date()).map(|x| x.title).collect();
+    assert_eq!(
+        titles,
+        [
+            "cook breakfast",
+            "enjoy today",
+            "pack",
+            "change the world",
+            "enjoy today",
+            "pack"
+        ]
+    );
+    assert_eq!(view.task_state.selected(), Some(1));
+
+    view.move_left();
+    view.yank();
+    view.paste(false);
+    let events: Vec<Event> = view.model.events_iter(date()).collect();
+    assert_eq!(events.len(), 3);
+    assert_ne!(events[1].uid, events[2].uid);
+    assert_eq!(events[1].title, events[2].title);
+    assert_eq!(events[1].importance, Importance::High);
+    assert_eq!(view.events_state.selected(), Some(1));
+}
+
+#[test]
+fn marked_entries() {
+    let mut view = view(journal(), 80, 10);
+    type_task(&mut view, "pack");
+    view.indent();
+    let titles = |view: &View<TestBackend>, date| -> Vec<String> {
+        view.model.tasks_iter(date).map(|x| x.title).collect()
+    };
+    let mark = |view: &mut View<TestBackend>, event: Option<usize>, task: Option<usize>| {
+        view.events_state.select(event);
+        view.task_state.select(task);
+        view.toggle_mark();
+    };
+
+    mark(&mut view, Some(0), None);
+    mark(&mut view, None, Some(0));
+    mark(&mut view, None, Some(2));
+    mark(&mut view, None, Some(1));
+    mark(&mut view, None, Some(1));
+    assert_snapshot!(render(&mut view));
+
+    view.cycle_importance();
+    let important = |view: &View<TestBackend>| {
+        let events = view.model.events_iter(date()).map(|x| x.importance);
+        let tasks = view.model.tasks_iter(date()).map(|x| x.importance);
+        events.chain(tasks).collect::<Vec<_>>()
+    };
+    use Importance::{High, Normal};
+    assert_eq!(important(&view), [High, High, High, Normal, High, Normal]);
+    view.cycle_importance();
+    assert_eq!(
+        important(&view),
+        [Normal, High, Normal, Normal, Normal, Normal]
+    );
+    view.cycle();
+    assert_eq!(
+        view.model.get_task(date(), 0).unwrap().completion_level,
+        CompletionLevel::None
+    );
+
+    view.send_to_tomorrow();
+    assert!(view.marks.is_empty());
+    assert_eq!(titles(&view, date()), ["change the world"]);
+    assert_eq!(view.model.events_len(date()), 1);
+    let tomorrow = date() + time::Duration::days(1);
+    assert_eq!(
+        titles(&view, tomorrow),
+        ["cook breakfast", "enjoy today", "pack"]
+    );
+    assert_eq!(view.model.get_task(tomorrow, 2).unwrap().depth, 1);
+
+    view.move_to_next();
+    mark(&mut view, None, Some(0));
+    mark(&mut view, None, Some(2));
+    view.delete();
+    assert_eq!(titles(&view, tomorrow), ["enjoy today"]);
+    assert_eq!(view.task_state.selected(), Some(0));
+
+    mark(&mut view, Some(0), None);
+    mark(&mut view, None, Some(0));
+    view.toggle_moving();
+    assert_eq!(view.moving.len(), 2);
+    view.move_to_prev();
+    view.toggle_moving();
+    assert!(view.moving.is_empty());
+    assert_eq!(titles(&view, date()), ["change the world", "enjoy today"]);
+    assert_eq!(view.model.events_len(date()), 2);
+    assert_eq!(view.model.events_len(tomorrow), 0);
+
+    mark(&mut view, None, Some(0));
+    view.exit_mode();
+    assert!(view.marks.is_empty());
+}
+
+#[test]
+fn trash() {
+    let mut view = view(journal(), 80, 10);
+    view.delete();
+    view.task_state.select(Some(0));
+    view.events_state.select(None);
+    view.delete();
+    view.toggle_trash();
+    let screen = render(&mut view);
+    assert!(screen.contains("● cook breakfast (from 2025-10-14, deleted"));
+    assert!(screen.contains("day 3 of conference (from 2025-10-14, deleted"));
+
+    view.move_down();
+    view.select();
+    assert_eq!(
+        view.model.get_event(date(), 0).unwrap().title,
+        "day 3 of conference"
+    );
+    view.delete();
+    assert!(view.model.trash().is_empty());
+    assert!(render(&mut view).contains("the trash is empty"));
+    view.toggle_trash();
+    assert_eq!(view.model.tasks_len(date()), 2);
+}
+
+#[test]
+fn entry_details() {
+    let conn = establish_connection(Path::new(":memory:")).unwrap();
+    let mut view = view(Box::new(SqliteModel::new(conn)), 80, 16);
+    view.append_new_task();
+    for c in "wash".chars() {
+        view.insert_char(c);
+    }
+    view.exit_mode();
+    view.cycle();
+    view.cycle();
+    view.toggle_details();
+    let screen = render(&mut view);
+    assert!(screen.contains("created"));
+    assert!(screen.contains("completed"));
+
+    view.send_to_tomorrow();
+    view.move_to_next();
+    let screen = render(&mut view);
+    assert!(screen.contains("moved to 2025-10-15"));
+
+    view.exit_mode();
+    assert!(!render(&mut view).contains("Details"));
+}
+
+#[test]
+fn entry_details_without_history() {
+    let mut view = view(journal(), 80, 10);
+    view.toggle_details();
+    assert!(render(&mut view).contains("no history is kept for this entry"));
+}
+
+#[test]
+fn stats() {
+    let mut model = journal();
+    let yesterday = date().previous_day().unwrap();
+    model.new_task(yesterday, 0).unwrap();
+    model
+        .replace_task(
+            yesterday,
+            0,
+            Task {
+                title: "file #taxes".to_string(),
+                completion_level: CompletionLevel::Full,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let mut view = view(model, 80, 24);
+    view.toggle_stats();
+    assert_snapshot!(render(&mut view));
+
+    view.move_left();
+    let screen = render(&mut view);
+    assert!(screen.contains("2025-10-08 to 2025-10-14"));
+    view.move_left();
+    assert!(render(&mut view).contains("2025-10-08 to 2025-10-14"));
+}
+
+#[test]
+fn heatmap() {
+    let mut model = journal();
+    let earlier = Date::from_calendar_date(2025, Month::March, 3).unwrap();
+    model.new_event(earlier, 0).unwrap();
+    let mut view = view(model, 80, 16);
+    view.toggle_heatmap();
+    assert_snapshot!(render(&mut view));
+
+    view.indent();
+    assert!(render(&mut view).contains("2025-10-14: 1 completed task"));
+    for _ in 0..32 {
+        view.move_left();
+    }
+    view.move_up();
+    view.select();
+    assert!(view.screen.is_none());
+    assert_eq!(view.date, earlier);
+}
+
+#[test]
+fn habits() {
+    let conn = establish_connection(Path::new(":memory:")).unwrap();
+    let mut model = SqliteModel::new(conn);
+    model.add_habit("exercise").unwrap();
+    for offset in 1..=3 {
+        model
+            .set_habit_done("exercise", date() - time::Duration::days(offset), true)
+            .unwrap();
+    }
+    let mut view = view(Box::new(model), 80, 12);
+    view.toggle_habit(0);
+    view.toggle_habit(1);
+    assert_snapshot!("habits_day", render(&mut view));
+
+    view.toggle_habits();
+    view.insert_new_item();
+    assert!(view.is_editing());
+    for c in "read".chars() {
+        view.insert_char(c);
+    }
+    view.select();
+    assert!(!view.is_editing());
+    view.move_left();
+    view.cycle();
+    assert_snapshot!("habits_month", render(&mut view));
+    assert_eq!(
+        view.model.habit_days("read"),
+        BTreeSet::from([date() - time::Duration::days(1)])
+    );
+
+    view.move_up();
+    view.delete();
+    assert_eq!(view.model.habits(), ["read"]);
+}
+
+#[test]
+fn moods() {
+    let mut view = view(journal(), 80, 16);
+    for _ in 0..4 {
+        view.cycle_mood();
+    }
+    view.cycle_energy();
+    view.reflect();
+    assert!(view.is_editing());
+    for c in "good talks".chars() {
+        view.insert_char(c);
+    }
+    view.delete_char();
+    assert_snapshot!("moods_day", render(&mut view));
+    assert_eq!(
+        view.terminal.get_cursor_position().unwrap(),
+        Position::new(29 + "reflection: good talk".len() as u16, 15)
+    );
+    view.select();
+    assert!(!view.is_editing());
+    assert_eq!(
+        view.model.mood(date()),
+        Mood {
+            mood: Some(4),
+            energy: Some(1),
+            reflection: "good talk".to_string()
+        }
+    );
+
+    view.move_to_next();
+    view.cycle_mood();
+    view.cycle_mood();
+    view.toggle_moods();
+    assert_snapshot!("moods_month", render(&mut view));
+    view.move_left();
+    assert!(render(&mut view).contains("no moods this month"));
+}
+
+#[test]
+fn journal_pane() {
+    let mut view = view(journal(), 40, 12);
+    view.toggle_journal();
+    assert!(render(&mut view).contains("nothing written on this day yet"));
+    view.select();
+    assert!(view.is_editing());
+    for c in "A slow morning, then the talk went well.".chars() {
+        view.insert_char(c);
+    }
+    view.select();
+    view.select();
+    for c in "Tired.".chars() {
+        view.insert_char(c);
+    }
+    // moving between rows follows the wrapping of the last frame
+    render(&mut view);
+    view.move_up();
+    view.move_up();
+    for _ in 0..4 {
+        view.move_cursor_right();
+    }
+    for c in ", mostlyy".chars() {
+        view.insert_char(c);
+    }
+    view.delete_char();
+    assert_snapshot!(render(&mut view));
+    assert_eq!(
+        view.terminal.get_cursor_position().unwrap(),
+        Position::new(13, 3)
+    );
+
+    // nothing is saved until writing stops
+    assert_eq!(view.model.journal(date()), "");
+    view.exit_mode();
+    assert!(!view.is_editing());
+    assert_eq!(
+        view.model.journal(date()),
+        "A slow morning, then the talk went well, mostly.\n\nTired."
+    );
+    view.move_to_next();
+    assert!(render(&mut view).contains("nothing written on this day yet"));
+
+    view.select();
+    view.insert_char('!');
+    view.move_to_prev();
+    assert!(!view.is_editing());
+    assert_eq!(view.model.journal(date() + Duration::days(1)), "!");
+}
+
+#[test]
+fn journal_in_editor() {
+    fn reflow(path: &Path) -> std::io::Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        std::fs::write(path, format!("{}\nand more\n", text.replace('\n', " ")))
+    }
+    fn fail(_: &Path) -> std::io::Result<()> {
+        Err(std::io::Error::other("no such editor"))
+    }
+
+    let mut model = journal();
+    model.set_journal(date(), "one\ntwo").unwrap();
+    let mut view = view(model, 60, 10).external_editor(reflow);
+    view.open_in_editor();
+    assert_eq!(view.model.journal(date()), "one\ntwo");
+    view.toggle_journal();
+    view.open_in_editor();
+    assert_eq!(view.model.journal(date()), "one two\nand more");
+
+    view.editor = Some(fail);
+    view.open_in_editor();
+    assert!(render(&mut view).contains("cannot open the editor: no such editor"));
+    assert!(!render(&mut view).contains("cannot open the editor"));
+    assert_eq!(view.model.journal(date()), "one two\nand more");
+}
+
+#[test]
+fn search() {
+    let mut model = journal();
+    model
+        .set_journal(date() - Duration::days(1), "Packed for the conference.")
+        .unwrap();
+    let mut view = view(model, 60, 10);
+    view.move_to_next();
+    view.toggle_search();
+    assert!(view.is_editing());
+    for c in "conference".chars() {
+        view.insert_char(c);
+    }
+    view.select();
+    assert!(!view.is_editing());
+    assert_snapshot!(render(&mut view));
+
+    view.toggle_search();
+    assert!(view.is_editing());
+    view.delete_char();
+    view.select();
+    view.move_down();
+    view.select();
+    assert!(view.screen.is_none());
+    assert_eq!(view.date, date());
+
+    view.toggle_search();
+    for c in "PACKED".chars() {
+        view.insert_char(c);
+    }
+    view.select();
+    view.select();
+    assert!(matches!(view.screen, Some(Screen::Journal(_))));
+    assert_eq!(view.date, date() - Duration::days(1));
+
+    view.toggle_search();
+    for c in "nowhere".chars() {
+        view.insert_char(c);
+    }
+    view.select();
+    assert!(render(&mut view).contains("nothing found"));
+}
+
+#[test]
+fn entry_in_editor() {
+    fn retime(path: &Path) -> std::io::Result<()> {
+        assert_eq!(std::fs::read_to_string(path)?, "day 3 of conference");
+        std::fs::write(path, "14:00 day 3 of conference\n\n  and the dinner\n")
+    }
+    fn postpone(path: &Path) -> std::io::Result<()> {
+        let title = std::fs::read_to_string(path)?;
+        std::fs::write(path, format!("{title} due:2025-10-20\n"))
+    }
+    fn fail(_: &Path) -> std::io::Result<()> {
+        Err(std::io::Error::other("no such editor"))
+    }
+
+    let mut view = view(journal(), 80, 10).external_editor(retime);
+    let events: Vec<Event> = view.model.events_iter(date()).collect();
+    view.open_in_editor();
+    assert!(view.model.events_iter(date()).eq(events));
+    view.edit_in_editor();
+    assert!(!view.is_editing());
+    let event = view.model.get_event(date(), 1).unwrap();
+    assert_eq!(event.title, "day 3 of conference and the dinner");
+    assert_eq!(event.start, crate::model::parse_time("14:00"));
+    assert_eq!(view.events_state.selected(), Some(1));
+
+    let mut view = self::view(journal(), 80, 10).external_editor(postpone);
+    view.move_right();
+    view.task_state.select(Some(1));
+    view.edit_in_editor();
+    let task = view.model.get_task(date(), 1).unwrap();
+    assert_eq!(task.title, "change the world");
+    assert_eq!(task.due, Some(date() + Duration::days(6)));
+
+    let mut view = self::view(journal(), 80, 10).external_editor(fail);
+    view.move_right();
+    let tasks: Vec<Task> = view.model.tasks_iter(date()).collect();
+    view.edit_in_editor();
+    assert!(view.model.tasks_iter(date()).eq(tasks));
+    assert!(render(&mut view).contains("cannot open the editor: no such editor"));
+}
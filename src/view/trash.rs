@@ -0,0 +1,99 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{layout::Flex, symbols::border};
+use time::Date;
+
+use super::{format_events, format_tasks};
+use crate::model::{Entry, Model, Trashed};
+
+/// Lists the entries in the trash, most recently deleted first.
+pub struct TrashList {
+    /// in the order they were deleted, the reverse of the order shown
+    trash: Vec<Trashed>,
+    state: ListState,
+}
+
+impl TrashList {
+    pub fn new(model: &dyn Model) -> Self {
+        let trash = model.trash();
+        Self {
+            state: ListState::default().with_selected((!trash.is_empty()).then_some(0)),
+            trash,
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn move_down(&mut self) {
+        self.state.select_next();
+    }
+
+    /// Puts the selected entry back on its day, returning where it was
+    /// deleted from.
+    pub fn restore(&mut self, model: &mut dyn Model) -> Option<Date> {
+        let trashed = self.take_selected(|idx| model.restore(idx))?;
+        Some(trashed.date)
+    }
+
+    /// Deletes the selected entry for good.
+    pub fn purge(&mut self, model: &mut dyn Model) {
+        self.take_selected(|idx| model.purge(idx));
+    }
+
+    /// Removes the selected entry from the list if `f` succeeds on its index
+    /// in the trash.
+    fn take_selected(&mut self, f: impl FnOnce(usize) -> anyhow::Result<()>) -> Option<Trashed> {
+        let shown = self.state.selected()?;
+        let idx = self.trash.len().checked_sub(shown + 1)?;
+        f(idx).ok()?;
+        let trashed = self.trash.remove(idx);
+        if self.trash.is_empty() {
+            self.state.select(None);
+        } else if shown == self.trash.len() {
+            self.state.select(Some(shown - 1));
+        }
+        Some(trashed)
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, today: Date) {
+        if self.trash.is_empty() {
+            let [text_area] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(area);
+            frame.render_widget(Paragraph::new("the trash is empty").centered(), text_area);
+            return;
+        }
+
+        let block = Block::bordered()
+            .title(Line::from(" Trash ".magenta().bold()).centered())
+            .title_bottom(
+                Line::from(" <ENTER> to restore, <d> to delete for good ".gray()).centered(),
+            )
+            .border_set(border::ROUNDED);
+        let widget = self
+            .trash
+            .iter()
+            .rev()
+            .map(|trashed| {
+                let mut line = match trashed.entry.clone() {
+                    Entry::Event(event) => {
+                        let mut line = format_events(event);
+                        line.spans.insert(0, "   ".into());
+                        line
+                    }
+                    Entry::Task(task) => format_tasks(task, today),
+                };
+                line.spans.push(
+                    format!(" (from {}, deleted {})", trashed.date, trashed.deleted).dark_gray(),
+                );
+                ListItem::new(line)
+            })
+            .collect::<List>()
+            .block(block)
+            .highlight_style(Style::new().fg(Color::Magenta));
+
+        frame.render_stateful_widget(widget, area, &mut self.state);
+    }
+}